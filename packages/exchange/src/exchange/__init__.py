@@ -1,9 +1,11 @@
 """Classes for interacting with the exchange API."""
 
-from exchange.tool import Tool  # noqa
+from exchange.tool import Tool, ToolChoice  # noqa
 from exchange.content import Text, ToolResult, ToolUse  # noqa
 from exchange.message import Message  # noqa
-from exchange.exchange import Exchange  # noqa
+from exchange.exchange import CompletionTimeoutError, Exchange  # noqa
+from exchange.reply_handler import ReplyHandler  # noqa
 from exchange.checkpoint import CheckpointData, Checkpoint  # noqa
+from exchange.tool_error import ToolError, ToolErrorCategory  # noqa
 
 module_name = "ai-exchange"
@@ -3,6 +3,8 @@ from typing import Optional
 import json
 from attrs import define, asdict
 
+from exchange.tool_error import ToolErrorCategory
+
 
 CONTENT_TYPES = {}
 
@@ -45,6 +47,12 @@ class ToolResult(Content):
     tool_use_id: str
     output: str
     is_error: bool = False
+    error_category: Optional[str] = None
+
+    @property
+    def retryable(self) -> bool:
+        """Whether the call failed in a way that may succeed if it is made again, such as a timeout"""
+        return self.error_category is not None and ToolErrorCategory(self.error_category).retryable
 
     @property
     def summary(self) -> str:
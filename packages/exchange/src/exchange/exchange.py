@@ -1,20 +1,40 @@
 import json
+import time
 import traceback
+from concurrent.futures import ThreadPoolExecutor
+from concurrent.futures import TimeoutError as FuturesTimeoutError
 from copy import deepcopy
-from typing import Mapping
+from threading import Event
+from typing import Callable, Mapping, Optional
 
 from attrs import Factory, define, evolve, field
 from tiktoken import get_encoding
 
 from exchange.checkpoint import Checkpoint, CheckpointData
 from exchange.content import Text, ToolResult, ToolUse
-from exchange.message import Message
+from exchange.message import EmptyResponseError, Message
 from exchange.moderators import Moderator
 from exchange.moderators.truncate import ContextTruncate
 from exchange.observers import observe_wrapper
 from exchange.providers import Provider, Usage
+from exchange.providers.utils import strip_images
+from exchange.reply_handler import ReplyHandler
 from exchange.token_usage_collector import _token_usage_collector
-from exchange.tool import Tool
+from exchange.tool import Tool, ToolChoice
+from exchange.tool_error import ToolError, ToolErrorCategory, classify_error
+from exchange.utils import coerce_parameters
+
+
+# added to the system prompt when asking again after an empty response
+EMPTY_RESPONSE_NUDGE = "Your last reply was empty. Reply to the last message with text, or call a tool."
+
+
+class CompletionTimeoutError(TimeoutError):
+    """The provider did not return a completion within the exchange's completion_timeout"""
+
+    def __init__(self, timeout: float) -> None:
+        self.timeout = timeout
+        super().__init__(f"The provider did not respond within {timeout:g} seconds")
 
 
 def validate_tool_output(output: str) -> None:
@@ -46,6 +66,24 @@ class Exchange:
     messages: list[Message] = field(factory=list)
     checkpoint_data: CheckpointData = field(factory=CheckpointData)
     generation_args: dict = field(default=Factory(dict))
+    # the usage of every generation, shared with the copies made by .replace so it totals the whole session
+    usage: Usage = field(factory=lambda: Usage(0, 0, 0))
+    # call identical tool uses within one message only once, off by default as a repeat can be intended
+    deduplicate_tool_calls: bool = False
+    # called after every tool call with the tool use, its result and how long the call took in seconds
+    tool_call_listeners: list[Callable[[ToolUse, ToolResult, float], None]] = field(factory=list)
+    # called with the number of messages dropped whenever the moderator trims the history to fit the context
+    context_trim_listeners: list[Callable[[int], None]] = field(factory=list)
+    # give up on a completion after this many seconds with CompletionTimeoutError, the request is abandoned
+    # rather than cancelled, so this is for early feedback when the client's own timeout is set high
+    completion_timeout: Optional[float] = None
+    # call a read-only tool that timed out once more, with twice its numeric timeout argument if it takes one,
+    # while a tool that changes state is never called again and the model is told it can retry it instead
+    retry_timed_out_tools: bool = False
+    # generate this many candidates for every message and keep the first, or the one sample_scorer scores highest,
+    # while the moderators' own completions, such as the token count probe, are only generated once
+    samples: int = 1
+    sample_scorer: Optional[Callable[[Message], float]] = None
 
     @property
     def _toolmap(self) -> Mapping[str, Tool]:
@@ -65,22 +103,42 @@ class Exchange:
             )
         return evolve(self, **kwargs)
 
+    def report_trimmed(self, dropped: int) -> None:
+        """Tell the context trim listeners that the moderator dropped this many messages"""
+        for listener in self.context_trim_listeners:
+            listener(dropped)
+
     def add(self, message: Message) -> None:
         """Add a message to the history."""
         if self.messages and message.role == self.messages[-1].role:
             raise ValueError("Messages in the exchange must alternate between user and assistant")
         self.messages.append(message)
 
-    def generate(self) -> Message:
-        """Generate the next message."""
+    def generate(
+        self, tool_choice: Optional[ToolChoice] = None, completion_timeout: Optional[float] = None
+    ) -> Message:
+        """Generate the next message.
+
+        Args:
+            tool_choice: Constrain which tools the model may call in this message, for providers that support it
+            completion_timeout: Give up on this completion after this many seconds, in place of the exchange's
+        """
+        if tool_choice is not None and tool_choice.name and tool_choice.name not in self._toolmap:
+            raise ValueError(f"The tool choice {tool_choice.name} is not one of the available tools")
+        # only pass a choice when there is one, so providers that do not support it are unaffected
+        choice_args = {"tool_choice": tool_choice} if tool_choice is not None else {}
         self.moderator.rewrite(self)
-        message, usage = self.provider.complete(
-            self.model,
-            self.system,
-            messages=self.messages,
-            tools=self.tools,
-            **self.generation_args,
-        )
+        try:
+            message, usage = self._complete(self.system, choice_args, completion_timeout)
+        except EmptyResponseError as e:
+            # providers occasionally return nothing at all, which would end the reply with nothing
+            # to show for it, so we ask once more before giving up
+            self.usage.add(e.usage)
+            if e.usage is not None:
+                _token_usage_collector.collect(self.model, e.usage)
+            message, usage = self._complete(f"{self.system}\n\n{EMPTY_RESPONSE_NUDGE}", choice_args, completion_timeout)
+        # accumulate before anything else can fail, so the tokens we paid for are always counted
+        self.usage.add(usage)
         self.add(message)
         self.add_checkpoints_from_usage(usage)  # this has to come after adding the response
 
@@ -93,7 +151,52 @@ class Exchange:
         _token_usage_collector.collect(self.model, usage)
         return message
 
-    def reply(self, max_tool_use: int = 128) -> Message:
+    def _complete(
+        self, system: str, choice_args: dict[str, ToolChoice], completion_timeout: Optional[float] = None
+    ) -> tuple[Message, Usage]:
+        # only send the provider what it accepts, e.g. no images to a model that can't read them
+        capabilities = self.provider.capabilities()
+        messages = self.messages if capabilities.images else strip_images(self.messages)
+        tools = self.tools if capabilities.tools else ()
+        if not (capabilities.tools and capabilities.tool_choice):
+            choice_args = {}
+        kwargs = dict(messages=messages, tools=tools, **choice_args, **self.generation_args)
+        timeout = completion_timeout if completion_timeout is not None else self.completion_timeout
+        if timeout is None:
+            return self._sample(system, kwargs)
+
+        executor = ThreadPoolExecutor(max_workers=1)
+        future = executor.submit(self._sample, system, kwargs)
+        try:
+            return future.result(timeout=timeout)
+        except FuturesTimeoutError:
+            if future.done():
+                # the completion finished just as we gave up, or the provider raised a TimeoutError of its own
+                exception = future.exception()
+                if exception is None:
+                    return future.result()
+                raise exception
+            raise CompletionTimeoutError(timeout)
+        finally:
+            executor.shutdown(wait=False)
+
+    def _sample(self, system: str, kwargs: dict[str, any]) -> tuple[Message, Usage]:
+        if self.samples <= 1:
+            return self.provider.complete(self.model, system, **kwargs)
+
+        candidates, usage = self.provider.complete_n(self.model, system, n=self.samples, **kwargs)
+        if self.sample_scorer is None:
+            return candidates[0], usage
+        # max keeps the first of the candidates tied for the highest score
+        return max(candidates, key=self.sample_scorer), usage
+
+    def reply(
+        self,
+        max_tool_use: int = 128,
+        cancel: Optional[Event] = None,
+        tool_choice: Optional[ToolChoice] = None,
+        handler: Optional[ReplyHandler] = None,
+    ) -> Message:
         """Get the reply from the underlying model.
 
         This will process any requests for tool calls, calling them immediately, and
@@ -102,18 +205,34 @@ class Exchange:
 
         Args:
             max_tool_use: The maximum number of tool calls to make before returning. Defaults to 128.
+            cancel: An event that can be set from another thread to stop the reply after the
+                current generation. Any pending tool calls are answered with an error instead of run.
+            tool_choice: Constrain which tools the model may call in its first message, e.g. to make it
+                search before anything else. The messages after that are generated without constraint.
+            handler: Receives the text, tool calls and tool results of the reply as they happen.
         """
         if max_tool_use <= 0:
             raise ValueError("max_tool_use must be greater than 0")
-        response = self.generate()
+        handler = handler or ReplyHandler()
+        response = self.generate(tool_choice=tool_choice)
         curr_iter = 1  # generate() already called once
         while response.tool_use:
-            content = []
+            if response.text:
+                handler.on_text(response.text)
             for tool_use in response.tool_use:
-                tool_result = self.call_function(tool_use)
-                content.append(tool_result)
+                handler.on_tool_request(tool_use)
+            content = self.call_functions(response.tool_use, cancel=cancel)
+            for tool_result in content:
+                handler.on_tool_response(tool_result)
             self.add(Message(role="user", content=content))
 
+            if cancel is not None and cancel.is_set():
+                response = Message.assistant(
+                    "We've stopped executing additional tool calls because the reply was cancelled",
+                )
+                self.add(response)
+                break
+
             # We've reached the limit of tool calls - break out of the loop
             if curr_iter >= max_tool_use:
                 # At this point, the most recent message is `Message(role='user', content=ToolResult(...))`
@@ -126,8 +245,78 @@ class Exchange:
                 response = self.generate()
                 curr_iter += 1
 
+        if response.text:
+            handler.on_text(response.text)
+        handler.on_finish(response)
         return response
 
+    def call_functions(
+        self, tool_uses: list[ToolUse], cancel: Optional[Event] = None, cancel_tool_on_interrupt: bool = False
+    ) -> list[ToolResult]:
+        """Call the functions for every tool use requested in a single message
+
+        When deduplicate_tool_calls is set, repeated requests with the same name and parameters
+        are only called once, and each repeat gets a copy of the first result under its own id.
+
+        Args:
+            tool_uses: The tool uses to call, in order
+            cancel: An event that, once set, answers the remaining tool uses with an error instead
+            cancel_tool_on_interrupt: Answer the tool running when a KeyboardInterrupt arrives with a
+                cancelled error and carry on with the rest. A second interrupt in the same call, or one
+                after cancel is set, is raised as usual to stop the whole reply.
+        """
+        results = []
+        called = {}
+        cancelled_one = False
+        for tool_use in tool_uses:
+            key = (tool_use.name, json.dumps(tool_use.parameters, sort_keys=True, default=str))
+            if cancel is not None and cancel.is_set():
+                tool_result = ToolResult(
+                    tool_use_id=tool_use.id,
+                    output="The reply was cancelled before this tool was called",
+                    is_error=True,
+                )
+            elif self.deduplicate_tool_calls and key in called:
+                tool_result = evolve(called[key], tool_use_id=tool_use.id)
+            else:
+                start = time.perf_counter()
+                try:
+                    tool_result = self.call_function(tool_use)
+                    if self._should_retry(tool_use, tool_result):
+                        tool_result = self.call_function(self._with_longer_timeout(tool_use))
+                except KeyboardInterrupt:
+                    if not cancel_tool_on_interrupt or cancelled_one or (cancel is not None and cancel.is_set()):
+                        raise
+                    cancelled_one = True
+                    tool_result = ToolResult(
+                        tool_use_id=tool_use.id,
+                        output=f"The user cancelled this call to {tool_use.name}, the other tool calls went ahead",
+                        is_error=True,
+                        error_category=ToolErrorCategory.CANCELLED,
+                    )
+                duration = time.perf_counter() - start
+                for listener in self.tool_call_listeners:
+                    listener(tool_use, tool_result, duration)
+                called[key] = tool_result
+            results.append(tool_result)
+        return results
+
+    def _should_retry(self, tool_use: ToolUse, tool_result: ToolResult) -> bool:
+        tool = self._toolmap.get(tool_use.name)
+        return (
+            self.retry_timed_out_tools
+            and tool is not None
+            and tool.reads_only(tool_use.parameters)
+            and tool_result.error_category == ToolErrorCategory.TIMEOUT
+        )
+
+    def _with_longer_timeout(self, tool_use: ToolUse) -> ToolUse:
+        """The same tool use, with its timeout argument doubled if it has one"""
+        timeout = tool_use.parameters.get("timeout") if isinstance(tool_use.parameters, dict) else None
+        if not isinstance(timeout, (int, float)) or isinstance(timeout, bool):
+            return tool_use
+        return evolve(tool_use, parameters={**tool_use.parameters, "timeout": timeout * 2})
+
     @observe_wrapper()
     def call_function(self, tool_use: ToolUse) -> ToolResult:
         """Call the function indicated by the tool use"""
@@ -138,20 +327,25 @@ class Exchange:
 
             if tool_use.is_error:
                 output += f"\n{tool_use.error_message}"
+                error_category = ToolErrorCategory.INVALID_ARGS
             elif tool is None:
                 valid_tool_names = ", ".join(self._toolmap.keys())
                 output += f"\nNo tool exists with the name '{tool_use.name}'. Valid tool names are: {valid_tool_names}"
+                error_category = ToolErrorCategory.NOT_FOUND
 
-            return ToolResult(tool_use_id=tool_use.id, output=output, is_error=True)
+            return ToolResult(tool_use_id=tool_use.id, output=output, is_error=True, error_category=error_category)
 
+        error_category = None
         try:
             if isinstance(tool_use.parameters, dict):
-                output = json.dumps(tool.function(**tool_use.parameters))
+                parameters = coerce_parameters(tool_use.parameters, tool.parameters)
+                output = json.dumps(tool.function(**parameters))
             elif isinstance(tool_use.parameters, list):
                 output = json.dumps(tool.function(*tool_use.parameters))
             else:
-                raise ValueError(
-                    f"The provided tool parameters, {tool_use.parameters} could not be interpreted as a mapping of arguments."  # noqa: E501
+                raise ToolError(
+                    f"The provided tool parameters, {tool_use.parameters} could not be interpreted as a mapping of arguments.",  # noqa: E501
+                    ToolErrorCategory.INVALID_ARGS,
                 )
 
             validate_tool_output(output)
@@ -161,8 +355,12 @@ class Exchange:
             tb = traceback.format_exc()
             output = str(tb) + "\n" + str(e)
             is_error = True
+            error_category = classify_error(e)
+            if error_category is not None:
+                retryable = ", retryable" if error_category.retryable else ""
+                output += f"\nerror category: {error_category.value}{retryable}. {error_category.hint}"
 
-        return ToolResult(tool_use_id=tool_use.id, output=output, is_error=is_error)
+        return ToolResult(tool_use_id=tool_use.id, output=output, is_error=is_error, error_category=error_category)
 
     def add_tool_use(self, tool_use: ToolUse) -> None:
         """Manually add a tool use and corresponding result
@@ -1,16 +1,41 @@
 import inspect
 import time
 from pathlib import Path
-from typing import Literal
+from typing import TYPE_CHECKING, Literal, Optional
 
 from attrs import define, field
-from jinja2 import Environment, FileSystemLoader
+from jinja2 import ChoiceLoader, DictLoader, Environment, FileSystemLoader
 
 from exchange.content import CONTENT_TYPES, Content, Text, ToolResult, ToolUse
 from exchange.utils import create_object_id
 
+if TYPE_CHECKING:
+    from exchange.providers.base import Usage
+
 Role = Literal["user", "assistant"]
 
+# Templates that Message.load checks, in order, before the directory of the caller
+REGISTERED_TEMPLATES: dict[str, str] = {}
+TEMPLATE_SEARCH_PATHS: list[Path] = []
+
+
+def register_template(name: str, content: str) -> None:
+    """Register a template by name, which takes precedence over any template file with that name"""
+    REGISTERED_TEMPLATES[name] = content
+
+
+def add_template_search_path(path: Path) -> None:
+    """Add a directory whose templates take precedence over the ones next to the calling code"""
+    if path not in TEMPLATE_SEARCH_PATHS:
+        TEMPLATE_SEARCH_PATHS.append(path)
+
+
+class EmptyResponseError(ValueError):
+    """An assistant message with neither text nor tool use, which providers occasionally return"""
+
+    # the usage of the response that was empty, set by the provider once it knows it
+    usage: Optional["Usage"] = None
+
 
 def validate_role_and_content(instance: "Message", *_: any) -> None:  # noqa: ANN401
     if instance.role == "user":
@@ -20,7 +45,7 @@ def validate_role_and_content(instance: "Message", *_: any) -> None:  # noqa: AN
             raise ValueError("User message does not support ToolUse")
     elif instance.role == "assistant":
         if not (instance.text or instance.tool_use):
-            raise ValueError("Assistant message must include a Text or ToolUsage")
+            raise EmptyResponseError("Assistant message must include a Text or ToolUsage")
         if instance.tool_result:
             raise ValueError("Assistant message does not support ToolResult")
 
@@ -100,13 +125,23 @@ class Message:
         if you have a directory of code that contains example.py, and example.py has
         a function that calls User.load('example.jinja'), it will look in the same
         directory as example.py for the jinja file.
+
+        Templates added through register_template, and then those found in TEMPLATE_SEARCH_PATHS,
+        are used in place of the file next to the calling code, so they can be customized.
         """
         frm = inspect.stack()[1]
         mod = inspect.getmodule(frm[0])
 
         base_path = Path(mod.__file__).parent
 
-        env = Environment(loader=FileSystemLoader(base_path))
+        loader = ChoiceLoader(
+            [
+                DictLoader(REGISTERED_TEMPLATES),
+                FileSystemLoader(TEMPLATE_SEARCH_PATHS),
+                FileSystemLoader(base_path),
+            ]
+        )
+        env = Environment(loader=loader)
         template = env.get_template(filename)
         rendered_content = template.render(**kwargs)
 
@@ -26,6 +26,7 @@ class ContextSummarizer(ContextTruncate):
             model=self.model if self.model else exchange.model,
             messages=messages_to_summarize,
             checkpoint_data=CheckpointData(),
+            samples=1,
         )
 
         # get the summarized content and the tokens associated with this content
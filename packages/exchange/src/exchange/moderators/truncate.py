@@ -38,6 +38,8 @@ class ContextTruncate(Moderator):
         messages_to_remove = self._get_messages_to_remove(exchange)
         for _ in range(len(messages_to_remove)):
             exchange.pop_first_message()
+        if messages_to_remove:
+            exchange.report_trimmed(len(messages_to_remove))
 
     def _update_system_prompt_token_count(self, exchange: Exchange) -> None:
         is_different_system_prompt = False
@@ -54,6 +56,7 @@ class ContextTruncate(Moderator):
                 checkpoint_data=CheckpointData(),
                 moderator=PassiveModerator(),
                 model=self.model if self.model else exchange.model,
+                samples=1,
             )
             _system_token_exchange.generate()
             last_system_prompt_token_count = self.system_prompt_token_count
@@ -2,13 +2,16 @@ from functools import cache
 
 from exchange.invalid_choice_error import InvalidChoiceError
 from exchange.providers.anthropic import AnthropicProvider  # noqa
-from exchange.providers.base import Provider, Usage  # noqa
+from exchange.providers.base import Provider, ProviderCapabilities, Usage  # noqa
 from exchange.providers.databricks import DatabricksProvider  # noqa
 from exchange.providers.openai import OpenAiProvider  # noqa
 from exchange.providers.ollama import OllamaProvider  # noqa
 from exchange.providers.groq import GroqProvider  # noqa
 from exchange.providers.azure import AzureProvider  # noqa
 from exchange.providers.google import GoogleProvider  # noqa
+from exchange.providers.fallback import FallbackProvider  # noqa
+from exchange.providers.caching import CachingProvider  # noqa
+from exchange.providers.replay import ReplayProvider  # noqa
 
 from exchange.utils import load_plugins
 
@@ -1,12 +1,22 @@
 import os
+from typing import Optional
 
 import httpx
 
 from exchange import Message, Tool
 from exchange.content import Text, ToolResult, ToolUse
-from exchange.providers.base import Provider, Usage
+from exchange.providers.base import Provider, ProviderCapabilities, Usage, usage_of_empty_response
 from tenacity import retry, wait_fixed, stop_after_attempt
-from exchange.providers.utils import retry_if_status, raise_for_status
+from exchange.providers.utils import (
+    EVENT_HOOKS,
+    encode_image,
+    image_media_type,
+    is_image_result,
+    normalize_messages,
+    raise_for_status,
+    record_payload_sizes,
+    retry_if_status,
+)
 from exchange.observers import observe_wrapper
 
 ANTHROPIC_HOST = "https://api.anthropic.com/v1/messages"
@@ -24,6 +34,9 @@ class AnthropicProvider(Provider):
 
     PROVIDER_NAME = "anthropic"
     REQUIRED_ENV_VARS = ["ANTHROPIC_API_KEY"]
+    # the API refuses images over 5 MB
+    MAX_IMAGE_BYTES = 5 * 1024 * 1024
+    CAPABILITIES = ProviderCapabilities(tools=True, images=True, tool_choice=False)
 
     def __init__(self, client: httpx.Client) -> None:
         self.client = client
@@ -41,6 +54,7 @@ class AnthropicProvider(Provider):
                 "anthropic-version": "2023-06-01",
             },
             timeout=httpx.Timeout(60 * 10),
+            event_hooks=EVENT_HOOKS,
         )
         return cls(client)
 
@@ -89,14 +103,16 @@ class AnthropicProvider(Provider):
         ]
 
     @staticmethod
-    def messages_to_anthropic_spec(messages: list[Message]) -> list[dict[str, any]]:
+    def messages_to_anthropic_spec(
+        messages: list[Message], max_image_bytes: Optional[int] = None
+    ) -> list[dict[str, any]]:
         messages_spec = []
         # if messages is empty - just make a default
-        for message in messages:
+        for message in normalize_messages(messages):
             converted = {"role": message.role}
             for content in message.content:
                 if isinstance(content, Text):
-                    converted["content"] = [{"type": "text", "text": content.text}]
+                    converted.setdefault("content", []).append({"type": "text", "text": content.text})
                 elif isinstance(content, ToolUse):
                     converted.setdefault("content", []).append(
                         {
@@ -107,11 +123,25 @@ class AnthropicProvider(Provider):
                         }
                     )
                 elif isinstance(content, ToolResult):
+                    output = content.output
+                    if is_image_result(content):
+                        image_path = content.output.replace('"image:', "").replace('"', "")
+                        encoded_image = encode_image(image_path, max_image_bytes)
+                        output = [
+                            {
+                                "type": "image",
+                                "source": {
+                                    "type": "base64",
+                                    "media_type": image_media_type(encoded_image),
+                                    "data": encoded_image,
+                                },
+                            }
+                        ]
                     converted.setdefault("content", []).append(
                         {
                             "type": "tool_result",
                             "tool_use_id": content.tool_use_id,
-                            "content": content.output,
+                            "content": output,
                         }
                     )
             messages_spec.append(converted)
@@ -141,19 +171,24 @@ class AnthropicProvider(Provider):
                 unique_tools.append(tool)
                 tools_set.add(tool.name)
 
+        # anthropic takes the stop sequences that other providers call stop as stop_sequences
+        if "stop" in kwargs:
+            kwargs["stop_sequences"] = kwargs.pop("stop")
         payload = dict(
             system=system,
             model=model,
             max_tokens=4096,
-            messages=self.messages_to_anthropic_spec(messages),
+            messages=self.messages_to_anthropic_spec(messages, self.MAX_IMAGE_BYTES),
             tools=self.tools_to_anthropic_spec(tuple(unique_tools)),
-            **kwargs,
         )
         payload = {k: v for k, v in payload.items() if v}
+        payload.update({k: v for k, v in kwargs.items() if v is not None})
 
-        response = self._post(payload)
-        message = self.anthropic_response_to_message(response)
-        usage = self.get_usage(response)
+        http_response = self._post(payload)
+        response = http_response.json()
+        usage = record_payload_sizes(self.get_usage(response), http_response)
+        with usage_of_empty_response(usage):
+            message = self.anthropic_response_to_message(response)
 
         return message, usage
 
@@ -165,4 +200,4 @@ class AnthropicProvider(Provider):
     @retry_procedure
     def _post(self, payload: dict) -> httpx.Response:
         response = self.client.post(ANTHROPIC_HOST, json=payload)
-        return raise_for_status(response).json()
+        return raise_for_status(response)
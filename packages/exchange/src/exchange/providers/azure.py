@@ -1,7 +1,10 @@
 import httpx
 import os
+from typing import Optional
 
 from exchange.providers import OpenAiProvider
+from exchange.providers.base import ACCOUNT_INFO_TIMEOUT, AccountInfo, Provider
+from exchange.providers.utils import EVENT_HOOKS
 
 
 class AzureProvider(OpenAiProvider):
@@ -15,8 +18,9 @@ class AzureProvider(OpenAiProvider):
         "AZURE_CHAT_COMPLETIONS_KEY",
     ]
 
-    def __init__(self, client: httpx.Client) -> None:
+    def __init__(self, client: httpx.Client, deployment_name: Optional[str] = None) -> None:
         super().__init__(client)
+        self.deployment_name = deployment_name
 
     @classmethod
     def from_env(cls: type["AzureProvider"]) -> "AzureProvider":
@@ -33,5 +37,16 @@ class AzureProvider(OpenAiProvider):
             headers={"api-key": key, "Content-Type": "application/json"},
             params={"api-version": api_version},
             timeout=httpx.Timeout(60 * 10),
+            event_hooks=EVENT_HOOKS,
         )
-        return cls(client)
+        return cls(client, deployment_name)
+
+    def account_info(self, timeout: float = ACCOUNT_INFO_TIMEOUT) -> AccountInfo:
+        # a deployment has no models listing to validate the key against
+        raise NotImplementedError(f"The {self.name} provider does not report account info")
+
+    def fetch_models(self) -> list[str]:
+        # a deployment serves the one model it was created for, and has no models listing
+        if self.deployment_name:
+            return [self.deployment_name]
+        return Provider.fetch_models(self)
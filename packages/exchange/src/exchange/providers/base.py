@@ -1,17 +1,82 @@
 import os
+import time
 from abc import ABC, abstractmethod
+from concurrent.futures import ThreadPoolExecutor
+from contextlib import contextmanager
 from attrs import define, field
-from typing import Optional
+from typing import Iterator, Optional
 
-from exchange.message import Message
+from exchange.message import EmptyResponseError, Message
 from exchange.tool import Tool
 
+# account info is best effort, so a slow provider shouldn't hold up whoever asked for it
+ACCOUNT_INFO_TIMEOUT = 5.0
+# how long a provider's model list is reused before asking for it again
+MODEL_LIST_TTL = 60.0
+
 
 @define(hash=True)
 class Usage:
     input_tokens: int = field(factory=None)
     output_tokens: int = field(default=None)
     total_tokens: int = field(default=None)
+    # the size of the serialized request and response bodies, for providers that record them
+    request_bytes: Optional[int] = field(default=None, repr=False)
+    response_bytes: Optional[int] = field(default=None, repr=False)
+
+    def add(self, other: Optional["Usage"]) -> None:
+        """Accumulate the counts of other into this usage, skipping any counts that are missing"""
+        if other is None:
+            return
+        if other.input_tokens is not None:
+            self.input_tokens = (self.input_tokens or 0) + other.input_tokens
+        if other.output_tokens is not None:
+            self.output_tokens = (self.output_tokens or 0) + other.output_tokens
+        if other.total_tokens is not None:
+            self.total_tokens = (self.total_tokens or 0) + other.total_tokens
+        if other.request_bytes is not None:
+            self.request_bytes = (self.request_bytes or 0) + other.request_bytes
+        if other.response_bytes is not None:
+            self.response_bytes = (self.response_bytes or 0) + other.response_bytes
+
+
+@contextmanager
+def usage_of_empty_response(usage: Usage) -> Iterator[None]:
+    """Attach usage to an EmptyResponseError raised while reading a response, so its tokens are still counted"""
+    try:
+        yield
+    except EmptyResponseError as e:
+        e.usage = usage
+        raise
+
+
+@define
+class AccountInfo:
+    """What a provider could cheaply find out about the account its credentials belong to"""
+
+    key_valid: bool
+    organization: Optional[str] = None
+
+
+@define(frozen=True)
+class ProviderCapabilities:
+    """What a provider's API accepts, so an exchange only sends it what it can use
+
+    Everything is assumed to be supported unless a provider says otherwise, which is how
+    requests were sent before providers could tell.
+    """
+
+    tools: bool = True
+    images: bool = True
+    tool_choice: bool = True
+
+    def intersect(self, other: "ProviderCapabilities") -> "ProviderCapabilities":
+        """The capabilities both support"""
+        return ProviderCapabilities(
+            tools=self.tools and other.tools,
+            images=self.images and other.images,
+            tool_choice=self.tool_choice and other.tool_choice,
+        )
 
 
 class EmptyProviderNameError(Exception):
@@ -23,6 +88,18 @@ class EmptyProviderNameError(Exception):
 class Provider(ABC):
     PROVIDER_NAME: str
     REQUIRED_ENV_VARS: list[str] = []
+    # the largest base64 encoded image to send, larger images are downscaled first, None sends them as they are
+    MAX_IMAGE_BYTES: Optional[int] = None
+    CAPABILITIES: ProviderCapabilities = ProviderCapabilities()
+
+    @property
+    def name(self) -> str:
+        """The name this provider is registered under, e.g. openai"""
+        return self.PROVIDER_NAME
+
+    def capabilities(self) -> ProviderCapabilities:
+        """What this provider's API accepts, providers that wrap others report what those accept"""
+        return self.CAPABILITIES
 
     @classmethod
     def from_env(cls: type["Provider"]) -> "Provider":
@@ -50,6 +127,63 @@ class Provider(ABC):
         """Generate the next message using the specified model"""
         pass
 
+    def complete_raw(
+        self,
+        model: str,
+        system: str,
+        messages: list[Message],
+        tools: tuple[Tool, ...],
+        **kwargs: dict[str, any],
+    ) -> tuple[Message, Usage, dict[str, any]]:
+        """Generate the next message like complete, also returning the response it was parsed from
+
+        This is meant for debugging a provider that returns something unexpected.
+        """
+        raise NotImplementedError(f"The {self.name} provider does not return its raw response")
+
+    def account_info(self, timeout: float = ACCOUNT_INFO_TIMEOUT) -> AccountInfo:
+        """Check the credentials with the lightest request the provider allows, giving up after timeout seconds
+
+        This is meant for validating a configuration and attributing costs, not for every session.
+        """
+        raise NotImplementedError(f"The {self.name} provider does not report account info")
+
+    def list_models(self) -> list[str]:
+        """The names of the models this provider can complete with, reused for MODEL_LIST_TTL seconds"""
+        fetched_at, models = getattr(self, "_model_list", (None, None))
+        if fetched_at is None or time.monotonic() - fetched_at > MODEL_LIST_TTL:
+            models = self.fetch_models()
+            self._model_list = (time.monotonic(), models)
+        # a copy, so that callers can't change the cached list
+        return list(models)
+
+    def fetch_models(self) -> list[str]:
+        """Ask the provider which models it serves, by default only the recommended ones are known"""
+        return list(dict.fromkeys(self.recommended_models()))
+
+    def complete_n(
+        self,
+        model: str,
+        system: str,
+        messages: list[Message],
+        tools: tuple[Tool, ...],
+        n: int,
+        **kwargs: dict[str, any],
+    ) -> tuple[list[Message], Usage]:
+        """Generate n candidates for the next message, with the usage of all of them
+
+        By default this makes n completions in parallel. Providers that can sample several
+        choices in one request override it to do so.
+        """
+        with ThreadPoolExecutor(max_workers=n) as executor:
+            futures = [executor.submit(self.complete, model, system, messages, tools, **kwargs) for _ in range(n)]
+            results = [future.result() for future in futures]
+
+        usage = Usage(0, 0, 0)
+        for _, candidate_usage in results:
+            usage.add(candidate_usage)
+        return [message for message, _ in results], usage
+
     @staticmethod
     def recommended_models() -> tuple[str, str]:
         """Return the recommended model and processor for this provider"""
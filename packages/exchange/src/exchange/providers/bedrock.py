@@ -11,9 +11,16 @@ import httpx
 
 from exchange.content import Text, ToolResult, ToolUse
 from exchange.message import Message
-from exchange.providers import Provider, Usage
+from exchange.providers import Provider, ProviderCapabilities, Usage
+from exchange.providers.base import usage_of_empty_response
 from tenacity import retry, wait_fixed, stop_after_attempt
-from exchange.providers.utils import raise_for_status, retry_if_status
+from exchange.providers.utils import (
+    EVENT_HOOKS,
+    normalize_messages,
+    raise_for_status,
+    record_payload_sizes,
+    retry_if_status,
+)
 from exchange.tool import Tool
 from exchange.observers import observe_wrapper
 
@@ -156,6 +163,8 @@ class BedrockProvider(Provider):
         "AWS_SECRET_ACCESS_KEY",
         "AWS_SESSION_TOKEN",
     ]
+    # the models can read images, but tool results are only sent as text or json, so images are left out for now
+    CAPABILITIES = ProviderCapabilities(tools=True, images=False, tool_choice=False)
 
     def __init__(self, client: AwsClient) -> None:
         self.client = client
@@ -173,6 +182,7 @@ class BedrockProvider(Provider):
             aws_access_key=aws_access_key,
             aws_secret_key=aws_secret_key,
             aws_session_token=aws_session_token,
+            event_hooks=EVENT_HOOKS,
         )
         return cls(client=client)
 
@@ -203,11 +213,11 @@ class BedrockProvider(Provider):
             temperature=kwargs.pop("temperature", None),
             maxTokens=kwargs.pop("max_tokens", None),
             stopSequences=kwargs.pop("stop", None),
-            topP=kwargs.pop("topP", None),
+            topP=kwargs.pop("top_p", kwargs.pop("topP", None)),
         )
         inference_config = {k: v for k, v in inference_config.items() if v is not None} or None
 
-        converted_messages = [self.message_to_bedrock_spec(message) for message in messages]
+        converted_messages = [self.message_to_bedrock_spec(message) for message in normalize_messages(messages)]
         converted_system = [dict(text=system)]
         tool_config = self.tools_to_bedrock_spec(tools)
         payload = dict(
@@ -220,7 +230,8 @@ class BedrockProvider(Provider):
         payload = {k: v for k, v in payload.items() if v}
 
         path = f"{self.client.host}model/{model}/converse"
-        response = self._post(payload, path)
+        http_response = self._post(payload, path)
+        response = http_response.json()
         response_message = response["output"]["message"]
 
         usage_data = response["usage"]
@@ -229,13 +240,15 @@ class BedrockProvider(Provider):
             output_tokens=usage_data.get("outputTokens"),
             total_tokens=usage_data.get("totalTokens"),
         )
+        record_payload_sizes(usage, http_response)
 
-        return self.response_to_message(response_message), usage
+        with usage_of_empty_response(usage):
+            return self.response_to_message(response_message), usage
 
     @retry_procedure
-    def _post(self, payload: any, path: str) -> dict:  # noqa: ANN401
+    def _post(self, payload: any, path: str) -> httpx.Response:  # noqa: ANN401
         response = self.client.post(path, json=payload)
-        return raise_for_status(response).json()
+        return raise_for_status(response)
 
     @staticmethod
     def message_to_bedrock_spec(message: Message) -> dict:
@@ -0,0 +1,152 @@
+import hashlib
+import json
+import os
+import re
+import time
+from collections import OrderedDict
+from copy import deepcopy
+from pathlib import Path
+from typing import Callable, Optional
+
+from attrs import asdict
+
+from exchange.message import Message
+from exchange.providers.base import Provider, ProviderCapabilities, Usage
+from exchange.tool import Tool
+
+# the name of an entry's file, so that other json files sharing the directory are never evicted
+ENTRY_FILE_NAME = re.compile(r"[0-9a-f]{64}\.json")
+
+
+def request_key(model: str, system: str, messages: list[Message], tools: tuple[Tool, ...], **kwargs: dict) -> str:
+    """A hash of everything in a completion request that affects the reply"""
+    # message ids and timestamps differ between runs, so only the role and content are part of the key
+    request = {
+        "model": model,
+        "system": system,
+        "messages": [[message.role, [item.to_dict() for item in message.content]] for message in messages],
+        "tools": [[tool.name, tool.description, tool.parameters] for tool in tools],
+        "kwargs": kwargs,
+    }
+    serialized = json.dumps(request, sort_keys=True, default=repr)
+    return hashlib.sha256(serialized.encode()).hexdigest()
+
+
+class CachingProvider(Provider):
+    """Returns the stored reply of its provider when the same request is sent again.
+
+    Requests are keyed on the model, system prompt, message contents, tools and any other arguments.
+    Entries expire after ttl seconds, if set, and the least recently used ones are evicted past
+    max_entries. They are kept in memory, or as one file each in path to be shared across runs.
+
+    Only deterministic requests, sent with a temperature of 0, are cached unless cache_nondeterministic
+    is set, since the provider would not give the same reply to the others. This is meant for
+    development and tests, where the same prompts are sent over and over.
+    """
+
+    PROVIDER_NAME = "cache"
+    REQUIRED_ENV_VARS = ["CACHE_PROVIDER"]
+
+    def __init__(
+        self,
+        provider: Provider,
+        ttl: Optional[float] = None,
+        max_entries: int = 256,
+        path: Optional[Path] = None,
+        cache_nondeterministic: bool = False,
+        clock: Callable[[], float] = time.time,
+    ) -> None:
+        if max_entries < 1:
+            raise ValueError("max_entries must be at least 1")
+        self.provider = provider
+        self.ttl = ttl
+        self.max_entries = max_entries
+        self.path = path
+        self.cache_nondeterministic = cache_nondeterministic
+        self.clock = clock
+        self._entries: OrderedDict[str, dict] = OrderedDict()
+        if path is not None:
+            path.mkdir(parents=True, exist_ok=True)
+
+    def capabilities(self) -> ProviderCapabilities:
+        return self.provider.capabilities()
+
+    def list_models(self) -> list[str]:
+        return self.provider.list_models()
+
+    @classmethod
+    def from_env(cls: type["CachingProvider"]) -> "CachingProvider":
+        from exchange.providers import get_provider
+
+        cls.check_env_vars()
+        name = os.environ["CACHE_PROVIDER"]
+        if name == cls.PROVIDER_NAME:
+            raise ValueError("CACHE_PROVIDER cannot be the cache provider itself")
+        ttl = os.environ.get("CACHE_TTL")
+        path = os.environ.get("CACHE_DIR")
+        return cls(
+            get_provider(name).from_env(),
+            ttl=float(ttl) if ttl else None,
+            max_entries=int(os.environ.get("CACHE_MAX_ENTRIES", 256)),
+            path=Path(path).expanduser() if path else None,
+            cache_nondeterministic=bool(os.environ.get("CACHE_NONDETERMINISTIC")),
+        )
+
+    def _read(self, key: str) -> Optional[dict]:
+        if self.path is None:
+            return self._entries.get(key)
+        entry_path = self.path / f"{key}.json"
+        if not entry_path.is_file():
+            return None
+        return json.loads(entry_path.read_text())
+
+    def _write(self, key: str, entry: dict) -> None:
+        if self.path is None:
+            self._entries[key] = entry
+            self._entries.move_to_end(key)
+            while len(self._entries) > self.max_entries:
+                self._entries.popitem(last=False)
+            return
+        (self.path / f"{key}.json").write_text(json.dumps(entry))
+        entries = [entry_path for entry_path in self.path.glob("*.json") if ENTRY_FILE_NAME.fullmatch(entry_path.name)]
+        entries.sort(key=lambda entry_path: entry_path.stat().st_mtime)
+        for entry_path in entries[: max(len(entries) - self.max_entries, 0)]:
+            entry_path.unlink(missing_ok=True)
+
+    def _touch(self, key: str) -> None:
+        """Mark the entry as recently used, so it is evicted last"""
+        if self.path is None:
+            self._entries.move_to_end(key)
+        else:
+            os.utime(self.path / f"{key}.json")
+
+    def _delete(self, key: str) -> None:
+        if self.path is None:
+            self._entries.pop(key, None)
+        else:
+            (self.path / f"{key}.json").unlink(missing_ok=True)
+
+    def complete(
+        self,
+        model: str,
+        system: str,
+        messages: list[Message],
+        tools: tuple[Tool, ...],
+        **kwargs: dict[str, any],
+    ) -> tuple[Message, Usage]:
+        if not self.cache_nondeterministic and kwargs.get("temperature") != 0:
+            return self.provider.complete(model, system, messages, tools, **kwargs)
+
+        key = request_key(model, system, messages, tools, **kwargs)
+        entry = self._read(key)
+        if entry is not None and self.ttl is not None and self.clock() - entry["created"] > self.ttl:
+            self._delete(key)
+            entry = None
+        if entry is not None:
+            self._touch(key)
+            # the message is built from a copy, since loading its content consumes the dicts
+            return Message(**deepcopy(entry["message"])), Usage(**entry["usage"])
+
+        message, usage = self.provider.complete(model, system, messages, tools, **kwargs)
+        self._write(key, {"created": self.clock(), "message": message.to_dict(), "usage": asdict(usage)})
+        return message, usage
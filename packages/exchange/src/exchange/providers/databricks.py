@@ -2,9 +2,9 @@ import httpx
 import os
 
 from exchange.message import Message
-from exchange.providers.base import Provider, Usage
+from exchange.providers.base import Provider, ProviderCapabilities, Usage, usage_of_empty_response
 from tenacity import retry, wait_fixed, stop_after_attempt
-from exchange.providers.utils import raise_for_status, retry_if_status
+from exchange.providers.utils import EVENT_HOOKS, raise_for_status, record_payload_sizes, retry_if_status
 from exchange.providers.utils import (
     messages_to_openai_spec,
     openai_response_to_message,
@@ -35,6 +35,7 @@ class DatabricksProvider(Provider):
         "DATABRICKS_HOST",
         "DATABRICKS_TOKEN",
     ]
+    CAPABILITIES = ProviderCapabilities(tools=True, images=True, tool_choice=False)
     instructions_url = "https://docs.databricks.com/en/dev-tools/auth/index.html#general-host-token-and-account-id-environment-variables-and-fields"
 
     def __init__(self, client: httpx.Client) -> None:
@@ -49,6 +50,7 @@ class DatabricksProvider(Provider):
             base_url=url,
             auth=("token", key),
             timeout=httpx.Timeout(60 * 10),
+            event_hooks=EVENT_HOOKS,
         )
         return cls(client)
 
@@ -79,15 +81,17 @@ class DatabricksProvider(Provider):
         payload = dict(
             messages=[
                 {"role": "system", "content": system},
-                *messages_to_openai_spec(messages),
+                *messages_to_openai_spec(messages, self.MAX_IMAGE_BYTES),
             ],
             tools=tools_to_openai_spec(tools) if tools else [],
             **kwargs,
         )
         payload = {k: v for k, v in payload.items() if v}
-        response = self._post(model, payload)
-        message = openai_response_to_message(response)
-        usage = self.get_usage(response)
+        http_response = self._post(model, payload)
+        response = http_response.json()
+        usage = record_payload_sizes(self.get_usage(response), http_response)
+        with usage_of_empty_response(usage):
+            message = openai_response_to_message(response)
         return message, usage
 
     @staticmethod
@@ -101,4 +105,4 @@ class DatabricksProvider(Provider):
             f"serving-endpoints/{model}/invocations",
             json=payload,
         )
-        return raise_for_status(response).json()
+        return raise_for_status(response)
@@ -0,0 +1,72 @@
+import os
+
+import httpx
+
+from exchange.message import Message
+from exchange.providers.base import Provider, ProviderCapabilities, Usage
+from exchange.tool import Tool
+
+# status codes that say nothing about the request itself, so another provider may still serve it
+TRANSIENT_STATUS_CODES = (408, 429)
+
+
+def is_transient_error(exc: BaseException) -> bool:
+    """Whether the request could succeed if sent again, possibly to another provider"""
+    if isinstance(exc, httpx.HTTPStatusError):
+        status = exc.response.status_code
+        return status in TRANSIENT_STATUS_CODES or status >= 500
+    return isinstance(exc, (httpx.TransportError, ConnectionError, TimeoutError))
+
+
+class FallbackProvider(Provider):
+    """Sends each request to the first of its providers that answers, failing over on transient errors.
+
+    Only connection failures, timeouts, rate limits and server errors fall through to the next provider;
+    anything else (e.g. an invalid request) is raised straight away. The same model name is sent to every
+    provider, so the chain should be made of providers that serve the same models.
+    """
+
+    PROVIDER_NAME = "fallback"
+    REQUIRED_ENV_VARS = ["FALLBACK_PROVIDERS"]
+
+    def __init__(self, providers: list[Provider]) -> None:
+        if not providers:
+            raise ValueError("FallbackProvider needs at least one provider")
+        self.providers = providers
+
+    def capabilities(self) -> ProviderCapabilities:
+        """What every provider in the chain accepts, since any of them may end up serving the request"""
+        capabilities = self.providers[0].capabilities()
+        for provider in self.providers[1:]:
+            capabilities = capabilities.intersect(provider.capabilities())
+        return capabilities
+
+    def list_models(self) -> list[str]:
+        """The models of the first provider, as the chain is expected to serve the same ones"""
+        return self.providers[0].list_models()
+
+    @classmethod
+    def from_env(cls: type["FallbackProvider"]) -> "FallbackProvider":
+        from exchange.providers import get_provider
+
+        cls.check_env_vars()
+        names = [name.strip() for name in os.environ["FALLBACK_PROVIDERS"].split(",") if name.strip()]
+        if cls.PROVIDER_NAME in names:
+            raise ValueError("FALLBACK_PROVIDERS cannot include the fallback provider itself")
+        return cls([get_provider(name).from_env() for name in names])
+
+    def complete(
+        self,
+        model: str,
+        system: str,
+        messages: list[Message],
+        tools: tuple[Tool, ...],
+        **kwargs: dict[str, any],
+    ) -> tuple[Message, Usage]:
+        for provider in self.providers[:-1]:
+            try:
+                return provider.complete(model, system, messages, tools, **kwargs)
+            except Exception as e:
+                if not is_transient_error(e):
+                    raise
+        return self.providers[-1].complete(model, system, messages, tools, **kwargs)
@@ -1,12 +1,20 @@
 import os
+from typing import Optional
 
 import httpx
 
 from exchange import Message, Tool
 from exchange.content import Text, ToolResult, ToolUse
-from exchange.providers.base import Provider, Usage
+from exchange.providers.base import Provider, ProviderCapabilities, Usage, usage_of_empty_response
 from tenacity import retry, wait_fixed, stop_after_attempt
-from exchange.providers.utils import raise_for_status, retry_if_status, encode_image
+from exchange.providers.utils import (
+    EVENT_HOOKS,
+    encode_image,
+    normalize_messages,
+    raise_for_status,
+    record_payload_sizes,
+    retry_if_status,
+)
 from exchange.observers import observe_wrapper
 
 
@@ -25,6 +33,8 @@ class GoogleProvider(Provider):
 
     PROVIDER_NAME = "google"
     REQUIRED_ENV_VARS = ["GOOGLE_API_KEY"]
+    MAX_IMAGE_BYTES = 20 * 1024 * 1024
+    CAPABILITIES = ProviderCapabilities(tools=True, images=True, tool_choice=False)
     instructions_url = "https://ai.google.dev/gemini-api/docs/api-key"
 
     def __init__(self, client: httpx.Client) -> None:
@@ -42,6 +52,7 @@ class GoogleProvider(Provider):
             },
             params={"key": key},
             timeout=httpx.Timeout(60 * 10),
+            event_hooks=EVENT_HOOKS,
         )
         return cls(client)
 
@@ -101,9 +112,9 @@ class GoogleProvider(Provider):
         return {"functionDeclarations": converted_tools}
 
     @staticmethod
-    def messages_to_google_spec(messages: list[Message]) -> list[dict[str, any]]:
+    def messages_to_google_spec(messages: list[Message], max_image_bytes: Optional[int] = None) -> list[dict[str, any]]:
         messages_spec = []
-        for message in messages:
+        for message in normalize_messages(messages):
             role = "user" if message.role == "user" else "model"
             converted = {"role": role, "parts": []}
             for content in message.content:
@@ -118,7 +129,7 @@ class GoogleProvider(Provider):
                             {
                                 "inline_data": {
                                     "mime_type": "image/png",
-                                    "data": f"{encode_image(image_path)}",
+                                    "data": f"{encode_image(image_path, max_image_bytes)}",
                                 }
                             }
                         )
@@ -151,20 +162,22 @@ class GoogleProvider(Provider):
 
         payload = dict(
             system_instruction={"parts": [{"text": system}]},
-            contents=self.messages_to_google_spec(messages),
+            contents=self.messages_to_google_spec(messages, self.MAX_IMAGE_BYTES),
             tools=self.tools_to_google_spec(tuple(unique_tools)),
             **kwargs,
         )
         payload = {k: v for k, v in payload.items() if v}
-        response = self._post(payload, model)
-        message = self.google_response_to_message(response)
-        usage = self.get_usage(response)
+        http_response = self._post(payload, model)
+        response = http_response.json()
+        usage = record_payload_sizes(self.get_usage(response), http_response)
+        with usage_of_empty_response(usage):
+            message = self.google_response_to_message(response)
         return message, usage
 
     @retry_procedure
     def _post(self, payload: dict, model: str) -> httpx.Response:
         response = self.client.post("models/" + model + ":generateContent", json=payload)
-        return raise_for_status(response).json()
+        return raise_for_status(response)
 
     @staticmethod
     def recommended_models() -> tuple[str, str]:
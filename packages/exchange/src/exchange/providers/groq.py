@@ -4,12 +4,14 @@ from exchange.observers import observe_wrapper
 import httpx
 
 from exchange.message import Message
-from exchange.providers.base import Provider, Usage
+from exchange.providers.base import Provider, ProviderCapabilities, Usage, usage_of_empty_response
 from exchange.providers.utils import (
+    EVENT_HOOKS,
     messages_to_openai_spec,
     openai_response_to_message,
     openai_single_message_context_length_exceeded,
     raise_for_status,
+    record_payload_sizes,
     tools_to_openai_spec,
 )
 from exchange.tool import Tool
@@ -31,6 +33,8 @@ class GroqProvider(Provider):
 
     PROVIDER_NAME = "groq"
     REQUIRED_ENV_VARS = ["GROQ_API_KEY"]
+    MAX_IMAGE_BYTES = 4 * 1024 * 1024
+    CAPABILITIES = ProviderCapabilities(tools=True, images=True, tool_choice=False)
     instructions_url = "https://console.groq.com/docs/quickstart"
 
     def __init__(self, client: httpx.Client) -> None:
@@ -46,6 +50,7 @@ class GroqProvider(Provider):
             base_url=url + "v1/",
             headers={"Authorization": "Bearer " + key},
             timeout=httpx.Timeout(60 * 10),
+            event_hooks=EVENT_HOOKS,
         )
         return cls(client)
 
@@ -76,23 +81,25 @@ class GroqProvider(Provider):
     ) -> tuple[Message, Usage]:
         system_message = [{"role": "system", "content": system}]
         payload = dict(
-            messages=system_message + messages_to_openai_spec(messages),
+            messages=system_message + messages_to_openai_spec(messages, self.MAX_IMAGE_BYTES),
             model=model,
             tools=tools_to_openai_spec(tools) if tools else [],
             **kwargs,
         )
         payload = {k: v for k, v in payload.items() if v}
-        response = self._post(payload)
+        http_response = self._post(payload)
+        response = http_response.json()
 
         # Check for context_length_exceeded error for single, long input message
         if "error" in response and len(messages) == 1:
             openai_single_message_context_length_exceeded(response["error"])
 
-        message = openai_response_to_message(response)
-        usage = self.get_usage(response)
+        usage = record_payload_sizes(self.get_usage(response), http_response)
+        with usage_of_empty_response(usage):
+            message = openai_response_to_message(response)
         return message, usage
 
     @retry_procedure
-    def _post(self, payload: dict) -> dict:
+    def _post(self, payload: dict) -> httpx.Response:
         response = self.client.post("chat/completions", json=payload)
-        return raise_for_status(response).json()
+        return raise_for_status(response)
@@ -3,6 +3,7 @@ import os
 import httpx
 
 from exchange.providers.openai import OpenAiProvider
+from exchange.providers.utils import EVENT_HOOKS, raise_for_status
 
 OLLAMA_HOST = "http://localhost:11434/"
 OLLAMA_MODEL = "qwen2.5"
@@ -41,9 +42,14 @@ ollama:
         httpx.get(ollama_url, timeout=timeout)
 
         # When served by Ollama, the OpenAI API is available at the path "v1/".
-        client = httpx.Client(base_url=ollama_url + "v1/", timeout=timeout)
+        client = httpx.Client(base_url=ollama_url + "v1/", timeout=timeout, event_hooks=EVENT_HOOKS)
         return cls(client)
 
+    def fetch_models(self) -> list[str]:
+        """The models pulled into Ollama, which it lists outside of its OpenAI compatible API"""
+        response = raise_for_status(self.client.get(self.client.base_url.join("/api/tags")))
+        return sorted(model["name"] for model in response.json()["models"])
+
     @staticmethod
     def recommended_models() -> tuple[str, str]:
         """Return the recommended model and processor for this provider"""
@@ -1,17 +1,30 @@
+import json
 import os
+from typing import Optional
 
 import httpx
 
 from exchange.message import Message
-from exchange.providers.base import Provider, Usage
+from exchange.providers.base import (
+    ACCOUNT_INFO_TIMEOUT,
+    AccountInfo,
+    Provider,
+    ProviderCapabilities,
+    Usage,
+    usage_of_empty_response,
+)
 from exchange.providers.utils import (
+    EVENT_HOOKS,
     messages_to_openai_spec,
     openai_response_to_message,
+    openai_response_to_messages,
     openai_single_message_context_length_exceeded,
     raise_for_status,
+    record_payload_sizes,
+    tool_choice_to_openai_spec,
     tools_to_openai_spec,
 )
-from exchange.tool import Tool
+from exchange.tool import Tool, ToolChoice
 from tenacity import retry, wait_fixed, stop_after_attempt
 from exchange.providers.utils import retry_if_status
 from exchange.observers import observe_wrapper
@@ -31,10 +44,13 @@ class OpenAiProvider(Provider):
 
     PROVIDER_NAME = "openai"
     REQUIRED_ENV_VARS = ["OPENAI_API_KEY"]
+    MAX_IMAGE_BYTES = 20 * 1024 * 1024
+    CAPABILITIES = ProviderCapabilities(tools=True, images=True, tool_choice=True)
     instructions_url = "https://platform.openai.com/docs/api-reference/api-keys"
 
-    def __init__(self, client: httpx.Client) -> None:
+    def __init__(self, client: httpx.Client, completions_path: str = "chat/completions") -> None:
         self.client = client
+        self.completions_path = completions_path
 
     @classmethod
     def from_env(cls: type["OpenAiProvider"]) -> "OpenAiProvider":
@@ -42,12 +58,27 @@ class OpenAiProvider(Provider):
         url = os.environ.get("OPENAI_HOST", OPENAI_HOST)
         key = os.environ.get("OPENAI_API_KEY")
 
+        # OpenAI compatible gateways can need extra headers, such as X-Title for OpenRouter,
+        # and can mount the completions endpoint somewhere other than chat/completions
         client = httpx.Client(
             base_url=url + "v1/",
             auth=("Bearer", key),
+            headers=cls.extra_headers_from_env(),
             timeout=httpx.Timeout(60 * 10),
+            event_hooks=EVENT_HOOKS,
         )
-        return cls(client)
+        return cls(client, completions_path=os.environ.get("OPENAI_COMPLETIONS_PATH", "chat/completions"))
+
+    @staticmethod
+    def extra_headers_from_env() -> dict[str, str]:
+        """Read the headers to add to every request from OPENAI_EXTRA_HEADERS, a JSON object"""
+        extra_headers = os.environ.get("OPENAI_EXTRA_HEADERS")
+        if not extra_headers:
+            return {}
+        headers = json.loads(extra_headers)
+        if not isinstance(headers, dict):
+            raise ValueError("OPENAI_EXTRA_HEADERS must be a JSON object of header names to values")
+        return {str(name): str(value) for name, value in headers.items()}
 
     @staticmethod
     def get_usage(data: dict) -> Usage:
@@ -72,31 +103,87 @@ class OpenAiProvider(Provider):
         system: str,
         messages: list[Message],
         tools: tuple[Tool, ...],
+        tool_choice: Optional[ToolChoice] = None,
         **kwargs: dict[str, any],
     ) -> tuple[Message, Usage]:
+        message, usage, _ = self.complete_raw(model, system, messages, tools, tool_choice=tool_choice, **kwargs)
+        return message, usage
+
+    def complete_raw(
+        self,
+        model: str,
+        system: str,
+        messages: list[Message],
+        tools: tuple[Tool, ...],
+        tool_choice: Optional[ToolChoice] = None,
+        **kwargs: dict[str, any],
+    ) -> tuple[Message, Usage, dict[str, any]]:
+        response, usage = self._request(model, system, messages, tools, tool_choice, **kwargs)
+        with usage_of_empty_response(usage):
+            return openai_response_to_message(response), usage, response
+
+    def complete_n(
+        self,
+        model: str,
+        system: str,
+        messages: list[Message],
+        tools: tuple[Tool, ...],
+        n: int,
+        tool_choice: Optional[ToolChoice] = None,
+        **kwargs: dict[str, any],
+    ) -> tuple[list[Message], Usage]:
+        """Sample n choices in a single request"""
+        response, usage = self._request(model, system, messages, tools, tool_choice, n=n, **kwargs)
+        with usage_of_empty_response(usage):
+            return openai_response_to_messages(response), usage
+
+    def _request(
+        self,
+        model: str,
+        system: str,
+        messages: list[Message],
+        tools: tuple[Tool, ...],
+        tool_choice: Optional[ToolChoice],
+        **kwargs: dict[str, any],
+    ) -> tuple[dict[str, any], Usage]:
         system_message = [] if model.startswith("o1") else [{"role": "system", "content": system}]
         payload = dict(
-            messages=system_message + messages_to_openai_spec(messages),
+            messages=system_message + messages_to_openai_spec(messages, self.MAX_IMAGE_BYTES),
             model=model,
             tools=tools_to_openai_spec(tools) if tools else [],
-            **kwargs,
+            # the api rejects a tool choice without any tools to choose from
+            tool_choice=tool_choice_to_openai_spec(tool_choice) if tool_choice and tools else None,
         )
         payload = {k: v for k, v in payload.items() if v}
-        response = self._post(payload)
+        # generation parameters such as temperature, top_p and stop are sent as given, so that 0 is kept
+        payload.update({k: v for k, v in kwargs.items() if v is not None})
+        http_response = self._post(payload)
+        response = http_response.json()
 
         # Check for context_length_exceeded error for single, long input message
         if "error" in response and len(messages) == 1:
             openai_single_message_context_length_exceeded(response["error"])
 
-        message = openai_response_to_message(response)
-        usage = self.get_usage(response)
-        return message, usage
+        usage = record_payload_sizes(self.get_usage(response), http_response)
+        return response, usage
+
+    def account_info(self, timeout: float = ACCOUNT_INFO_TIMEOUT) -> AccountInfo:
+        """Validate the key by listing the models, which costs nothing"""
+        response = self.client.get("models", timeout=timeout)
+        if response.status_code in (401, 403):
+            return AccountInfo(key_valid=False)
+        raise_for_status(response)
+        return AccountInfo(key_valid=True, organization=response.headers.get("openai-organization"))
+
+    def fetch_models(self) -> list[str]:
+        response = raise_for_status(self.client.get("models"))
+        return sorted(model["id"] for model in response.json()["data"])
 
     @retry_procedure
-    def _post(self, payload: dict) -> dict:
+    def _post(self, payload: dict) -> httpx.Response:
         # Note: While OpenAI and Ollama mount the API under "v1", this is
         # conventional and not a strict requirement. For example, Azure OpenAI
         # mounts the API under the deployment name, and "v1" is not in the URL.
         # See https://github.com/openai/openai-openapi/blob/master/openapi.yaml
-        response = self.client.post("chat/completions", json=payload)
-        return raise_for_status(response).json()
+        response = self.client.post(self.completions_path, json=payload)
+        return raise_for_status(response)
@@ -0,0 +1,92 @@
+import json
+import os
+from pathlib import Path
+from typing import Optional
+
+from attrs import asdict
+
+from exchange.message import Message
+from exchange.providers.base import Provider, ProviderCapabilities, Usage
+from exchange.providers.caching import request_key
+from exchange.tool import Tool
+
+
+class ReplayMismatchError(Exception):
+    def __init__(self, path: Path, index: int, reason: str) -> None:
+        self.message = f"Can't replay completion {index} from {path}: {reason}"
+        super().__init__(self.message)
+
+
+class ReplayProvider(Provider):
+    """Records the completions of another provider to a file, then replays them in order without it.
+
+    Pass a provider to record: every completion it returns is written to path, replacing what was
+    recorded before. Without a provider, the completions in path are returned in the order they were
+    recorded, so a whole reply with its tool rounds can be replayed in tests without a live model.
+    Each request is checked against the recorded one, and a ReplayMismatchError is raised when the
+    conversation diverges from the recording.
+    """
+
+    PROVIDER_NAME = "replay"
+    REQUIRED_ENV_VARS = ["REPLAY_FILE"]
+
+    def __init__(self, path: Path, provider: Optional[Provider] = None) -> None:
+        self.path = path
+        self.provider = provider
+        self.index = 0
+        if provider is None:
+            self.recorded = [json.loads(line) for line in path.read_text().splitlines() if line.strip()]
+        else:
+            path.parent.mkdir(parents=True, exist_ok=True)
+            path.write_text("")
+
+    def capabilities(self) -> ProviderCapabilities:
+        """The capabilities of the recording provider, so replayed requests are shaped like the recorded ones"""
+        if self.provider is not None:
+            return self.provider.capabilities()
+        if self.recorded and "capabilities" in self.recorded[0]:
+            return ProviderCapabilities(**self.recorded[0]["capabilities"])
+        return ProviderCapabilities()
+
+    @classmethod
+    def from_env(cls: type["ReplayProvider"]) -> "ReplayProvider":
+        from exchange.providers import get_provider
+
+        cls.check_env_vars()
+        path = Path(os.environ["REPLAY_FILE"]).expanduser()
+        name = os.environ.get("REPLAY_PROVIDER")
+        if name == cls.PROVIDER_NAME:
+            raise ValueError("REPLAY_PROVIDER cannot be the replay provider itself")
+        return cls(path, get_provider(name).from_env() if name else None)
+
+    def complete(
+        self,
+        model: str,
+        system: str,
+        messages: list[Message],
+        tools: tuple[Tool, ...],
+        **kwargs: dict[str, any],
+    ) -> tuple[Message, Usage]:
+        key = request_key(model, system, messages, tools, **kwargs)
+        index = self.index
+        self.index += 1
+
+        if self.provider is not None:
+            message, usage = self.provider.complete(model, system, messages, tools, **kwargs)
+            entry = {
+                "index": index,
+                "key": key,
+                "message": message.to_dict(),
+                "usage": asdict(usage),
+                "capabilities": asdict(self.provider.capabilities()),
+            }
+            with self.path.open("a") as f:
+                f.write(json.dumps(entry) + "\n")
+            return message, usage
+
+        if index >= len(self.recorded):
+            raise ReplayMismatchError(self.path, index, f"only {len(self.recorded)} completions were recorded")
+        entry = self.recorded[index]
+        if entry["key"] != key:
+            raise ReplayMismatchError(self.path, index, "the request differs from the recorded one")
+        return Message(**entry["message"]), Usage(**entry["usage"])
@@ -1,14 +1,33 @@
 import base64
+import io
 import json
+import logging
+import math
+import os
 import re
-from typing import Optional
+import uuid
+from importlib.metadata import version
+from typing import Optional, Union
 
 import httpx
+from attrs import evolve
 from exchange.content import Text, ToolResult, ToolUse
 from exchange.message import Message
-from exchange.tool import Tool
+from exchange.providers.base import Usage
+from exchange.tool import Tool, ToolChoice
 from tenacity import retry_if_exception
 
+logger = logging.getLogger(__name__)
+
+# headers and query parameters providers send credentials in
+SECRET_HEADERS = ("authorization", "api-key", "x-api-key")
+SECRET_PARAMS = ("key",)
+REDACTED = "[REDACTED]"
+
+# every request says who sent it and carries an id to find it by in the provider's logs
+USER_AGENT_ENV = "GOOSE_USER_AGENT"
+REQUEST_ID_HEADER = "X-Request-Id"
+
 
 def retry_if_status(codes: Optional[list[int]] = None, above: Optional[int] = None) -> callable:
     codes = codes or []
@@ -24,31 +43,165 @@ def retry_if_status(codes: Optional[list[int]] = None, above: Optional[int] = No
     return retry_if_exception(predicate)
 
 
+def request_secrets(request: httpx.Request) -> list[str]:
+    """The credentials sent with a request, which must never be logged"""
+    secrets = []
+    for name in SECRET_HEADERS:
+        value = request.headers.get(name)
+        if value:
+            # keep the whole value as well as the token after a scheme such as "Bearer"
+            secrets.extend({value, value.split(" ")[-1]})
+    for name in SECRET_PARAMS:
+        value = request.url.params.get(name)
+        if value:
+            secrets.append(value)
+    return sorted(secrets, key=len, reverse=True)
+
+
+def redact(text: str, secrets: list[str]) -> str:
+    for secret in secrets:
+        text = text.replace(secret, REDACTED)
+    return text
+
+
+def log_response(response: httpx.Response) -> None:
+    """Log the request a provider sent and the raw response at debug level, with credentials redacted"""
+    if not logger.isEnabledFor(logging.DEBUG):
+        return
+    request = response.request
+    secrets = request_secrets(request)
+    response.read()
+    url, payload = redact(str(request.url), secrets), redact(request.content.decode(), secrets)
+    logger.debug("%s %s\n%s", request.method, url, payload)
+    logger.debug("%s response\n%s", response.status_code, redact(response.text, secrets))
+
+
+def user_agent() -> str:
+    """The user agent providers send, from GOOSE_USER_AGENT or else goose/<version>"""
+    return os.environ.get(USER_AGENT_ENV) or f"goose/{version('ai-exchange')}"
+
+
+def identify_request(request: httpx.Request) -> None:
+    """Set the user agent and a new request id on an outgoing request, to install as a request event hook"""
+    request_id = str(uuid.uuid4())
+    request.headers["User-Agent"] = user_agent()
+    request.headers[REQUEST_ID_HEADER] = request_id
+    logger.debug("%s %s sent as request %s", request.method, request.url.path, request_id)
+
+
+# pass as the event_hooks of a provider's client
+EVENT_HOOKS = {"request": [identify_request]}
+
+
 def raise_for_status(response: httpx.Response) -> httpx.Response:
     """Raise with reason text."""
+    log_response(response)
     try:
         response.raise_for_status()
         return response
     except httpx.HTTPStatusError as e:
         response.read()
+        message = str(e)
+        request_id = e.request.headers.get(REQUEST_ID_HEADER)
+        if request_id:
+            message += f" (request id {request_id})"
         if response.text:
-            raise httpx.HTTPStatusError(f"{e}\n{response.text}", request=e.request, response=e.response)
-        else:
-            raise e
+            message += f"\n{response.text}"
+        raise httpx.HTTPStatusError(message, request=e.request, response=e.response)
+
 
+def record_payload_sizes(usage: Usage, response: httpx.Response) -> Usage:
+    """Fill in the sizes of the request and response bodies that a completion's usage came from"""
+    usage.request_bytes = len(response.request.content)
+    usage.response_bytes = len(response.content)
+    return usage
 
-def encode_image(image_path: str) -> str:
+
+def encode_image(image_path: str, max_bytes: Optional[int] = None) -> str:
+    """Base64 encode the image at image_path, downscaling it first if the encoding would exceed max_bytes"""
     with open(image_path, "rb") as image_file:
-        return base64.b64encode(image_file.read()).decode("utf-8")
+        data = image_file.read()
+    if max_bytes is not None and _base64_size(len(data)) > max_bytes:
+        data = downscale_image(data, max_bytes)
+    return base64.b64encode(data).decode("utf-8")
+
+
+# the start of the base64 encoding of each image format's signature
+IMAGE_MEDIA_TYPES = {"/9j/": "image/jpeg", "iVBORw0KGgo": "image/png", "R0lGOD": "image/gif", "UklGR": "image/webp"}
+
+
+def image_media_type(encoded_image: str) -> str:
+    """The media type of a base64 encoded image, from the signature at the start of its data"""
+    for prefix, media_type in IMAGE_MEDIA_TYPES.items():
+        if encoded_image.startswith(prefix):
+            return media_type
+    # the format downscale_image falls back to
+    return "image/png"
+
+
+def _base64_size(size: int) -> int:
+    return 4 * math.ceil(size / 3)
+
+
+def downscale_image(data: bytes, max_bytes: int) -> bytes:
+    """Resize and recompress an image, keeping its format, until its base64 encoding fits in max_bytes
 
+    JPEG images are saved as JPEG and everything else as PNG.
+    """
+    # pillow is only needed, and only imported, for images that are over the budget
+    from PIL import Image
 
-def messages_to_openai_spec(messages: list[Message]) -> list[dict[str, any]]:
+    image = Image.open(io.BytesIO(data))
+    image_format = "JPEG" if image.format == "JPEG" else "PNG"
+    if image_format == "JPEG" and image.mode != "RGB":
+        image = image.convert("RGB")
+
+    width, height = image.size
+    size = _base64_size(len(data))
+    while size > max_bytes and (width, height) != (1, 1):
+        # the encoded size roughly tracks the area, so shrink each side by the square root of the excess
+        scale = min(0.9, math.sqrt(max_bytes / size))
+        width, height = max(1, int(width * scale)), max(1, int(height * scale))
+        buffer = io.BytesIO()
+        image.resize((width, height), Image.Resampling.LANCZOS).save(buffer, format=image_format, optimize=True)
+        data = buffer.getvalue()
+        size = _base64_size(len(data))
+    logger.debug("Downscaled image to %dx%d to fit in %d bytes", width, height, max_bytes)
+    return data
+
+
+def is_image_result(content: ToolResult) -> bool:
+    """Whether a tool result is an image, which tools return as a path such as "image:/tmp/screenshot.png" """
+    return content.output.startswith('"image:')
+
+
+def strip_images(messages: list[Message]) -> list[Message]:
+    """Replace the images in tool results with a note that they were left out, for models that can't read them"""
+    stripped = []
+    for message in messages:
+        if not any(is_image_result(content) for content in message.tool_result):
+            stripped.append(message)
+            continue
+        content = [
+            evolve(item, output=json.dumps("The image was left out, this model can't read images."))
+            if isinstance(item, ToolResult) and is_image_result(item)
+            else item
+            for item in message.content
+        ]
+        stripped.append(evolve(message, content=content))
+    return stripped
+
+
+def messages_to_openai_spec(messages: list[Message], max_image_bytes: Optional[int] = None) -> list[dict[str, any]]:
     messages_spec = []
     for message in messages:
         converted = {"role": message.role}
         output = []
         for content in message.content:
             if isinstance(content, Text):
+                # whitespace-only assistant text, such as alongside a tool call, would be sent as empty content
+                if message.role == "assistant" and not content.text.strip():
+                    continue
                 converted["content"] = content.text
             elif isinstance(content, ToolUse):
                 sanitized_name = re.sub(r"[^a-zA-Z0-9_-]", "_", content.name)
@@ -65,6 +218,7 @@ def messages_to_openai_spec(messages: list[Message]) -> list[dict[str, any]]:
             elif isinstance(content, ToolResult):
                 if content.output.startswith('"image:'):
                     image_path = content.output.replace('"image:', "").replace('"', "")
+                    encoded_image = encode_image(image_path, max_image_bytes)
                     output.append(
                         {
                             "role": "tool",
@@ -85,7 +239,7 @@ def messages_to_openai_spec(messages: list[Message]) -> list[dict[str, any]]:
                             "content": [
                                 {
                                     "type": "image_url",
-                                    "image_url": {"url": f"data:image/jpeg;base64,{encode_image(image_path)}"},
+                                    "image_url": {"url": f"data:image/jpeg;base64,{encoded_image}"},
                                 }
                             ],
                         }
@@ -127,8 +281,49 @@ def tools_to_openai_spec(tools: tuple[Tool, ...]) -> dict[str, any]:
     return result
 
 
-def openai_response_to_message(response: dict) -> Message:
-    original = response["choices"][0]["message"]
+def tool_choice_to_openai_spec(tool_choice: ToolChoice) -> Union[str, dict[str, any]]:
+    if tool_choice.name:
+        return {"type": "function", "function": {"name": tool_choice.name}}
+    return tool_choice.mode
+
+
+def strip_trailing_commas(text: str) -> str:
+    """Remove the commas right before a closing bracket or brace, leaving the contents of strings alone"""
+    result = []
+    in_string = escaped = False
+    for index, char in enumerate(text):
+        if in_string:
+            if escaped:
+                escaped = False
+            elif char == "\\":
+                escaped = True
+            elif char == '"':
+                in_string = False
+        elif char == '"':
+            in_string = True
+        elif char == "," and text[index + 1 :].lstrip()[:1] in ("}", "]"):
+            continue
+        result.append(char)
+    return "".join(result)
+
+
+def parse_tool_arguments(arguments: str) -> dict[str, any]:
+    """Parse the JSON arguments of a tool call, recovering from common model mistakes
+
+    Models occasionally emit raw newlines inside strings, which are accepted as they are, or trailing
+    commas. When the parse fails, retry without those commas before giving up with the original error.
+    """
+    try:
+        return json.loads(arguments, strict=False)
+    except json.JSONDecodeError as error:
+        try:
+            return json.loads(strip_trailing_commas(arguments), strict=False)
+        except json.JSONDecodeError:
+            raise error
+
+
+def openai_response_to_message(response: dict, choice: int = 0) -> Message:
+    original = response["choices"][choice]["message"]
     content = []
     text = original.get("content")
     if text:
@@ -156,7 +351,7 @@ def openai_response_to_message(response: dict) -> Message:
                         ToolUse(
                             id=tool_call["id"],
                             name=function_name,
-                            parameters=json.loads(tool_call["function"]["arguments"]),
+                            parameters=parse_tool_arguments(tool_call["function"]["arguments"]),
                         )
                     )
             except json.JSONDecodeError:
@@ -173,6 +368,37 @@ def openai_response_to_message(response: dict) -> Message:
     return Message(role="assistant", content=content)
 
 
+def openai_response_to_messages(response: dict) -> list[Message]:
+    """Parse every choice of a response, such as the candidates sampled with the n parameter"""
+    return [openai_response_to_message(response, choice) for choice in range(len(response["choices"]))]
+
+
+def normalize_messages(messages: list[Message]) -> list[Message]:
+    """Coalesce consecutive messages with the same role, for providers that require the roles to alternate
+
+    Raises a ValueError if a tool result does not answer a tool use in the assistant message before it,
+    which those providers reject as well.
+    """
+    normalized = []
+    for message in messages:
+        if normalized and normalized[-1].role == message.role:
+            normalized[-1] = evolve(normalized[-1], content=normalized[-1].content + message.content)
+        else:
+            normalized.append(message)
+
+    requested = set()
+    for message in normalized:
+        if message.role == "assistant":
+            requested = {tool_use.id for tool_use in message.tool_use}
+            continue
+        for tool_result in message.tool_result:
+            if tool_result.tool_use_id not in requested:
+                raise ValueError(
+                    f"The tool result for {tool_result.tool_use_id} does not follow an assistant message that used it"
+                )
+    return normalized
+
+
 def openai_single_message_context_length_exceeded(error_dict: dict) -> None:
     code = error_dict.get("code")
     if code == "context_length_exceeded" or code == "string_above_max_length":
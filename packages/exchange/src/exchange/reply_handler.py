@@ -0,0 +1,26 @@
+from exchange.content import ToolResult, ToolUse
+from exchange.message import Message
+
+
+class ReplyHandler:
+    """Receives the parts of a reply as Exchange.reply produces them
+
+    Subclass it and override the callbacks of interest, e.g. to show a reply in a UI
+    without going through the message content. Every callback does nothing by default.
+    """
+
+    def on_text(self, text: str) -> None:
+        """Called with the text of each message the model generates, when it has any"""
+        pass
+
+    def on_tool_request(self, tool_use: ToolUse) -> None:
+        """Called for each tool call the model asks for, before it is made"""
+        pass
+
+    def on_tool_response(self, tool_result: ToolResult) -> None:
+        """Called with the result of each tool call"""
+        pass
+
+    def on_finish(self, message: Message) -> None:
+        """Called once with the message the reply ends on"""
+        pass
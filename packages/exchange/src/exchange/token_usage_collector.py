@@ -13,13 +13,7 @@ class _TokenUsageCollector:
     def get_token_usage_group_by_model(self) -> dict[str, Usage]:
         usage_group_by_model = defaultdict(lambda: Usage(0, 0, 0))
         for model, usage in self.usage_data:
-            usage_by_model = usage_group_by_model[model]
-            if usage is not None and usage.input_tokens is not None:
-                usage_by_model.input_tokens += usage.input_tokens
-            if usage is not None and usage.output_tokens is not None:
-                usage_by_model.output_tokens += usage.output_tokens
-            if usage is not None and usage.total_tokens is not None:
-                usage_by_model.total_tokens += usage.total_tokens
+            usage_group_by_model[model].add(usage)
         return usage_group_by_model
 
 
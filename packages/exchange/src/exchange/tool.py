@@ -1,4 +1,5 @@
 import inspect
+from typing import Callable, Literal, Optional, Union
 
 from attrs import define
 
@@ -14,15 +15,22 @@ class Tool:
         description (str): A description of what the tool does
         parameters dict[str, any]: A json schema of the function signature
         function (Callable): The python function that powers the tool
+        read_only (bool or Callable): Whether the tool only reads state, so calling it again is safe,
+            or a function of the call's parameters for a tool whose commands only read some of the time
     """
 
     name: str
     description: str
     parameters: dict[str, any]
     function: callable
+    read_only: Union[bool, Callable[[dict[str, any]], bool]] = False
 
     @classmethod
-    def from_function(cls: type["Tool"], func: any) -> "Tool":  # noqa: ANN401
+    def from_function(
+        cls: type["Tool"],
+        func: any,  # noqa: ANN401
+        read_only: Union[bool, Callable[[dict[str, any]], bool]] = False,
+    ) -> "Tool":
         """Create a tool instance from a function and its docstring
 
         The function must have a docstring - we require it to load the description
@@ -51,4 +59,30 @@ class Tool:
             description=description,
             parameters=schema,
             function=func,
+            read_only=read_only,
         )
+
+    def reads_only(self, parameters: dict[str, any]) -> bool:
+        """Whether calling the tool with these parameters only reads state"""
+        if callable(self.read_only):
+            return bool(self.read_only(parameters))
+        return self.read_only
+
+
+@define(frozen=True)
+class ToolChoice:
+    """Constrains which tools the model may call in its next message
+
+    Attributes:
+        mode (str): "auto" lets the model decide, "none" forbids calling tools and
+            "required" makes it call at least one
+        name (str, optional): The name of a tool the model must call
+    """
+
+    mode: Literal["auto", "none", "required"] = "auto"
+    name: Optional[str] = None
+
+    @classmethod
+    def named(cls: type["ToolChoice"], name: str) -> "ToolChoice":
+        """Force the model to call the tool with this name"""
+        return cls(mode="required", name=name)
@@ -0,0 +1,59 @@
+import subprocess
+from enum import Enum
+from typing import Optional
+
+
+class ToolErrorCategory(str, Enum):
+    """A machine readable classification of why a tool call failed"""
+
+    NOT_FOUND = "not_found"
+    PERMISSION_DENIED = "permission_denied"
+    INVALID_ARGS = "invalid_args"
+    TIMEOUT = "timeout"
+    TRANSIENT = "transient"
+    CANCELLED = "cancelled"
+
+    @property
+    def hint(self) -> str:
+        return {
+            ToolErrorCategory.NOT_FOUND: "Check the name or path, or install the missing tool before trying again.",
+            ToolErrorCategory.PERMISSION_DENIED: "Do not retry as is, ask the user for access or use another location.",
+            ToolErrorCategory.INVALID_ARGS: "Fix the arguments to match the tool description before trying again.",
+            ToolErrorCategory.TIMEOUT: (
+                "This may succeed if tried again with more time, otherwise try a smaller or non-interactive "
+                "operation, or ask the user to run it."
+            ),
+            ToolErrorCategory.TRANSIENT: "This may succeed if tried again.",
+            ToolErrorCategory.CANCELLED: "The user stopped this call, do not retry it unless they ask you to.",
+        }[self]
+
+    @property
+    def retryable(self) -> bool:
+        """Whether the same call may succeed if it is made again"""
+        return self in (ToolErrorCategory.TIMEOUT, ToolErrorCategory.TRANSIENT)
+
+
+class ToolError(Exception):
+    """An error raised by a tool that knows why it failed"""
+
+    def __init__(self, message: str, category: ToolErrorCategory) -> None:
+        self.message = message
+        self.category = category
+        super().__init__(self.message)
+
+
+def classify_error(error: Exception) -> Optional[ToolErrorCategory]:
+    """Find the category of an error raised by a tool, if it can be recognized"""
+    if isinstance(error, ToolError):
+        return error.category
+    if isinstance(error, FileNotFoundError):
+        return ToolErrorCategory.NOT_FOUND
+    if isinstance(error, PermissionError):
+        return ToolErrorCategory.PERMISSION_DENIED
+    if isinstance(error, (TimeoutError, subprocess.TimeoutExpired)):
+        return ToolErrorCategory.TIMEOUT
+    if isinstance(error, ConnectionError):
+        return ToolErrorCategory.TRANSIENT
+    if isinstance(error, TypeError):
+        return ToolErrorCategory.INVALID_ARGS
+    return None
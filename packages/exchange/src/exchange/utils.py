@@ -141,6 +141,48 @@ def _map_type_to_schema(py_type: type) -> dict[str, Any]:
         return {"type": "string"}
 
 
+def coerce_parameters(parameters: dict[str, Any], schema: dict[str, Any]) -> dict[str, Any]:
+    """Coerce string encoded numbers and booleans to the types declared in a json schema
+
+    Models sometimes send `"5"` or `"true"` where the schema asks for an integer or boolean.
+    Values that cannot be interpreted as the declared type are left unchanged, so that they
+    fail in the tool as they would have otherwise.
+    """
+    properties = schema.get("properties", {})
+    coerced = dict(parameters)
+    for name, value in parameters.items():
+        if isinstance(value, str) and name in properties:
+            coerced[name] = _coerce_value(value, properties[name])
+    return coerced
+
+
+def _coerce_value(value: str, schema: dict[str, Any]) -> Any:  # noqa: ANN401
+    declared = schema.get("type")
+    types = [declared] if isinstance(declared, str) else list(declared or [])
+    for option in schema.get("anyOf", []):
+        if isinstance(option.get("type"), str):
+            types.append(option["type"])
+
+    if "string" in types:
+        return value
+
+    text = value.strip()
+    for declared_type in types:
+        if declared_type == "boolean" and text.lower() in ("true", "false"):
+            return text.lower() == "true"
+        if declared_type == "integer":
+            try:
+                return int(text)
+            except ValueError:
+                pass
+        if declared_type == "number":
+            try:
+                return float(text)
+            except ValueError:
+                pass
+    return value
+
+
 def load_plugins(group: str) -> dict:
     """
     Load plugins based on a specified entry point group.
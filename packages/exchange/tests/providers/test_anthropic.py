@@ -121,6 +121,58 @@ def test_messages_to_anthropic_spec() -> None:
     assert actual_spec == expected_spec
 
 
+
+def test_messages_to_anthropic_spec_sends_images_in_tool_results() -> None:
+    messages = [
+        Message(role="user", content=[Text("What does the screen show?")]),
+        Message(role="assistant", content=[ToolUse(id="xyz", name="screenshot", parameters={})]),
+        Message(role="user", content=[ToolResult(tool_use_id="xyz", output='"image:tests/test_image.png"')]),
+    ]
+    actual_spec = AnthropicProvider.messages_to_anthropic_spec(messages)
+
+    [tool_result] = actual_spec[2]["content"]
+    [image] = tool_result["content"]
+    assert tool_result["tool_use_id"] == "xyz"
+    assert image["type"] == "image"
+    assert image["source"]["type"] == "base64"
+    assert image["source"]["media_type"] == "image/png"
+    assert image["source"]["data"]
+    assert AnthropicProvider.CAPABILITIES.images
+
+
+def test_messages_to_anthropic_spec_coalesces_assistant_messages() -> None:
+    messages = [
+        Message(role="user", content=[Text("Hello, Claude")]),
+        Message(role="assistant", content=[Text("Calling it")]),
+        Message(role="assistant", content=[ToolUse(id="1", name="example_fn", parameters={"param": "value"})]),
+        Message(role="user", content=[ToolResult(tool_use_id="1", output="Result")]),
+    ]
+    actual_spec = AnthropicProvider.messages_to_anthropic_spec(messages)
+    assert [message["role"] for message in actual_spec] == ["user", "assistant", "user"]
+    assert actual_spec[1]["content"] == [
+        {"type": "text", "text": "Calling it"},
+        {"type": "tool_use", "id": "1", "name": "example_fn", "input": {"param": "value"}},
+    ]
+
+
+def test_anthropic_generation_args(anthropic_provider):
+    response = httpx.Response(
+        200,
+        json={"content": [{"type": "text", "text": "Hi"}], "usage": {"input_tokens": 10, "output_tokens": 1}},
+        request=httpx.Request("POST", "https://api.anthropic.com/v1/messages"),
+    )
+    with patch.object(AnthropicProvider, "_post", return_value=response) as mock_post:
+        anthropic_provider.complete(
+            "claude-3-5-sonnet-20240620", "system", [Message.user("Hello")], (), stop=["END"], top_p=0.5, temperature=0
+        )
+
+    payload = mock_post.call_args.args[0]
+    assert payload["stop_sequences"] == ["END"]
+    assert "stop" not in payload
+    assert payload["top_p"] == 0.5
+    assert payload["temperature"] == 0
+
+
 @patch("httpx.Client.post")
 @patch("logging.warning")
 @patch("logging.error")
@@ -150,6 +202,7 @@ def test_anthropic_completion(mock_error, mock_warning, mock_post, anthropic_pro
 
     assert reply_message.content == [Text("Hello from Claude!")]
     assert reply_usage.total_tokens == 35
+    assert reply_usage.response_bytes == len(httpx._content.json_dumps(mock_response).encode())
     assert mock_post.call_count == 2
     mock_post.assert_any_call(
         "https://api.anthropic.com/v1/messages",
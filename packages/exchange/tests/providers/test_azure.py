@@ -1,8 +1,9 @@
 import os
 from unittest.mock import patch
 
+import httpx
 import pytest
-from exchange import Text, ToolUse
+from exchange import Message, Text, ToolUse
 from exchange.providers.azure import AzureProvider
 from exchange.providers.base import MissingProviderEnvVariableError
 
@@ -39,6 +40,52 @@ def test_from_env_throw_error_when_missing_env_var(env_var_name):
         assert context.value.message == f"Missing environment variables: {env_var_name} for provider azure."
 
 
+def test_azure_deployment_url_and_api_key_header():
+    requests = []
+
+    def handler(request: httpx.Request) -> httpx.Response:
+        requests.append(request)
+        return httpx.Response(
+            200,
+            json={
+                "choices": [{"message": {"role": "assistant", "content": "Hello!"}}],
+                "usage": {"prompt_tokens": 10, "completion_tokens": 2, "total_tokens": 12},
+            },
+        )
+
+    client_cls = httpx.Client
+
+    def client_with_mock_transport(**kwargs):
+        return client_cls(transport=httpx.MockTransport(handler), **kwargs)
+
+    env = {
+        "AZURE_CHAT_COMPLETIONS_HOST_NAME": "https://my-resource.openai.azure.com",
+        "AZURE_CHAT_COMPLETIONS_DEPLOYMENT_NAME": "my-deployment",
+        "AZURE_CHAT_COMPLETIONS_DEPLOYMENT_API_VERSION": "2024-10-21",
+        "AZURE_CHAT_COMPLETIONS_KEY": "test_api_key",
+    }
+    with (
+        patch.dict(os.environ, env, clear=True),
+        patch("exchange.providers.azure.httpx.Client", side_effect=client_with_mock_transport),
+    ):
+        provider = AzureProvider.from_env()
+
+    provider.complete(AZURE_MODEL, "You are a helpful assistant.", [Message.user("Hi")], ())
+
+    assert str(requests[0].url) == (
+        "https://my-resource.openai.azure.com/openai/deployments/my-deployment/chat/completions?api-version=2024-10-21"
+    )
+    assert requests[0].headers["api-key"] == "test_api_key"
+    assert "Authorization" not in requests[0].headers
+
+
+def test_azure_lists_its_deployment_as_the_model():
+    client = httpx.Client(transport=httpx.MockTransport(lambda _: httpx.Response(404)))
+
+    assert AzureProvider(client, deployment_name="my-deployment").list_models() == ["my-deployment"]
+    assert AzureProvider(client).list_models() == ["gpt-4o", "gpt-4o-mini"]
+
+
 @pytest.mark.vcr()
 def test_azure_complete(default_azure_env):
     reply_message, reply_usage = complete(AzureProvider, AZURE_MODEL)
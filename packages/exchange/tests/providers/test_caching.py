@@ -0,0 +1,104 @@
+import os
+
+from exchange import Message, Text
+from exchange.providers.base import Provider, Usage
+from exchange.providers.caching import CachingProvider
+
+
+class CountingProvider(Provider):
+    PROVIDER_NAME = "counting"
+
+    def __init__(self) -> None:
+        self.calls = 0
+
+    def complete(self, model, system, messages, tools, **kwargs):
+        self.calls += 1
+        return Message(role="assistant", content=[Text(f"reply {self.calls}")]), Usage(3, 2, 5)
+
+
+class Clock:
+    def __init__(self) -> None:
+        self.now = 1000.0
+
+    def __call__(self) -> float:
+        return self.now
+
+
+def test_cache_hit_and_miss():
+    inner = CountingProvider()
+    provider = CachingProvider(inner)
+
+    first, usage = provider.complete("gpt-4o", "system", [Message.user("hi")], (), temperature=0)
+    # a new message with the same content has another id, but is the same request
+    second, _ = provider.complete("gpt-4o", "system", [Message.user("hi")], (), temperature=0)
+    other, _ = provider.complete("gpt-4o", "system", [Message.user("bye")], (), temperature=0)
+
+    assert first.text == second.text == "reply 1"
+    assert usage.total_tokens == 5
+    assert other.text == "reply 2"
+    assert inner.calls == 2
+
+
+def test_cache_skips_nondeterministic_requests():
+    inner = CountingProvider()
+    provider = CachingProvider(inner)
+
+    provider.complete("gpt-4o", "system", [Message.user("hi")], ())
+    provider.complete("gpt-4o", "system", [Message.user("hi")], ())
+    assert inner.calls == 2
+
+    provider = CachingProvider(inner, cache_nondeterministic=True)
+    provider.complete("gpt-4o", "system", [Message.user("hi")], (), temperature=0.7)
+    provider.complete("gpt-4o", "system", [Message.user("hi")], (), temperature=0.7)
+    assert inner.calls == 3
+
+
+def test_cache_ttl_expiry():
+    inner = CountingProvider()
+    clock = Clock()
+    provider = CachingProvider(inner, ttl=60, clock=clock)
+
+    provider.complete("gpt-4o", "system", [Message.user("hi")], (), temperature=0)
+    clock.now += 30
+    assert provider.complete("gpt-4o", "system", [Message.user("hi")], (), temperature=0)[0].text == "reply 1"
+    clock.now += 31
+    assert provider.complete("gpt-4o", "system", [Message.user("hi")], (), temperature=0)[0].text == "reply 2"
+
+
+def test_cache_evicts_least_recently_used():
+    inner = CountingProvider()
+    provider = CachingProvider(inner, max_entries=2)
+
+    for text in ["a", "b", "a", "c", "a", "b"]:
+        provider.complete("gpt-4o", "system", [Message.user(text)], (), temperature=0)
+
+    # b was evicted when c was added, since a had been used more recently
+    assert inner.calls == 4
+
+
+def test_cache_on_disk_is_shared(tmp_path):
+    inner = CountingProvider()
+    CachingProvider(inner, path=tmp_path).complete("gpt-4o", "system", [Message.user("hi")], (), temperature=0)
+
+    message, usage = CachingProvider(inner, path=tmp_path).complete(
+        "gpt-4o", "system", [Message.user("hi")], (), temperature=0
+    )
+
+    assert message.text == "reply 1"
+    assert usage.total_tokens == 5
+    assert inner.calls == 1
+    assert len(list(tmp_path.glob("*.json"))) == 1
+
+
+def test_cache_on_disk_only_evicts_its_own_entries(tmp_path):
+    unrelated = tmp_path / "settings.json"
+    unrelated.write_text("{}")
+    os.utime(unrelated, (0, 0))
+    inner = CountingProvider()
+    provider = CachingProvider(inner, max_entries=1, path=tmp_path)
+
+    provider.complete("gpt-4o", "system", [Message.user("a")], (), temperature=0)
+    provider.complete("gpt-4o", "system", [Message.user("b")], (), temperature=0)
+
+    assert unrelated.read_text() == "{}"
+    assert len(list(tmp_path.glob("*.json"))) == 2
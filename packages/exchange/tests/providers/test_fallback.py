@@ -0,0 +1,80 @@
+import httpx
+import pytest
+from exchange import Message, Text
+from exchange.providers import get_provider
+from exchange.providers.base import Provider, ProviderCapabilities, Usage
+from exchange.providers.fallback import FallbackProvider
+
+
+class StubProvider(Provider):
+    PROVIDER_NAME = "stub"
+
+    def __init__(self, reply: str = None, error: Exception = None) -> None:
+        self.reply = reply
+        self.error = error
+        self.calls = 0
+
+    def complete(self, model, system, messages, tools, **kwargs):
+        self.calls += 1
+        if self.error:
+            raise self.error
+        return Message(role="assistant", content=[Text(self.reply)]), Usage(1, 1, 2)
+
+
+def status_error(code: int) -> httpx.HTTPStatusError:
+    request = httpx.Request("POST", "https://example.com/v1/chat/completions")
+    response = httpx.Response(code, request=request)
+    return httpx.HTTPStatusError(f"status {code}", request=request, response=response)
+
+
+@pytest.mark.parametrize(
+    "error",
+    [status_error(503), status_error(429), httpx.ConnectError("connection refused")],
+)
+def test_fallback_uses_next_provider_on_transient_error(error):
+    primary = StubProvider(error=error)
+    backup = StubProvider(reply="from backup")
+    provider = FallbackProvider([primary, backup])
+
+    message, usage = provider.complete("gpt-4o", "system", [Message.user("hi")], ())
+
+    assert message.text == "from backup"
+    assert usage.total_tokens == 2
+    assert primary.calls == 1
+    assert backup.calls == 1
+
+
+def test_fallback_raises_non_transient_error():
+    primary = StubProvider(error=status_error(400))
+    backup = StubProvider(reply="from backup")
+    provider = FallbackProvider([primary, backup])
+
+    with pytest.raises(httpx.HTTPStatusError):
+        provider.complete("gpt-4o", "system", [Message.user("hi")], ())
+    assert backup.calls == 0
+
+
+def test_fallback_raises_last_error_when_all_fail():
+    provider = FallbackProvider([StubProvider(error=status_error(500)), StubProvider(error=status_error(502))])
+
+    with pytest.raises(httpx.HTTPStatusError) as error:
+        provider.complete("gpt-4o", "system", [Message.user("hi")], ())
+    assert error.value.response.status_code == 502
+
+
+def test_fallback_capabilities_are_what_every_provider_supports():
+    text_only = StubProvider("hi")
+    text_only.CAPABILITIES = ProviderCapabilities(tools=True, images=False, tool_choice=False)
+    provider = FallbackProvider([StubProvider("hi"), text_only])
+
+    assert provider.capabilities() == ProviderCapabilities(tools=True, images=False, tool_choice=False)
+
+
+def test_fallback_from_env(monkeypatch):
+    monkeypatch.setenv("FALLBACK_PROVIDERS", "openai, groq")
+    monkeypatch.setenv("OPENAI_API_KEY", "test_api_key")
+    monkeypatch.setenv("GROQ_API_KEY", "test_api_key")
+
+    provider = get_provider("fallback").from_env()
+
+    assert [p.name for p in provider.providers] == ["openai", "groq"]
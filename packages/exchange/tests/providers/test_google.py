@@ -1,6 +1,7 @@
 import os
 from unittest.mock import patch
 
+import httpx
 import pytest
 from exchange import Message, Text
 from exchange.content import ToolResult, ToolUse
@@ -101,6 +102,30 @@ def test_messages_to_google_spec() -> None:
     assert actual_spec == expected_spec
 
 
+def test_google_records_payload_sizes():
+    requests = []
+
+    def handler(request: httpx.Request) -> httpx.Response:
+        requests.append(request)
+        return httpx.Response(
+            200,
+            json={
+                "candidates": [{"content": {"parts": [{"text": "Hello!"}]}}],
+                "usageMetadata": {"promptTokenCount": 10, "candidatesTokenCount": 2},
+            },
+        )
+
+    client = httpx.Client(
+        base_url="https://generativelanguage.googleapis.com/v1beta/", transport=httpx.MockTransport(handler)
+    )
+    provider = GoogleProvider(client)
+
+    _, usage = provider.complete(GOOGLE_MODEL, "system", [Message.user("Hi")], ())
+
+    assert usage.request_bytes == len(requests[0].content)
+    assert usage.response_bytes > 0
+
+
 @pytest.mark.vcr()
 def test_google_complete(default_google_env):
     reply_message, reply_usage = complete(GoogleProvider, GOOGLE_MODEL)
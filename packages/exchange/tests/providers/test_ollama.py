@@ -1,5 +1,6 @@
 import os
 
+import httpx
 import pytest
 
 from exchange import Text, ToolUse
@@ -9,6 +10,20 @@ from .conftest import complete, tools
 OLLAMA_MODEL = os.getenv("OLLAMA_MODEL", OLLAMA_MODEL)
 
 
+def test_ollama_list_models():
+    requests = []
+
+    def handler(request: httpx.Request) -> httpx.Response:
+        requests.append(request)
+        models = [{"name": "qwen2.5:latest", "size": 4683087332}, {"name": "llama3.2:latest", "size": 2019393189}]
+        return httpx.Response(200, json={"models": models})
+
+    client = httpx.Client(base_url="http://localhost:11434/v1/", transport=httpx.MockTransport(handler))
+
+    assert OllamaProvider(client).list_models() == ["llama3.2:latest", "qwen2.5:latest"]
+    assert str(requests[0].url) == "http://localhost:11434/api/tags"
+
+
 @pytest.mark.vcr()
 def test_ollama_complete():
     reply_message, reply_usage = complete(OllamaProvider, OLLAMA_MODEL)
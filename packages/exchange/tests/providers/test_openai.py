@@ -1,9 +1,14 @@
+import json
 import os
+import time
+from importlib.metadata import version
 from unittest.mock import patch
 
+import httpx
 import pytest
-from exchange import Text, ToolUse
-from exchange.providers.base import MissingProviderEnvVariableError
+from exchange import Message, Text, Tool, ToolChoice, ToolUse
+from exchange.message import EmptyResponseError
+from exchange.providers.base import MODEL_LIST_TTL, AccountInfo, MissingProviderEnvVariableError
 from exchange.providers.openai import OpenAiProvider
 from .conftest import complete, vision, tools
 
@@ -20,6 +25,211 @@ def test_from_env_throw_error_when_missing_api_key():
         assert "https://platform.openai.com" in context.value.message
 
 
+def test_openai_extra_headers_and_completions_path(monkeypatch):
+    monkeypatch.setenv("OPENAI_API_KEY", "test_api_key")
+    monkeypatch.setenv("OPENAI_HOST", "https://gateway.example.com/")
+    monkeypatch.setenv("OPENAI_EXTRA_HEADERS", '{"X-Title": "goose", "HTTP-Referer": "https://example.com"}')
+    monkeypatch.setenv("OPENAI_COMPLETIONS_PATH", "custom/completions")
+    requests = []
+
+    def handler(request: httpx.Request) -> httpx.Response:
+        requests.append(request)
+        return httpx.Response(
+            200,
+            json={
+                "choices": [{"message": {"role": "assistant", "content": "Hello!"}}],
+                "usage": {"prompt_tokens": 10, "completion_tokens": 2, "total_tokens": 12},
+            },
+        )
+
+    client_cls = httpx.Client
+
+    def client_with_mock_transport(**kwargs):
+        return client_cls(transport=httpx.MockTransport(handler), **kwargs)
+
+    with patch("exchange.providers.openai.httpx.Client", side_effect=client_with_mock_transport):
+        provider = OpenAiProvider.from_env()
+
+    reply_message, _ = provider.complete(OPENAI_MODEL, "You are a helpful assistant.", [Message.user("Hi")], ())
+
+    assert reply_message.content == [Text("Hello!")]
+    assert str(requests[0].url) == "https://gateway.example.com/v1/custom/completions"
+    assert requests[0].headers["X-Title"] == "goose"
+    assert requests[0].headers["HTTP-Referer"] == "https://example.com"
+
+
+def test_openai_requests_carry_user_agent_and_request_id(monkeypatch):
+    monkeypatch.setenv("OPENAI_API_KEY", "test_api_key")
+    monkeypatch.delenv("GOOSE_USER_AGENT", raising=False)
+    requests = []
+
+    def handler(request: httpx.Request) -> httpx.Response:
+        requests.append(request)
+        return httpx.Response(
+            200,
+            json={
+                "choices": [{"message": {"role": "assistant", "content": "Hello!"}}],
+                "usage": {"prompt_tokens": 10, "completion_tokens": 2, "total_tokens": 12},
+            },
+        )
+
+    client_cls = httpx.Client
+
+    def client_with_mock_transport(**kwargs):
+        return client_cls(transport=httpx.MockTransport(handler), **kwargs)
+
+    with patch("exchange.providers.openai.httpx.Client", side_effect=client_with_mock_transport):
+        provider = OpenAiProvider.from_env()
+
+    provider.complete(OPENAI_MODEL, "You are a helpful assistant.", [Message.user("Hi")], ())
+    provider.complete(OPENAI_MODEL, "You are a helpful assistant.", [Message.user("Hi")], ())
+
+    assert requests[0].headers["User-Agent"] == f"goose/{version('ai-exchange')}"
+    request_ids = [request.headers["X-Request-Id"] for request in requests]
+    assert all(request_ids) and request_ids[0] != request_ids[1]
+
+
+def test_openai_capabilities():
+    capabilities = OpenAiProvider(httpx.Client()).capabilities()
+
+    assert capabilities.tools and capabilities.images and capabilities.tool_choice
+
+
+def test_openai_extra_headers_must_be_an_object(monkeypatch):
+    monkeypatch.setenv("OPENAI_EXTRA_HEADERS", '["X-Title"]')
+    with pytest.raises(ValueError):
+        OpenAiProvider.extra_headers_from_env()
+
+
+def get_weather(location: str) -> str:
+    """Get the weather for a location
+
+    Args:
+        location (str): The city to get the weather for
+    """
+    return "sunny"
+
+
+@pytest.mark.parametrize(
+    "tool_choice,expected",
+    [
+        (ToolChoice(), "auto"),
+        (ToolChoice(mode="none"), "none"),
+        (ToolChoice(mode="required"), "required"),
+        (ToolChoice.named("get_weather"), {"type": "function", "function": {"name": "get_weather"}}),
+    ],
+)
+def test_openai_tool_choice(tool_choice, expected):
+    payloads = []
+
+    def handler(request: httpx.Request) -> httpx.Response:
+        payloads.append(json.loads(request.content))
+        return httpx.Response(
+            200,
+            json={
+                "choices": [{"message": {"role": "assistant", "content": "Hello!"}}],
+                "usage": {"prompt_tokens": 10, "completion_tokens": 2, "total_tokens": 12},
+            },
+        )
+
+    client = httpx.Client(base_url="https://api.openai.com/v1/", transport=httpx.MockTransport(handler))
+    provider = OpenAiProvider(client)
+    tools = (Tool.from_function(get_weather),)
+
+    provider.complete(OPENAI_MODEL, "system", [Message.user("Hi")], tools, tool_choice=tool_choice)
+    provider.complete(OPENAI_MODEL, "system", [Message.user("Hi")], (), tool_choice=tool_choice)
+
+    assert payloads[0]["tool_choice"] == expected
+    assert "tool_choice" not in payloads[1]
+
+
+def test_openai_records_payload_sizes():
+    requests = []
+
+    def handler(request: httpx.Request) -> httpx.Response:
+        requests.append(request)
+        return httpx.Response(
+            200,
+            json={
+                "choices": [{"message": {"role": "assistant", "content": "Hello!"}}],
+                "usage": {"prompt_tokens": 10, "completion_tokens": 2, "total_tokens": 12},
+            },
+        )
+
+    client = httpx.Client(base_url="https://api.openai.com/v1/", transport=httpx.MockTransport(handler))
+    provider = OpenAiProvider(client)
+
+    _, usage, response = provider.complete_raw(OPENAI_MODEL, "system", [Message.user("Hi")], ())
+
+    assert usage.request_bytes == len(requests[0].content)
+    assert usage.response_bytes > 0
+    assert response["choices"][0]["message"]["content"] == "Hello!"
+
+
+def test_openai_generation_args():
+    payloads = []
+
+    def handler(request: httpx.Request) -> httpx.Response:
+        payloads.append(json.loads(request.content))
+        return httpx.Response(
+            200,
+            json={
+                "choices": [{"message": {"role": "assistant", "content": "Hello!"}}],
+                "usage": {"prompt_tokens": 10, "completion_tokens": 2, "total_tokens": 12},
+            },
+        )
+
+    client = httpx.Client(base_url="https://api.openai.com/v1/", transport=httpx.MockTransport(handler))
+    provider = OpenAiProvider(client)
+
+    provider.complete(
+        OPENAI_MODEL, "system", [Message.user("Hi")], (), stop=["\n\n", "END"], top_p=0.5, temperature=0
+    )
+
+    assert payloads[0]["stop"] == ["\n\n", "END"]
+    assert payloads[0]["top_p"] == 0.5
+    assert payloads[0]["temperature"] == 0
+    assert "tools" not in payloads[0]
+
+
+def test_openai_complete_raw():
+    body = {
+        "id": "chatcmpl-1",
+        "choices": [{"index": 0, "message": {"role": "assistant", "content": "Hello!"}, "finish_reason": "stop"}],
+        "usage": {"prompt_tokens": 10, "completion_tokens": 2, "total_tokens": 12},
+    }
+    client = httpx.Client(
+        base_url="https://api.openai.com/v1/", transport=httpx.MockTransport(lambda _: httpx.Response(200, json=body))
+    )
+    provider = OpenAiProvider(client)
+
+    message, usage, raw = provider.complete_raw(OPENAI_MODEL, "system", [Message.user("Hi")], ())
+
+    assert message.content == [Text("Hello!")]
+    assert usage.total_tokens == 12
+    assert raw["choices"] == body["choices"]
+
+
+def test_openai_empty_response_carries_its_usage():
+    body = {
+        "choices": [{"index": 0, "message": {"role": "assistant", "content": ""}, "finish_reason": "stop"}],
+        "usage": {"prompt_tokens": 10, "completion_tokens": 0, "total_tokens": 10},
+    }
+    client = httpx.Client(
+        base_url="https://api.openai.com/v1/", transport=httpx.MockTransport(lambda _: httpx.Response(200, json=body))
+    )
+    provider = OpenAiProvider(client)
+
+    with pytest.raises(EmptyResponseError) as error:
+        provider.complete(OPENAI_MODEL, "system", [Message.user("Hi")], ())
+
+    assert error.value.usage.total_tokens == 10
+
+
+def test_openai_name(default_openai_env):
+    assert OpenAiProvider.from_env().name == "openai"
+
+
 @pytest.mark.vcr()
 def test_openai_complete(default_openai_env):
     reply_message, reply_usage = complete(OpenAiProvider, OPENAI_MODEL)
@@ -72,3 +282,88 @@ def test_openai_vision_integration():
     reply = vision(OpenAiProvider, OPENAI_MODEL)
 
     assert "ask goose" in reply[0].text.lower()
+
+
+def test_openai_complete_n_samples_in_one_request():
+    payloads = []
+
+    def handler(request: httpx.Request) -> httpx.Response:
+        payloads.append(json.loads(request.content))
+        return httpx.Response(
+            200,
+            json={
+                "choices": [
+                    {"index": 0, "message": {"role": "assistant", "content": "Hello!"}},
+                    {"index": 1, "message": {"role": "assistant", "content": "Hi there!"}},
+                ],
+                "usage": {"prompt_tokens": 10, "completion_tokens": 5, "total_tokens": 15},
+            },
+        )
+
+    client = httpx.Client(base_url="https://api.openai.com/v1/", transport=httpx.MockTransport(handler))
+    provider = OpenAiProvider(client)
+
+    messages, usage = provider.complete_n(OPENAI_MODEL, "system", [Message.user("Hi")], (), n=2)
+
+    assert len(payloads) == 1
+    assert payloads[0]["n"] == 2
+    assert [message.text for message in messages] == ["Hello!", "Hi there!"]
+    assert usage.total_tokens == 15
+
+
+@pytest.mark.parametrize(
+    "status, headers, expected",
+    [
+        (200, {"openai-organization": "org-goose"}, AccountInfo(key_valid=True, organization="org-goose")),
+        (401, {}, AccountInfo(key_valid=False)),
+    ],
+)
+def test_openai_account_info(status, headers, expected):
+    requests = []
+
+    def handler(request: httpx.Request) -> httpx.Response:
+        requests.append(request)
+        return httpx.Response(status, headers=headers, json={"object": "list", "data": []})
+
+    client = httpx.Client(base_url="https://api.openai.com/v1/", transport=httpx.MockTransport(handler))
+    provider = OpenAiProvider(client)
+
+    assert provider.account_info() == expected
+    assert requests[0].method == "GET"
+    assert requests[0].url.path == "/v1/models"
+
+
+def test_openai_list_models_is_reused_briefly(monkeypatch):
+    requests = []
+
+    def handler(request: httpx.Request) -> httpx.Response:
+        requests.append(request)
+        data = [{"id": "gpt-4o-mini", "object": "model"}, {"id": "gpt-4o", "object": "model"}]
+        return httpx.Response(200, json={"object": "list", "data": data})
+
+    client = httpx.Client(base_url="https://api.openai.com/v1/", transport=httpx.MockTransport(handler))
+    provider = OpenAiProvider(client)
+    now = 1000.0
+    monkeypatch.setattr(time, "monotonic", lambda: now)
+
+    assert provider.list_models() == ["gpt-4o", "gpt-4o-mini"]
+    assert provider.list_models() == ["gpt-4o", "gpt-4o-mini"]
+    assert len(requests) == 1
+    assert requests[0].url.path == "/v1/models"
+
+    now += MODEL_LIST_TTL + 1
+    provider.list_models()
+    assert len(requests) == 2
+
+
+def test_list_models_hands_out_a_copy_of_the_cache():
+    data = [{"id": "gpt-4o", "object": "model"}]
+    client = httpx.Client(
+        base_url="https://api.openai.com/v1/",
+        transport=httpx.MockTransport(lambda _: httpx.Response(200, json={"object": "list", "data": data})),
+    )
+    provider = OpenAiProvider(client)
+
+    provider.list_models().append("changed")
+
+    assert provider.list_models() == ["gpt-4o"]
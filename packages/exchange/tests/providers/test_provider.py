@@ -1,6 +1,7 @@
 import pytest
 from exchange.invalid_choice_error import InvalidChoiceError
 from exchange.providers import get_provider
+from exchange.providers.base import Provider
 
 
 def test_get_provider_valid():
@@ -16,3 +17,19 @@ def test_get_provider_throw_error_for_unknown_provider():
     assert error.value.attribute_value == "nonexistent"
     assert "openai" in error.value.available_values
     assert "openai" in error.value.message
+
+
+
+class RecommendingProvider(Provider):
+    PROVIDER_NAME = "recommending"
+
+    def complete(self, *args: object, **kwargs: object) -> None:
+        raise NotImplementedError
+
+    @staticmethod
+    def recommended_models() -> tuple[str, str]:
+        return "big-model", "big-model"
+
+
+def test_list_models_defaults_to_the_recommended_models():
+    assert RecommendingProvider().list_models() == ["big-model"]
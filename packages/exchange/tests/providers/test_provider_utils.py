@@ -1,5 +1,9 @@
+import base64
 from copy import deepcopy
 import json
+import os
+import struct
+import zlib
 from unittest.mock import Mock
 from attrs import asdict
 import httpx
@@ -9,8 +13,11 @@ from unittest.mock import patch
 from exchange.content import Text, ToolResult, ToolUse
 from exchange.message import Message
 from exchange.providers.utils import (
+    encode_image,
     messages_to_openai_spec,
+    normalize_messages,
     openai_response_to_message,
+    openai_response_to_messages,
     raise_for_status,
     tools_to_openai_spec,
 )
@@ -101,6 +108,43 @@ def test_raise_for_status_failure_without_text() -> None:
         assert e.request is None
 
 
+def test_raise_for_status_failure_names_the_request_id() -> None:
+    request = httpx.Request("POST", "https://api.example.com/v1/chat", headers={"X-Request-Id": "abc-123"})
+    response = httpx.Response(429, text="Slow down", request=request)
+
+    with pytest.raises(httpx.HTTPStatusError) as e:
+        raise_for_status(response)
+
+    assert "(request id abc-123)" in str(e.value)
+    assert str(e.value).endswith("\nSlow down")
+
+
+@pytest.mark.parametrize(
+    "headers,url",
+    [
+        ({"Authorization": "Bearer sk-test-secret"}, "https://api.openai.com/v1/chat/completions"),
+        ({"x-api-key": "sk-test-secret"}, "https://api.anthropic.com/v1/messages"),
+        ({}, "https://generativelanguage.googleapis.com/v1beta/models/gemini:generateContent?key=sk-test-secret"),
+    ],
+)
+def test_raise_for_status_logs_exchange_without_api_key(headers, url) -> None:
+    api_key = "sk-test-secret"
+    payload = {"messages": [{"role": "user", "content": f"is {api_key} my key?"}]}
+    request = httpx.Request("POST", url, headers=headers, json=payload)
+    response = httpx.Response(200, request=request, json={"content": f"{api_key} is your key"})
+
+    with patch("exchange.providers.utils.logger") as mock_logger:
+        mock_logger.isEnabledFor.return_value = True
+        raise_for_status(response)
+
+    logged = [call.args[0] % call.args[1:] for call in mock_logger.debug.call_args_list]
+    assert len(logged) == 2
+    assert '"messages"' in logged[0]
+    assert "is your key" in logged[1]
+    assert all(api_key not in line for line in logged)
+    assert all("[REDACTED]" in line for line in logged)
+
+
 def test_messages_to_openai_spec() -> None:
     messages = [
         Message(role="assistant", content=[Text("Hello!")]),
@@ -138,6 +182,20 @@ def test_messages_to_openai_spec() -> None:
     ]
 
 
+def test_messages_to_openai_spec_skips_whitespace_assistant_text() -> None:
+    messages = [
+        Message(role="assistant", content=[Text(" \n"), ToolUse(id="1", name="tool1", parameters={})]),
+        Message(role="user", content=[ToolResult(tool_use_id="1", output="  ")]),
+        Message(role="assistant", content=[Text("\n\n")]),
+    ]
+
+    spec = messages_to_openai_spec(messages)
+
+    assert "content" not in spec[0]
+    assert spec[1]["content"] == "  "
+    assert spec[2:] == []
+
+
 def test_tools_to_openai_spec() -> None:
     tools = (Tool.from_function(example_fn), Tool.from_function(example_fn_two))
     assert len(tools_to_openai_spec(tools)) == 2
@@ -209,6 +267,20 @@ def test_openai_response_to_message_text() -> None:
     assert actual == expect
 
 
+def test_openai_response_to_messages_parses_every_choice() -> None:
+    response = {
+        "choices": [
+            {"index": 0, "message": {"role": "assistant", "content": "First answer"}},
+            {"index": 1, "message": {"role": "assistant", "content": "Second answer"}},
+        ],
+    }
+
+    messages = openai_response_to_messages(response)
+
+    assert [message.text for message in messages] == ["First answer", "Second answer"]
+    assert openai_response_to_message(response).text == "First answer"
+
+
 def test_openai_response_to_message_valid_tooluse() -> None:
     response = deepcopy(OPEN_AI_TOOL_USE_RESPONSE)
     message = openai_response_to_message(response)
@@ -243,3 +315,89 @@ def test_openai_response_to_message_json_decode_error(mock_json) -> None:
     assert message.content[0].name == "example_fn"
     assert message.content[0].is_error
     assert message.content[0].error_message.startswith("Could not interpret tool use")
+
+
+@pytest.mark.parametrize(
+    "arguments,expected",
+    [
+        ('{"param": "value",}', {"param": "value"}),
+        ('{"items": [1, 2, ], }', {"items": [1, 2]}),
+        ('{"param": "line one\nline two"}', {"param": "line one\nline two"}),
+        ('{"new_str": "[1,\n]"}', {"new_str": "[1,\n]"}),
+        ('{"new_str": "f(a, )", "escaped": "\\"x, }",}', {"new_str": "f(a, )", "escaped": '"x, }'}),
+    ],
+)
+def test_openai_response_to_message_recovers_malformed_arguments(arguments, expected) -> None:
+    response = deepcopy(OPEN_AI_TOOL_USE_RESPONSE)
+    response["choices"][0]["message"]["tool_calls"][0]["function"]["arguments"] = arguments
+    message = openai_response_to_message(response)
+    assert not message.content[0].is_error
+    assert message.content[0].parameters == expected
+
+
+def test_openai_response_to_message_garbage_arguments() -> None:
+    response = deepcopy(OPEN_AI_TOOL_USE_RESPONSE)
+    response["choices"][0]["message"]["tool_calls"][0]["function"]["arguments"] = "{param: value"
+    message = openai_response_to_message(response)
+    assert message.content[0].is_error
+    assert message.content[0].parameters == "{param: value"
+    assert message.content[0].error_message.startswith("Could not interpret tool use")
+
+
+def noise_png(width: int, height: int) -> bytes:
+    """An RGB png of random pixels, which doesn't compress"""
+
+    def chunk(kind: bytes, data: bytes) -> bytes:
+        return struct.pack(">I", len(data)) + kind + data + struct.pack(">I", zlib.crc32(kind + data))
+
+    rows = b"".join(b"\x00" + os.urandom(width * 3) for _ in range(height))
+    header = struct.pack(">IIBBBBB", width, height, 8, 2, 0, 0, 0)
+    return b"\x89PNG\r\n\x1a\n" + chunk(b"IHDR", header) + chunk(b"IDAT", zlib.compress(rows)) + chunk(b"IEND", b"")
+
+
+def test_encode_image_under_budget_is_unchanged(tmp_path) -> None:
+    image = noise_png(16, 16)
+    image_path = tmp_path / "image.png"
+    image_path.write_bytes(image)
+
+    assert base64.b64decode(encode_image(str(image_path), max_bytes=1024 * 1024)) == image
+
+
+def test_encode_image_downscales_oversized_image(tmp_path) -> None:
+    image_path = tmp_path / "image.png"
+    image_path.write_bytes(noise_png(512, 512))
+    budget = 100 * 1024
+
+    encoded = encode_image(str(image_path), max_bytes=budget)
+
+    assert len(encoded) <= budget
+    assert base64.b64decode(encoded).startswith(b"\x89PNG")
+
+
+def test_normalize_messages_coalesces_adjacent_assistant_messages():
+    messages = [
+        Message.user("What is the weather in Paris?"),
+        Message(role="assistant", content=[Text("Let me check.")]),
+        Message(role="assistant", content=[ToolUse(id="1", name="get_weather", parameters={"city": "Paris"})]),
+        Message(role="user", content=[ToolResult(tool_use_id="1", output="sunny")]),
+    ]
+
+    normalized = normalize_messages(messages)
+
+    assert [message.role for message in normalized] == ["user", "assistant", "user"]
+    assert normalized[1].id == messages[1].id
+    assert normalized[1].content == messages[1].content + messages[2].content
+    assert normalized[2].tool_result[0].tool_use_id == normalized[1].tool_use[0].id
+
+
+def test_normalize_messages_rejects_an_unanswered_tool_result():
+    messages = [
+        Message.user("What is the weather in Paris?"),
+        Message(role="assistant", content=[ToolUse(id="1", name="get_weather", parameters={"city": "Paris"})]),
+        Message(role="user", content=[ToolResult(tool_use_id="1", output="sunny")]),
+        Message(role="assistant", content=[Text("It is sunny.")]),
+        Message(role="user", content=[ToolResult(tool_use_id="1", output="sunny")]),
+    ]
+
+    with pytest.raises(ValueError, match="tool result for 1"):
+        normalize_messages(messages)
@@ -0,0 +1,69 @@
+import pytest
+
+from exchange import Exchange, Message, Text, Tool, ToolUse
+from exchange.moderators import PassiveModerator
+from exchange.providers.base import Provider, Usage
+from exchange.providers.replay import ReplayMismatchError, ReplayProvider
+
+
+class ScriptedProvider(Provider):
+    PROVIDER_NAME = "scripted"
+
+    def __init__(self, replies: list[Message]) -> None:
+        self.replies = list(replies)
+
+    def complete(self, model, system, messages, tools, **kwargs):
+        return self.replies.pop(0), Usage(10, 5, 15)
+
+
+def lookup_weather(city: str) -> str:
+    """Look up the weather
+
+    Args:
+        city (str): The city to look up
+    """
+    return f"sunny in {city}"
+
+
+def weather_exchange(provider: Provider) -> Exchange:
+    return Exchange(
+        provider=provider,
+        model="gpt-4o",
+        system="You are a weather assistant.",
+        moderator=PassiveModerator(),
+        tools=(Tool.from_function(lookup_weather),),
+    )
+
+
+def test_replay_multi_turn_tool_calls(tmp_path):
+    path = tmp_path / "weather.jsonl"
+    replies = [
+        Message(role="assistant", content=[ToolUse(id="1", name="lookup_weather", parameters={"city": "Paris"})]),
+        Message(role="assistant", content=[Text("It is sunny in Paris.")]),
+    ]
+    recording = weather_exchange(ReplayProvider(path, ScriptedProvider(replies)))
+    recording.add(Message.user("What is the weather in Paris?"))
+    recorded = recording.reply()
+
+    replaying = weather_exchange(ReplayProvider(path))
+    replaying.add(Message.user("What is the weather in Paris?"))
+    replayed = replaying.reply()
+
+    assert replayed.text == recorded.text == "It is sunny in Paris."
+    assert [m.to_dict()["content"] for m in replaying.messages] == [m.to_dict()["content"] for m in recording.messages]
+    assert replaying.messages[2].content[0].output == '"sunny in Paris"'
+
+
+def test_replay_raises_when_the_conversation_diverges(tmp_path):
+    path = tmp_path / "weather.jsonl"
+    provider = ReplayProvider(path, ScriptedProvider([Message.assistant("Hello")]))
+    provider.complete("gpt-4o", "system", [Message.user("hi")], ())
+
+    replaying = ReplayProvider(path)
+    with pytest.raises(ReplayMismatchError, match="completion 0 .* differs"):
+        replaying.complete("gpt-4o", "system", [Message.user("bye")], ())
+
+    replaying = ReplayProvider(path)
+    replaying.complete("gpt-4o", "system", [Message.user("hi")], ())
+    with pytest.raises(ReplayMismatchError, match="only 1 completions were recorded"):
+        replaying.complete("gpt-4o", "system", [Message.user("hi")], ())
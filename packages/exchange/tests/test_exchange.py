@@ -1,12 +1,22 @@
+import time
+from concurrent.futures import Future
+from concurrent.futures import TimeoutError as FuturesTimeoutError
+from threading import Event
+from typing import Callable, Optional, Union
+
 import pytest
 
+from exchange import exchange as exchange_module
 from exchange.checkpoint import Checkpoint, CheckpointData
 from exchange.content import Text, ToolResult, ToolUse
-from exchange.exchange import Exchange
-from exchange.message import Message
+from exchange.exchange import EMPTY_RESPONSE_NUDGE, CompletionTimeoutError, Exchange
+from exchange.message import EmptyResponseError, Message
 from exchange.moderators import PassiveModerator
-from exchange.providers import Provider, Usage
-from exchange.tool import Tool
+from exchange.providers import Provider, ProviderCapabilities, Usage
+from exchange.providers.base import usage_of_empty_response
+from exchange.reply_handler import ReplyHandler
+from exchange.tool import Tool, ToolChoice
+from exchange.tool_error import ToolError, ToolErrorCategory
 
 
 def dummy_tool() -> str:
@@ -37,6 +47,7 @@ class MockProvider(Provider):
         self.sequence = sequence
         self.call_count = 0
         self.usage_dicts = usage_dicts
+        self.completion_kwargs = []
 
     @staticmethod
     def get_usage(data: dict) -> Usage:
@@ -62,6 +73,7 @@ class MockProvider(Provider):
         tools: tuple[Tool, ...],
         **kwargs: dict[str, any],
     ) -> tuple[Message, Usage]:
+        self.completion_kwargs.append(kwargs)
         output = self.sequence[self.call_count]
         usage = self.get_usage(self.usage_dicts[self.call_count])
         self.call_count += 1
@@ -98,6 +110,119 @@ def test_reply_with_unsupported_tool():
 
     content = ex.messages[-2].content[0]
     assert isinstance(content, ToolResult) and content.is_error and "no tool exists" in content.output.lower()
+    assert content.error_category == ToolErrorCategory.NOT_FOUND
+
+
+@pytest.mark.parametrize(
+    "error,category",
+    [
+        (FileNotFoundError("missing.txt"), ToolErrorCategory.NOT_FOUND),
+        (PermissionError("/etc/shadow"), ToolErrorCategory.PERMISSION_DENIED),
+        (TimeoutError("took too long"), ToolErrorCategory.TIMEOUT),
+        (ConnectionError("connection reset"), ToolErrorCategory.TRANSIENT),
+        (ToolError("bad scope", ToolErrorCategory.INVALID_ARGS), ToolErrorCategory.INVALID_ARGS),
+    ],
+)
+def test_call_function_classifies_errors(error, category):
+    def failing_tool() -> str:
+        """A tool that fails"""
+        raise error
+
+    ex = Exchange(
+        provider=MockProvider(sequence=[], usage_dicts=[]),
+        model="gpt-4o-2024-05-13",
+        system="You are a helpful assistant.",
+        tools=(Tool.from_function(failing_tool),),
+    )
+
+    result = ex.call_function(ToolUse(id="1", name="failing_tool", parameters={}))
+
+    assert result.is_error
+    assert result.error_category == category
+    retryable = category in (ToolErrorCategory.TIMEOUT, ToolErrorCategory.TRANSIENT)
+    assert result.retryable == retryable
+    assert f"error category: {category.value}{', retryable' if retryable else ''}. {category.hint}" in result.output
+
+
+def timing_out_exchange(read_only: Union[bool, Callable[[dict], bool]]) -> tuple[Exchange, list[dict]]:
+    calls = []
+
+    def search(pattern: str, timeout: float) -> str:
+        """Search the project, timing out on the first call
+
+        Args:
+            pattern (str): The pattern to search for
+            timeout (float): How many seconds the search may take
+        """
+        calls.append({"pattern": pattern, "timeout": timeout})
+        if len(calls) == 1:
+            raise TimeoutError(f"search took longer than {timeout} seconds")
+        return "found it"
+
+    ex = Exchange(
+        provider=MockProvider(sequence=[], usage_dicts=[]),
+        model="gpt-4o-2024-05-13",
+        system="You are a helpful assistant.",
+        tools=(Tool.from_function(search, read_only=read_only),),
+        retry_timed_out_tools=True,
+    )
+    return ex, calls
+
+
+def test_call_functions_retries_timed_out_read_only_tool_with_longer_timeout():
+    ex, calls = timing_out_exchange(read_only=True)
+
+    results = ex.call_functions([ToolUse(id="1", name="search", parameters={"pattern": "TODO", "timeout": 10})])
+
+    assert results[0].tool_use_id == "1"
+    assert not results[0].is_error
+    assert results[0].output == '"found it"'
+    assert calls == [{"pattern": "TODO", "timeout": 10}, {"pattern": "TODO", "timeout": 20}]
+
+
+def test_call_functions_does_not_retry_tools_that_change_state():
+    ex, calls = timing_out_exchange(read_only=False)
+
+    results = ex.call_functions([ToolUse(id="1", name="search", parameters={"pattern": "TODO", "timeout": 10})])
+
+    assert results[0].is_error and results[0].retryable
+    assert "error category: timeout, retryable" in results[0].output
+    assert len(calls) == 1
+
+
+def test_call_functions_retries_only_the_calls_a_tool_marks_read_only():
+    def reads_only(parameters: dict) -> bool:
+        return parameters["pattern"] != "DELETE"
+
+    ex, calls = timing_out_exchange(read_only=reads_only)
+    results = ex.call_functions([ToolUse(id="1", name="search", parameters={"pattern": "DELETE", "timeout": 10})])
+
+    assert results[0].is_error
+    assert len(calls) == 1
+
+    ex, calls = timing_out_exchange(read_only=reads_only)
+    results = ex.call_functions([ToolUse(id="1", name="search", parameters={"pattern": "TODO", "timeout": 10})])
+
+    assert not results[0].is_error
+    assert len(calls) == 2
+
+
+def test_call_function_leaves_unknown_errors_unclassified():
+    def failing_tool() -> str:
+        """A tool that fails"""
+        raise ValueError("something went wrong")
+
+    ex = Exchange(
+        provider=MockProvider(sequence=[], usage_dicts=[]),
+        model="gpt-4o-2024-05-13",
+        system="You are a helpful assistant.",
+        tools=(Tool.from_function(failing_tool),),
+    )
+
+    result = ex.call_function(ToolUse(id="1", name="failing_tool", parameters={}))
+
+    assert result.is_error and result.error_category is None
+    assert "error category" not in result.output
 
 
 def test_invalid_tool_parameters():
@@ -133,6 +258,220 @@ def test_invalid_tool_parameters():
     assert isinstance(content, ToolResult) and content.is_error and "invalid json" in content.output.lower()
 
 
+def remember(text: str, category: str, is_global: bool) -> str:
+    """An example memory tool with a boolean parameter
+
+    Args:
+        text (str): The memory text to store
+        category (str): The category to store the memory under
+        is_global (bool): Whether to store the memory for every project
+    """
+    assert isinstance(is_global, bool)
+    return f"remembered {text} in {category}, global {is_global}"
+
+
+def test_tool_parameters_are_coerced_to_schema_types():
+    """Test that string encoded tool arguments are converted to the declared types"""
+    parameters = {"text": "use ruff", "category": "development", "is_global": "true"}
+    ex = Exchange(
+        provider=MockProvider(
+            sequence=[
+                Message(
+                    role="assistant",
+                    content=[ToolUse(id="1", name="remember", parameters=parameters)],
+                ),
+                Message(
+                    role="assistant",
+                    content=[Text(text="done")],
+                ),
+            ],
+            usage_dicts=[
+                {"usage": {"input_tokens": 12, "output_tokens": 23}},
+                {"usage": {"input_tokens": 12, "output_tokens": 23}},
+            ],
+        ),
+        model="gpt-4o-2024-05-13",
+        system="You are a helpful assistant.",
+        tools=(Tool.from_function(remember),),
+        moderator=PassiveModerator(),
+    )
+
+    ex.add(Message(role="user", content=[Text(text="test coerced parameters")]))
+
+    ex.reply()
+
+    content = ex.messages[-2].content[0]
+    assert isinstance(content, ToolResult) and not content.is_error
+    assert content.output == '"remembered use ruff in development, global True"'
+
+
+def test_reply_stops_when_cancelled():
+    """Test that setting the cancel event mid reply stops before the next generation"""
+    cancel = Event()
+
+    def cancelling_tool() -> str:
+        """A tool that cancels the reply"""
+        cancel.set()
+        return "cancelled"
+
+    provider = MockProvider(
+        sequence=[
+            Message(
+                role="assistant",
+                content=[
+                    ToolUse(id="1", name="cancelling_tool", parameters={}),
+                    ToolUse(id="2", name="dummy_tool", parameters={}),
+                ],
+            ),
+            Message(
+                role="assistant",
+                content=[Text(text="This should never be generated")],
+            ),
+        ],
+        usage_dicts=[
+            {"usage": {"input_tokens": 12, "output_tokens": 23}},
+            {"usage": {"input_tokens": 12, "output_tokens": 23}},
+        ],
+    )
+    ex = Exchange(
+        provider=provider,
+        model="gpt-4o-2024-05-13",
+        system="You are a helpful assistant.",
+        tools=(Tool.from_function(cancelling_tool), Tool.from_function(dummy_tool)),
+        moderator=PassiveModerator(),
+    )
+
+    ex.add(Message(role="user", content=[Text(text="test cancel")]))
+
+    response = ex.reply(cancel=cancel)
+
+    assert provider.call_count == 1
+    assert "cancelled" in response.text
+    assert ex.messages[-1] == response
+    first, second = ex.messages[-2].content
+    assert not first.is_error and first.output == '"cancelled"'
+    assert second.is_error and "cancelled" in second.output
+
+
+@pytest.mark.parametrize("deduplicate,expected_calls", [(True, 1), (False, 2)])
+def test_call_functions_deduplicates_identical_tool_uses(deduplicate, expected_calls):
+    calls = []
+
+    def counting_tool(path: str) -> str:
+        """A tool that records its calls
+
+        Args:
+            path (str): The path to act on
+        """
+        calls.append(path)
+        return f"removed {path}"
+
+    ex = Exchange(
+        provider=MockProvider(sequence=[], usage_dicts=[]),
+        model="gpt-4o-2024-05-13",
+        system="You are a helpful assistant.",
+        tools=(Tool.from_function(counting_tool),),
+        deduplicate_tool_calls=deduplicate,
+    )
+
+    results = ex.call_functions(
+        [
+            ToolUse(id="1", name="counting_tool", parameters={"path": "a.txt"}),
+            ToolUse(id="2", name="counting_tool", parameters={"path": "a.txt"}),
+            ToolUse(id="3", name="counting_tool", parameters={"path": "b.txt"}),
+        ]
+    )
+
+    assert calls.count("a.txt") == expected_calls
+    assert calls.count("b.txt") == 1
+    assert [result.tool_use_id for result in results] == ["1", "2", "3"]
+    assert results[0].output == results[1].output == '"removed a.txt"'
+
+
+def test_call_functions_notifies_tool_call_listeners():
+    def slow_tool() -> str:
+        """A tool that takes a moment"""
+        time.sleep(0.01)
+        return "done"
+
+    calls = []
+    ex = Exchange(
+        provider=MockProvider(sequence=[], usage_dicts=[]),
+        model="gpt-4o-2024-05-13",
+        system="You are a helpful assistant.",
+        tools=[Tool.from_function(slow_tool)],
+        tool_call_listeners=[lambda tool_use, result, duration: calls.append((tool_use, result, duration))],
+    )
+
+    results = ex.call_functions(
+        [ToolUse(id="1", name="slow_tool", parameters={}), ToolUse(id="2", name="missing_tool", parameters={})]
+    )
+
+    assert [(tool_use.id, result) for tool_use, result, _ in calls] == [("1", results[0]), ("2", results[1])]
+    assert calls[0][2] >= 0.01
+    assert not results[0].is_error and results[1].is_error
+
+
+def stuck_tool() -> str:
+    """A tool the user interrupts with ctrl-c"""
+    raise KeyboardInterrupt
+
+
+def quick_tool() -> str:
+    """A tool that finishes"""
+    return "done"
+
+
+def interruptible_exchange() -> Exchange:
+    return Exchange(
+        provider=MockProvider(sequence=[], usage_dicts=[]),
+        model="gpt-4o-2024-05-13",
+        system="You are a helpful assistant.",
+        tools=[Tool.from_function(stuck_tool), Tool.from_function(quick_tool)],
+    )
+
+
+def test_call_functions_cancels_only_the_interrupted_tool():
+    ex = interruptible_exchange()
+
+    results = ex.call_functions(
+        [ToolUse(id="1", name="stuck_tool", parameters={}), ToolUse(id="2", name="quick_tool", parameters={})],
+        cancel_tool_on_interrupt=True,
+    )
+
+    assert results[0].is_error
+    assert results[0].error_category == ToolErrorCategory.CANCELLED
+    assert "cancelled this call to stuck_tool" in results[0].output
+    assert results[1] == ToolResult(tool_use_id="2", output='"done"')
+
+
+def test_call_functions_second_interrupt_stops_the_reply():
+    ex = interruptible_exchange()
+    tool_uses = [ToolUse(id="1", name="stuck_tool", parameters={}), ToolUse(id="2", name="stuck_tool", parameters={})]
+
+    with pytest.raises(KeyboardInterrupt):
+        ex.call_functions(tool_uses, cancel_tool_on_interrupt=True)
+
+    with pytest.raises(KeyboardInterrupt):
+        ex.call_functions(tool_uses[:1])
+
+
+def test_call_functions_interrupt_after_cancel_stops_the_reply():
+    cancel = Event()
+
+    def terminate() -> str:
+        """Raise the interrupt the way a SIGTERM handler does"""
+        cancel.set()
+        raise KeyboardInterrupt
+
+    ex = interruptible_exchange().replace(tools=(Tool.from_function(terminate),))
+
+    with pytest.raises(KeyboardInterrupt):
+        ex.call_functions(
+            [ToolUse(id="1", name="terminate", parameters={})], cancel=cancel, cancel_tool_on_interrupt=True
+        )
+
+
 def test_max_tool_use_when_limit_reached():
     """Test the max_tool_use parameter in the reply method."""
     ex = Exchange(
@@ -175,6 +514,329 @@ def test_max_tool_use_when_limit_reached():
     assert ex.messages[-1].role == "assistant"
 
 
+def test_reply_tool_choice_applies_to_first_generation():
+    ex = Exchange(
+        provider=MockProvider(
+            sequence=[
+                Message(role="assistant", content=[ToolUse(id="1", name="dummy_tool", parameters={})]),
+                Message.assistant("done"),
+            ],
+            usage_dicts=[
+                {"usage": {"input_tokens": 12, "output_tokens": 23}},
+                {"usage": {"input_tokens": 12, "output_tokens": 23}},
+            ],
+        ),
+        model="gpt-4o-2024-05-13",
+        system="You are a helpful assistant.",
+        tools=(Tool.from_function(dummy_tool),),
+        moderator=PassiveModerator(),
+    )
+    ex.add(Message.user("use the dummy tool"))
+
+    ex.reply(tool_choice=ToolChoice.named("dummy_tool"))
+
+    assert ex.provider.completion_kwargs == [{"tool_choice": ToolChoice(mode="required", name="dummy_tool")}, {}]
+
+
+class RecordingHandler(ReplyHandler):
+    def __init__(self):
+        self.events = []
+
+    def on_text(self, text):
+        self.events.append(("text", text))
+
+    def on_tool_request(self, tool_use):
+        self.events.append(("tool_request", tool_use.name))
+
+    def on_tool_response(self, tool_result):
+        self.events.append(("tool_response", tool_result.output))
+
+    def on_finish(self, message):
+        self.events.append(("finish", message.text))
+
+
+def test_reply_calls_handler_in_order():
+    ex = Exchange(
+        provider=MockProvider(
+            sequence=[
+                Message(
+                    role="assistant",
+                    content=[Text("Let me check."), ToolUse(id="1", name="dummy_tool", parameters={})],
+                ),
+                Message.assistant("done"),
+            ],
+            usage_dicts=[
+                {"usage": {"input_tokens": 12, "output_tokens": 23}},
+                {"usage": {"input_tokens": 12, "output_tokens": 23}},
+            ],
+        ),
+        model="gpt-4o-2024-05-13",
+        system="You are a helpful assistant.",
+        tools=(Tool.from_function(dummy_tool),),
+        moderator=PassiveModerator(),
+    )
+    ex.add(Message.user("use the dummy tool"))
+    handler = RecordingHandler()
+
+    ex.reply(handler=handler)
+
+    assert handler.events == [
+        ("text", "Let me check."),
+        ("tool_request", "dummy_tool"),
+        ("tool_response", '"dummy response"'),
+        ("text", "done"),
+        ("finish", "done"),
+    ]
+
+def test_reply_rejects_tool_choice_for_unknown_tool():
+    ex = Exchange(
+        provider=MockProvider(sequence=[], usage_dicts=[]),
+        model="gpt-4o-2024-05-13",
+        system="You are a helpful assistant.",
+        tools=(Tool.from_function(dummy_tool),),
+        moderator=PassiveModerator(),
+    )
+    ex.add(Message.user("use the search tool"))
+
+    with pytest.raises(ValueError, match="search"):
+        ex.reply(tool_choice=ToolChoice.named("search"))
+    assert ex.provider.call_count == 0
+
+
+def test_reply_accumulates_usage_across_rounds():
+    """Test that the usage of every round in a reply is summed on the exchange."""
+    ex = Exchange(
+        provider=MockProvider(
+            sequence=[
+                Message(
+                    role="assistant",
+                    content=[ToolUse(id="1", name="dummy_tool", parameters={})],
+                ),
+                Message(
+                    role="assistant",
+                    content=[Text(text="Here is the completion after tool call")],
+                ),
+            ],
+            usage_dicts=[
+                {"usage": {"input_tokens": 12, "output_tokens": 23}},
+                {"usage": {"input_tokens": 40, "output_tokens": 7}},
+            ],
+        ),
+        model="gpt-4o-2024-05-13",
+        system="You are a helpful assistant.",
+        tools=(Tool.from_function(dummy_tool),),
+        moderator=PassiveModerator(),
+    )
+
+    ex.add(Message(role="user", content=[Text(text="test usage")]))
+    ex.reply()
+
+    assert ex.usage == Usage(input_tokens=52, output_tokens=30, total_tokens=82)
+
+
+def test_reply_keeps_usage_when_a_round_errors():
+    """Test that usage from completed rounds is kept when a later round fails."""
+    ex = Exchange(
+        provider=MockProvider(
+            sequence=[
+                Message(
+                    role="assistant",
+                    content=[ToolUse(id="1", name="dummy_tool", parameters={})],
+                ),
+                # the second round returns a user message, which fails to be added
+                Message(role="user", content=[Text(text="not an assistant reply")]),
+            ],
+            usage_dicts=[
+                {"usage": {"input_tokens": 12, "output_tokens": 23}},
+                {"usage": {"input_tokens": 40, "output_tokens": 7}},
+            ],
+        ),
+        model="gpt-4o-2024-05-13",
+        system="You are a helpful assistant.",
+        tools=(Tool.from_function(dummy_tool),),
+        moderator=PassiveModerator(),
+    )
+
+    ex.add(Message(role="user", content=[Text(text="test usage")]))
+    with pytest.raises(ValueError):
+        ex.reply()
+
+    assert ex.usage == Usage(input_tokens=52, output_tokens=30, total_tokens=82)
+
+
+class EmptyThenTextProvider(Provider):
+    """Returns empty responses, the way a provider's parsing fails on them, before answering with text"""
+
+    def __init__(self, empty_responses: int) -> None:
+        self.empty_responses = empty_responses
+        self.systems = []
+
+    def complete(
+        self,
+        model: str,
+        system: str,
+        messages: list[Message],
+        tools: tuple[Tool, ...],
+        **kwargs: dict[str, any],
+    ) -> tuple[Message, Usage]:
+        self.systems.append(system)
+        if len(self.systems) <= self.empty_responses:
+            usage = Usage(10, 0, 10)
+            with usage_of_empty_response(usage):
+                return Message(role="assistant", content=[]), usage
+        return Message.assistant("Here is the answer"), Usage(10, 5, 15)
+
+
+def test_reply_retries_once_after_empty_response():
+    provider = EmptyThenTextProvider(empty_responses=1)
+    ex = Exchange(provider=provider, model="gpt-4o-2024-05-13", system="You are a helpful assistant.")
+    ex.add(Message.user("test empty"))
+
+    response = ex.reply()
+
+    assert response.text == "Here is the answer"
+    assert [message.text for message in ex.messages] == ["test empty", "Here is the answer"]
+    assert provider.systems[0] == "You are a helpful assistant."
+    assert provider.systems[1].endswith(EMPTY_RESPONSE_NUDGE)
+
+
+def test_reply_counts_the_usage_of_the_empty_response():
+    provider = EmptyThenTextProvider(empty_responses=1)
+    ex = Exchange(provider=provider, model="gpt-4o-2024-05-13", system="You are a helpful assistant.")
+    ex.add(Message.user("test empty"))
+
+    ex.reply()
+
+    assert ex.usage == Usage(input_tokens=20, output_tokens=5, total_tokens=25)
+
+
+def test_reply_raises_after_second_empty_response():
+    provider = EmptyThenTextProvider(empty_responses=2)
+    ex = Exchange(provider=provider, model="gpt-4o-2024-05-13", system="You are a helpful assistant.")
+    ex.add(Message.user("test empty"))
+
+    with pytest.raises(EmptyResponseError):
+        ex.reply()
+    assert len(provider.systems) == 2
+
+
+class SlowProvider(Provider):
+    def __init__(self, delay: float) -> None:
+        self.delay = delay
+
+    def complete(
+        self,
+        model: str,
+        system: str,
+        messages: list[Message],
+        tools: tuple[Tool, ...],
+        **kwargs: dict[str, any],
+    ) -> tuple[Message, Usage]:
+        time.sleep(self.delay)
+        return Message.assistant("Here is the answer"), Usage(10, 5, 15)
+
+
+def test_generate_raises_after_completion_timeout():
+    ex = Exchange(provider=SlowProvider(delay=1), model="gpt-4o", system="system", completion_timeout=0.05)
+    ex.add(Message.user("test slow"))
+
+    start = time.monotonic()
+    with pytest.raises(CompletionTimeoutError) as error:
+        ex.generate()
+
+    assert time.monotonic() - start < 0.5
+    assert str(error.value) == "The provider did not respond within 0.05 seconds"
+    assert [message.text for message in ex.messages] == ["test slow"]
+
+
+def test_generate_takes_a_completion_timeout_for_one_call():
+    ex = Exchange(provider=SlowProvider(delay=1), model="gpt-4o", system="system")
+    ex.add(Message.user("test slow"))
+
+    with pytest.raises(CompletionTimeoutError) as error:
+        ex.generate(completion_timeout=0.05)
+
+    assert error.value.timeout == 0.05
+
+
+class LateFuture(Future):
+    """A future that finished, but only once waiting for it with a timeout had given up"""
+
+    def result(self, timeout: Optional[float] = None) -> tuple[Message, Usage]:
+        if timeout is not None:
+            raise FuturesTimeoutError()
+        return super().result()
+
+
+class LateExecutor:
+    def __init__(self, max_workers: int) -> None:
+        pass
+
+    def submit(self, fn: Callable, *args: object) -> Future:
+        future = LateFuture()
+        future.set_result(fn(*args))
+        return future
+
+    def shutdown(self, wait: bool) -> None:
+        pass
+
+
+def test_generate_keeps_a_completion_that_finishes_as_the_timeout_gives_up(monkeypatch):
+    monkeypatch.setattr(exchange_module, "ThreadPoolExecutor", LateExecutor)
+    ex = Exchange(provider=SlowProvider(delay=0), model="gpt-4o", system="system", completion_timeout=5)
+    ex.add(Message.user("test late"))
+
+    assert ex.generate().text == "Here is the answer"
+
+
+def test_generate_within_completion_timeout():
+    ex = Exchange(provider=SlowProvider(delay=0), model="gpt-4o", system="system", completion_timeout=5)
+    ex.add(Message.user("test fast"))
+
+    assert ex.generate().text == "Here is the answer"
+
+
+class SamplingProvider(Provider):
+    def __init__(self, candidates: list[str]) -> None:
+        self.candidates = candidates
+        self.calls = []
+
+    def complete(self, model, system, messages, tools, **kwargs):
+        raise AssertionError("complete should not be called when sampling")
+
+    def complete_n(self, model, system, messages, tools, n, **kwargs):
+        self.calls.append(n)
+        return [Message.assistant(text) for text in self.candidates[:n]], Usage(10, 15, 25)
+
+
+def test_generate_keeps_the_first_sample_by_default():
+    provider = SamplingProvider(["first", "second", "third"])
+    ex = Exchange(provider=provider, model="gpt-4o", system="system", samples=3)
+    ex.add(Message.user("hi"))
+
+    assert ex.generate().text == "first"
+    assert provider.calls == [3]
+    assert ex.usage.total_tokens == 25
+
+
+def test_generate_keeps_the_sample_scored_highest():
+    provider = SamplingProvider(["short", "the longest answer", "medium one"])
+    ex = Exchange(provider=provider, model="gpt-4o", system="system", samples=3, sample_scorer=lambda m: len(m.text))
+    ex.add(Message.user("hi"))
+
+    assert ex.generate().text == "the longest answer"
+    assert [message.text for message in ex.messages] == ["hi", "the longest answer"]
+
+
+def test_complete_n_defaults_to_parallel_completions():
+    provider = SlowProvider(delay=0)
+
+    messages, usage = provider.complete_n("gpt-4o", "system", [Message.user("hi")], (), n=3)
+
+    assert [message.text for message in messages] == ["Here is the answer"] * 3
+    assert usage.total_tokens == 45
+
+
 def test_tool_output_too_long_character_error():
     """Test tool handling when output exceeds character limit."""
 
@@ -766,3 +1428,38 @@ def test_rewind_with_tool_usage():
     assert ex.messages[0].content[0].text == "test"
     assert type(ex.messages[1].content[0]) is Text
     assert ex.messages[1].role == "assistant"
+
+
+class TextOnlyProvider(Provider):
+    CAPABILITIES = ProviderCapabilities(tools=False, images=False, tool_choice=False)
+
+    def __init__(self) -> None:
+        self.requests = []
+
+    def complete(self, model, system, messages, tools, **kwargs):
+        self.requests.append((messages, tools, kwargs))
+        return Message.assistant("a cat"), Usage(10, 5, 15)
+
+
+def test_generate_sends_only_what_the_provider_supports():
+    provider = TextOnlyProvider()
+    ex = Exchange(
+        provider=provider,
+        model="text-only",
+        system="You are a helpful assistant.",
+        tools=(Tool.from_function(dummy_tool),),
+        moderator=PassiveModerator(),
+    )
+    ex.add(Message.user("take a screenshot"))
+    ex.add(Message(role="assistant", content=[ToolUse(id="1", name="dummy_tool", parameters={})]))
+    ex.add(Message(role="user", content=[ToolResult(tool_use_id="1", output='"image:/tmp/screenshot.png"')]))
+
+    ex.generate(tool_choice=ToolChoice.named("dummy_tool"))
+
+    messages, tools, kwargs = provider.requests[0]
+    assert tools == ()
+    assert "tool_choice" not in kwargs
+    assert messages[-1].tool_result[0].tool_use_id == "1"
+    assert "image:" not in messages[-1].tool_result[0].output
+    # the history keeps the image, only the request leaves it out
+    assert ex.messages[2].tool_result[0].output == '"image:/tmp/screenshot.png"'
@@ -1,6 +1,8 @@
 from unittest.mock import MagicMock
+
+import pytest
 from exchange.exchange import Exchange
-from exchange.message import Message
+from exchange.message import EmptyResponseError, Message
 from exchange.moderators.passive import PassiveModerator
 from exchange.providers.base import Provider
 from exchange.tool import Tool
@@ -31,3 +33,21 @@ def test_exchange_generate_collect_usage(usage_factory, dummy_tool, monkeypatch)
     exchange.generate()
 
     mock_usage_collector.collect.assert_called_once_with(MODEL_NAME, usage)
+
+
+def test_exchange_generate_collects_the_usage_of_an_empty_response_when_the_retry_fails(
+    usage_factory, dummy_tool, monkeypatch
+):
+    mock_provider = MagicMock(spec=Provider)
+    mock_usage_collector = MagicMock(spec=_TokenUsageCollector)
+    usage = usage_factory()
+    empty = EmptyResponseError("Assistant message must include a Text or ToolUsage")
+    empty.usage = usage
+    mock_provider.complete.side_effect = [empty, RuntimeError("provider down")]
+    exchange = create_exchange(mock_provider, dummy_tool)
+
+    monkeypatch.setattr("exchange.exchange._token_usage_collector", mock_usage_collector)
+    with pytest.raises(RuntimeError):
+        exchange.generate()
+
+    mock_usage_collector.collect.assert_called_once_with(MODEL_NAME, usage)
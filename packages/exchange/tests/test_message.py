@@ -2,7 +2,8 @@ import subprocess
 from pathlib import Path
 import pytest
 
-from exchange.message import Message
+import exchange.message as message_module
+from exchange.message import Message, add_template_search_path, register_template
 from exchange.content import Text, ToolUse, ToolResult
 
 
@@ -72,6 +73,30 @@ test_function()
     assert result.returncode == 0
 
 
+@pytest.fixture
+def template_registry(monkeypatch):
+    monkeypatch.setattr(message_module, "REGISTERED_TEMPLATES", {})
+    monkeypatch.setattr(message_module, "TEMPLATE_SEARCH_PATHS", [])
+
+
+def test_message_load_registered_template(template_registry):
+    register_template("greeting.jinja", "hello {{ name }}")
+
+    assert Message.load("greeting.jinja", name="a").text == "hello a"
+
+
+def test_message_load_from_search_path(template_registry, tmp_path):
+    (tmp_path / "greeting.jinja").write_text("hi {{ name }} from the search path")
+    add_template_search_path(tmp_path)
+    add_template_search_path(tmp_path)
+
+    assert Message.load("greeting.jinja", name="a").text == "hi a from the search path"
+    assert message_module.TEMPLATE_SEARCH_PATHS == [tmp_path]
+
+    register_template("greeting.jinja", "registered {{ name }}")
+    assert Message.load("greeting.jinja", name="a").text == "registered a"
+
+
 def test_message_validation():
     # Valid user message
     message = Message(role="user", content=[Text(text="Hello")])
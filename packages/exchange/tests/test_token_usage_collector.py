@@ -1,3 +1,4 @@
+from exchange.providers.base import Usage
 from exchange.token_usage_collector import _TokenUsageCollector
 
 
@@ -22,3 +23,14 @@ def test_collect_with_non_input_or_output_token(usage_factory):
     assert usage_collector.get_token_usage_group_by_model() == {
         "model1": usage_factory(100, 2000, 0),
     }
+
+
+def test_collect_payload_sizes():
+    usage_collector = _TokenUsageCollector()
+    usage_collector.collect("model1", Usage(100, 10, 110, request_bytes=2000, response_bytes=300))
+    usage_collector.collect("model1", Usage(200, 20, 220, request_bytes=4000, response_bytes=500))
+    usage_collector.collect("model2", Usage(100, 10, 110))
+
+    usage = usage_collector.get_token_usage_group_by_model()
+    assert (usage["model1"].request_bytes, usage["model1"].response_bytes) == (6000, 800)
+    assert usage["model2"].request_bytes is None
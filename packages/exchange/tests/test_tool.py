@@ -28,6 +28,7 @@ def test_load():
             "required": ["location"],
         },
         "function": get_current_weather,
+        "read_only": False,
     }
 
     assert attrs.asdict(tool) == expected
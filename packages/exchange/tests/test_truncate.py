@@ -130,3 +130,39 @@ def test_truncate_on_generic_conversation(conversation_exchange_instance: Exchan
         # ensure the total token count is not anything exhorbitant
         assert conversation_exchange_instance.checkpoint_data.total_token_count < 700
         assert conversation_exchange_instance.moderator.system_prompt_token_count == 100
+
+
+def test_truncate_reports_dropped_messages(conversation_exchange_instance: Exchange):
+    dropped = []
+    conversation_exchange_instance.context_trim_listeners.append(dropped.append)
+
+    for i in range(0, len(MESSAGE_SEQUENCE), 2):
+        conversation_exchange_instance.add(MESSAGE_SEQUENCE[i])
+        conversation_exchange_instance.generate()
+
+    assert dropped
+    assert all(count > 0 for count in dropped)
+
+
+def test_truncate_counts_system_prompt_tokens_without_sampling():
+    provider = TruncateLinearProvider()
+    sampled = []
+
+    def complete_n(model, system, messages, tools, n, **kwargs):
+        sampled.append(n)
+        message, usage = provider.complete(model, system, messages, tools, **kwargs)
+        return [message] * n, usage
+
+    provider.complete_n = complete_n
+    ex = Exchange(
+        provider=provider,
+        model="test-model",
+        system="test-system",
+        moderator=ContextTruncate(max_tokens=500),
+        samples=3,
+    )
+    ex.add(MESSAGE_SEQUENCE[0])
+    ex.generate()
+
+    assert sampled == [3]
+    assert ex.moderator.system_prompt_token_count == 100
@@ -136,6 +136,42 @@ def test_json_schema_with_optional_params() -> None:
     }
 
 
+def test_coerce_parameters() -> None:
+    schema = {
+        "type": "object",
+        "properties": {
+            "line": {"type": "integer"},
+            "ratio": {"type": "number"},
+            "flag": {"type": "boolean"},
+            "name": {"type": "string"},
+            "either": {"anyOf": [{"type": "integer"}, {"type": "boolean"}]},
+        },
+    }
+    parameters = {"line": "5", "ratio": "0.5", "flag": "True", "name": "7", "either": "false", "extra": "1"}
+
+    coerced = utils.coerce_parameters(parameters, schema)
+
+    assert coerced == {"line": 5, "ratio": 0.5, "flag": True, "name": "7", "either": False, "extra": "1"}
+    assert parameters["line"] == "5"
+
+
+def test_coerce_parameters_leaves_invalid_values() -> None:
+    schema = {"type": "object", "properties": {"line": {"type": "integer"}, "flag": {"type": "boolean"}}}
+
+    coerced = utils.coerce_parameters({"line": "five", "flag": "yes"}, schema)
+
+    assert coerced == {"line": "five", "flag": "yes"}
+
+
+def test_coerce_parameters_does_not_change_the_schema() -> None:
+    schema = {"type": "object", "properties": {"line": {"type": ["integer", "null"], "anyOf": [{"type": "boolean"}]}}}
+
+    utils.coerce_parameters({"line": "5"}, schema)
+    utils.coerce_parameters({"line": "5"}, schema)
+
+    assert schema["properties"]["line"]["type"] == ["integer", "null"]
+
+
 def test_load_plugins() -> None:
     class DummyEntryPoint:
         def __init__(self, name, plugin):
@@ -5,14 +5,19 @@ _LOGGER_NAME = "goose"
 _LOGGER_FILE_NAME = "goose.log"
 
 
+# exchange logs the requests sent to providers and their raw responses at DEBUG
+_EXCHANGE_LOGGER_NAME = "exchange"
+
+
 def setup_logging(log_file_directory: Path, log_level: str = "INFO") -> None:
-    logger = logging.getLogger(_LOGGER_NAME)
-    logger.setLevel(getattr(logging, log_level))
     log_file_directory.mkdir(parents=True, exist_ok=True)
     file_handler = logging.FileHandler(log_file_directory / _LOGGER_FILE_NAME)
-    logger.addHandler(file_handler)
     formatter = logging.Formatter("%(asctime)s - %(name)s - %(levelname)s - %(message)s")
     file_handler.setFormatter(formatter)
+    for name in (_LOGGER_NAME, _EXCHANGE_LOGGER_NAME):
+        logger = logging.getLogger(name)
+        logger.setLevel(getattr(logging, log_level))
+        logger.addHandler(file_handler)
 
 
 def get_logger() -> logging.Logger:
@@ -1,17 +1,29 @@
+import os
 from itertools import chain
+from pathlib import Path
+from typing import Optional, Sequence
 
 from exchange import Exchange, Message
 from exchange.moderators import get_moderator
-from exchange.providers import get_provider
+from exchange.providers import Provider, get_provider
 
 from goose.notifier import Notifier
 from goose.profile import Profile
 from goose.toolkit import get_toolkit
-from goose.toolkit.base import Requirements
+from goose.toolkit.base import Requirements, Toolkit
+from goose.toolkit.utils import render_template
 from goose.view import ExchangeView
 
+# how many seconds to wait for each completion before giving up on it, by default as long as the provider takes
+COMPLETION_TIMEOUT_ENV = "GOOSE_COMPLETION_TIMEOUT"
 
-def build_exchange(profile: Profile, notifier: Notifier) -> Exchange:
+
+def build_exchange(
+    profile: Profile,
+    notifier: Notifier,
+    system_prompt_file: Optional[Path] = None,
+    append_system: Sequence[str] = (),
+) -> Exchange:
     """Build an exchange configured through the profile
 
     This will setup any toolkits and use that to build the exchange's collection
@@ -20,10 +32,86 @@ def build_exchange(profile: Profile, notifier: Notifier) -> Exchange:
     Args:
         profile (Profile): The profile specifying how to setup this exchange
         notifier (Notifier): A notifier instance used by tools to send info
+        system_prompt_file (Path, optional): A template to use in place of the bundled system prompt
+        append_system (Sequence[str]): Extra instructions to add to the system prompt, in order
     """
 
     provider = get_provider(profile.provider).from_env()
+    toolkits = build_toolkits(profile, notifier=notifier)
+
+    # From the toolkits, we derive the exchange prompt and tools
+    base_system = build_system_prompt(profile, system_prompt_file, append_system)
+    system = "\n\n".join([base_system] + [toolkit.system() for toolkit in toolkits])
+    tools = tuple(chain(*(toolkit.tools() for toolkit in toolkits)))
+    completion_timeout = os.environ.get(COMPLETION_TIMEOUT_ENV)
+    exchange = Exchange(
+        provider=provider,
+        system=system,
+        tools=tools,
+        moderator=get_moderator(profile.moderator)(),
+        model=profile.processor,
+        generation_args=dict(profile.generation_args),
+        completion_timeout=float(completion_timeout) if completion_timeout else None,
+        retry_timed_out_tools=True,
+    )
+
+    # This is a bit awkward, but we have to set this after the fact because building
+    # the exchange requires having the toolkits
+    for toolkit in toolkits:
+        toolkit.exchange_view = ExchangeView(profile.processor, profile.accelerator, exchange)
+
+    return exchange
+
+
+def switch_provider(exchange: Exchange, profile: Profile, provider: Provider) -> Exchange:
+    """Copy the exchange to continue it with the provider and models of another profile, keeping its messages
+
+    The toolkits keep their tools, with their view pointed at the copy and the profile's models.
+    """
+    switched = exchange.replace(
+        provider=provider, model=profile.processor, generation_args=dict(profile.generation_args)
+    )
+
+    # the tools are bound methods of the toolkits, which is the only place the exchange keeps them
+    owners = [getattr(tool.function, "__self__", None) for tool in exchange.tools]
+    toolkits = {id(owner): owner for owner in owners if isinstance(owner, Toolkit)}
+    for toolkit in toolkits.values():
+        toolkit.exchange_view = ExchangeView(profile.processor, profile.accelerator, switched)
+    return switched
+
+
+def build_system_prompt(
+    profile: Profile, system_prompt_file: Optional[Path] = None, append_system: Sequence[str] = ()
+) -> str:
+    """Render the base system prompt, which the toolkits' prompts are appended to
 
+    The template is rendered with the names of the profile's toolkits available as `toolkits`
+    and the assistant's name as `name`.
+
+    Args:
+        profile (Profile): The profile specifying the assistant's name and which toolkits are in use
+        system_prompt_file (Path, optional): A template to render instead of the bundled system.jinja
+        append_system (Sequence[str]): Extra instructions from the user, listed after the template in order
+    """
+    context = {"toolkits": [spec.name for spec in profile.toolkits], "name": profile.name}
+    if system_prompt_file is None:
+        system = Message.load("system.jinja", **context).text
+    else:
+        system = render_template(Path(system_prompt_file), context=context)
+
+    if append_system:
+        instructions = "\n".join(f"- {instruction}" for instruction in append_system)
+        system += f"\n\n# Instructions from the user\n\nAlways follow these instructions:\n{instructions}"
+    return system
+
+
+def build_toolkits(profile: Profile, notifier: Notifier) -> list[Toolkit]:
+    """Instantiate the toolkits configured through the profile, in the order they are listed
+
+    Args:
+        profile (Profile): The profile specifying which toolkits to setup
+        notifier (Notifier): A notifier instance used by tools to send info
+    """
     # Support instantating toolkits in *two* passes for now, no further nesting
     concrete_toolkits = {}
 
@@ -46,20 +134,4 @@ def build_exchange(profile: Profile, notifier: Notifier) -> Exchange:
         toolkit = get_toolkit(spec.name)(notifier=notifier, requires=requires)
         toolkits.append(toolkit)
 
-    # From the toolkits, we derive the exchange prompt and tools
-    system = "\n\n".join([Message.load("system.jinja").text] + [toolkit.system() for toolkit in toolkits])
-    tools = tuple(chain(*(toolkit.tools() for toolkit in toolkits)))
-    exchange = Exchange(
-        provider=provider,
-        system=system,
-        tools=tools,
-        moderator=get_moderator(profile.moderator)(),
-        model=profile.processor,
-    )
-
-    # This is a bit awkward, but we have to set this after the fact because building
-    # the exchange requires having the toolkits
-    for toolkit in toolkits:
-        toolkit.exchange_view = ExchangeView(profile.processor, profile.accelerator, exchange)
-
-    return exchange
+    return toolkits
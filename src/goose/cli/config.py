@@ -1,7 +1,10 @@
+import os
 from functools import cache
 from pathlib import Path
 from typing import Mapping, Optional
 
+from dotenv import load_dotenv
+from exchange.message import add_template_search_path
 from rich import print
 from rich.panel import Panel
 from ruamel.yaml import YAML
@@ -9,13 +12,17 @@ from ruamel.yaml import YAML
 
 from goose.profile import Profile
 from goose.utils import load_plugins
+from goose.utils.credentials import credential_stores, load_credential, mask_secret
 
 GOOSE_GLOBAL_PATH = Path("~/.config/goose").expanduser()
 PROFILES_CONFIG_PATH = GOOSE_GLOBAL_PATH.joinpath("profiles.yaml")
 SESSIONS_PATH = GOOSE_GLOBAL_PATH.joinpath("sessions")
 SESSION_FILE_SUFFIX = ".jsonl"
 LOG_PATH = GOOSE_GLOBAL_PATH.joinpath("logs")
+PROMPTS_PATH = GOOSE_GLOBAL_PATH.joinpath("prompts")
+CREDENTIALS_PATH = GOOSE_GLOBAL_PATH.joinpath("credentials.yaml")
 RECOMMENDED_DEFAULT_PROVIDER = "openai"
+SESSION_LOCK_TIMEOUT_ENV = "GOOSE_SESSION_LOCK_TIMEOUT"
 
 
 @cache
@@ -23,11 +30,57 @@ def default_profiles() -> Mapping[str, callable]:
     return load_plugins(group="goose.profile")
 
 
+def use_user_prompts() -> None:
+    """Let templates in the prompts directory override the ones bundled with goose and its toolkits"""
+    add_template_search_path(PROMPTS_PATH)
+
+
 def session_path(name: str) -> Path:
-    SESSIONS_PATH.mkdir(parents=True, exist_ok=True)
+    try:
+        SESSIONS_PATH.mkdir(parents=True, exist_ok=True)
+    except OSError:
+        # the session checks whether it can write its file, and runs without saving if not
+        pass
     return SESSIONS_PATH.joinpath(f"{name}{SESSION_FILE_SUFFIX}")
 
 
+def session_lock_timeout() -> float:
+    """How many seconds to wait for another process to finish with a session, by default not at all
+
+    Raises a ValueError naming the variable if it isn't a number of seconds.
+    """
+    value = os.environ.get(SESSION_LOCK_TIMEOUT_ENV, "0")
+    try:
+        timeout = float(value)
+    except ValueError:
+        timeout = None
+    # written so that nan is refused too
+    if timeout is None or not timeout >= 0:
+        raise ValueError(f"{SESSION_LOCK_TIMEOUT_ENV} must be a number of seconds, 0 or more, got '{value}'.")
+    return timeout
+
+
+def find_dotenv(start: Path) -> Optional[Path]:
+    """The .env file in start or the closest parent that has one, looking no higher than the git repository root"""
+    for directory in [start, *start.parents]:
+        if (directory / ".env").is_file():
+            return directory / ".env"
+        if (directory / ".git").exists():
+            return None
+    return None
+
+
+def load_dotenv_file(start: Path) -> Optional[Path]:
+    """Set the variables in the project's .env file, such as provider API keys, that aren't already set
+
+    Returns the file that was loaded, if any.
+    """
+    path = find_dotenv(start)
+    if path is not None:
+        load_dotenv(path, override=False)
+    return path
+
+
 def write_config(profiles: dict[str, Profile]) -> None:
     """Overwrite the config with the passed profiles"""
     PROFILES_CONFIG_PATH.parent.mkdir(parents=True, exist_ok=True)
@@ -79,6 +132,25 @@ def read_config() -> dict[str, Profile]:
     return {name: Profile(**profile) for name, profile in data.items()}
 
 
+def profile_settings(profile: Profile) -> dict[str, any]:
+    """The profile's settings and the credentials its provider reads, masked, to show the user
+
+    Credentials are found the way a session would, in the environment and then the credential stores,
+    and are None when they aren't set anywhere.
+    """
+    settings = profile.to_dict()
+    provider_cls = load_plugins(group="exchange.provider").get(profile.provider)
+    stores = credential_stores(CREDENTIALS_PATH)
+    credentials = {}
+    for env_variable in getattr(provider_cls, "REQUIRED_ENV_VARS", []):
+        value = os.environ.get(env_variable)
+        if value is None:
+            value, _ = load_credential(stores, env_variable)
+        credentials[env_variable] = mask_secret(value) if value else None
+    settings["credentials"] = credentials
+    return settings
+
+
 def default_model_configuration() -> tuple[str, str, str]:
     providers = load_plugins(group="exchange.provider")
     for provider, cls in providers.items():
@@ -0,0 +1,125 @@
+import os
+from typing import Optional
+
+from attrs import define
+from exchange import Message
+from exchange.invalid_choice_error import InvalidChoiceError
+from exchange.moderators import get_moderator
+from exchange.providers import get_provider
+from exchange.providers.base import MissingProviderEnvVariableError, Provider
+
+from goose.cli.config import CREDENTIALS_PATH, PROFILES_CONFIG_PATH, read_config
+from goose.profile import Profile
+from goose.toolkit import get_toolkit
+from goose.utils.credentials import credential_stores, load_credential
+
+PING_SYSTEM = "You are a helpful assistant."
+PING_MESSAGE = "Reply with OK."
+
+
+@define
+class Check:
+    """The outcome of validating one part of the configuration"""
+
+    name: str
+    ok: bool
+    detail: str
+
+
+def check_profile(name: str) -> tuple[Check, Optional[Profile]]:
+    if not PROFILES_CONFIG_PATH.exists():
+        detail = f"No configuration at {PROFILES_CONFIG_PATH}, start a session to create one"
+        return Check("profile", False, detail), None
+    try:
+        profiles = read_config()
+    except Exception as e:
+        return Check("profile", False, f"Could not read {PROFILES_CONFIG_PATH}: {e}"), None
+    if name not in profiles:
+        available = ", ".join(profiles.keys())
+        return Check("profile", False, f"No profile named '{name}'. Available profiles: {available}"), None
+    return Check("profile", True, f"'{name}' in {PROFILES_CONFIG_PATH}"), profiles[name]
+
+
+def check_choice(name: str, choose: callable, value: str) -> Check:
+    try:
+        choose(value)
+    except InvalidChoiceError as e:
+        return Check(name, False, e.message)
+    return Check(name, True, value)
+
+
+def check_credentials(profile: Profile) -> Check:
+    """Check the provider's environment variables are set or stored, the way a session would find them"""
+    provider_cls = get_provider(profile.provider)
+    stores = credential_stores(CREDENTIALS_PATH)
+    missing, found = [], []
+    for env_variable in provider_cls.REQUIRED_ENV_VARS:
+        if env_variable in os.environ:
+            found.append(f"{env_variable} from the environment")
+            continue
+        _, store = load_credential(stores, env_variable)
+        if store is None:
+            missing.append(env_variable)
+        else:
+            found.append(f"{env_variable} from {store.description}")
+
+    if missing:
+        error = MissingProviderEnvVariableError(", ".join(missing), profile.provider)
+        return Check("credentials", False, error.message)
+    return Check("credentials", True, ", ".join(found) or "none required")
+
+
+def load_provider(profile: Profile) -> Provider:
+    """Create the profile's provider, with its credentials loaded the way a session would find them"""
+    provider_cls = get_provider(profile.provider)
+    stores = credential_stores(CREDENTIALS_PATH)
+    for env_variable in provider_cls.REQUIRED_ENV_VARS:
+        if env_variable not in os.environ:
+            os.environ[env_variable], _ = load_credential(stores, env_variable)
+    return provider_cls.from_env()
+
+
+def check_account(profile: Profile) -> Optional[Check]:
+    """Ask the provider whether it accepts the key, or None if the provider can't tell"""
+    try:
+        account = load_provider(profile).account_info()
+    except NotImplementedError:
+        return None
+    except Exception as e:
+        return Check("account", False, f"{profile.provider}: {e}")
+    if not account.key_valid:
+        return Check("account", False, f"{profile.provider} rejected the key")
+    detail = f"organization {account.organization}" if account.organization else "the key is valid"
+    return Check("account", True, f"{profile.provider}: {detail}")
+
+
+def check_round_trip(profile: Profile) -> Check:
+    """Send the processor a minimal message, to catch rejected keys and unknown models"""
+    try:
+        provider = load_provider(profile)
+        _, usage = provider.complete(profile.processor, PING_SYSTEM, [Message.user(PING_MESSAGE)], ())
+    except Exception as e:
+        return Check("round trip", False, f"{profile.provider}/{profile.processor}: {e}")
+    return Check("round trip", True, f"{profile.provider}/{profile.processor} used {usage.total_tokens} tokens")
+
+
+def run_checks(name: str, round_trip: bool = False) -> list[Check]:
+    """Validate the profile and everything it refers to, stopping at the first check others depend on"""
+    profile_check, profile = check_profile(name)
+    checks = [profile_check]
+    if profile is None:
+        return checks
+
+    provider_check = check_choice("provider", get_provider, profile.provider)
+    checks.append(provider_check)
+    if provider_check.ok:
+        checks.append(check_credentials(profile))
+    checks.append(check_choice("moderator", get_moderator, profile.moderator))
+    checks.extend(check_choice("toolkit", get_toolkit, spec.name) for spec in profile.toolkits)
+
+    if round_trip and all(check.ok for check in checks):
+        account_check = check_account(profile)
+        if account_check is not None:
+            checks.append(account_check)
+        checks.append(check_round_trip(profile))
+    return checks
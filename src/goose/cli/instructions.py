@@ -0,0 +1,58 @@
+from typing import Optional
+
+from attrs import define, field
+from ruamel.yaml import YAML
+from ruamel.yaml.error import YAMLError
+
+FRONT_MATTER_DELIMITER = "---"
+FRONT_MATTER_KEYS = ("model", "provider", "systems", "append_system")
+
+
+def _as_tuple(value: Optional[object]) -> tuple[str, ...]:
+    if value is None:
+        return ()
+    if isinstance(value, str):
+        return (value,)
+    return tuple(str(item) for item in value)
+
+
+@define
+class Instructions:
+    """The instructions for goose run, with the settings from the front-matter of their file"""
+
+    text: str
+    model: Optional[str] = None
+    provider: Optional[str] = None
+    systems: Optional[tuple[str, ...]] = field(default=None, converter=lambda value: _as_tuple(value) or None)
+    append_system: tuple[str, ...] = field(default=(), converter=_as_tuple)
+
+    def profile_overrides(self) -> dict[str, any]:
+        """The profile fields to replace for this run, leaving out the ones the front-matter didn't set"""
+        overrides = {"provider": self.provider, "processor": self.model, "toolkits": self.systems}
+        return {key: value for key, value in overrides.items() if value is not None}
+
+
+def parse_instructions(content: str) -> Instructions:
+    """Split the YAML front-matter between --- lines at the start of content from the instructions after it
+
+    Content without front-matter is returned unchanged as the instructions.
+    """
+    lines = content.splitlines(keepends=True)
+    if not lines or lines[0].strip() != FRONT_MATTER_DELIMITER:
+        return Instructions(content)
+    end = next((index for index, line in enumerate(lines[1:], 1) if line.strip() == FRONT_MATTER_DELIMITER), None)
+    if end is None:
+        return Instructions(content)
+
+    try:
+        front_matter = YAML(typ="safe").load("".join(lines[1:end])) or {}
+    except YAMLError as e:
+        raise ValueError(f"Could not parse the front-matter of the instructions: {e}")
+    if not isinstance(front_matter, dict):
+        raise ValueError("The front-matter of the instructions must be a mapping of settings")
+    unknown = sorted(set(front_matter) - set(FRONT_MATTER_KEYS))
+    if unknown:
+        raise ValueError(
+            f"Unknown front-matter keys: {', '.join(unknown)}. Supported keys are: {', '.join(FRONT_MATTER_KEYS)}"
+        )
+    return Instructions("".join(lines[end + 1 :]).lstrip("\n"), **front_matter)
@@ -1,18 +1,44 @@
+import json
 import os
-from datetime import datetime
+import re
+import time
+from datetime import datetime, timedelta
 from pathlib import Path
-from typing import Optional
+from typing import Optional, TextIO
 
 import click
 from rich import print
+from rich.status import Status
 from ruamel.yaml import YAML
 
-from goose.cli.config import SESSIONS_PATH
-from goose.cli.session import Session
+from goose.build import build_toolkits
+from goose.cli.config import (
+    CREDENTIALS_PATH,
+    PROFILES_CONFIG_PATH,
+    SESSIONS_PATH,
+    load_dotenv_file,
+    profile_settings,
+    read_config,
+)
+from goose.cli.doctor import run_checks
+from goose.cli.instructions import parse_instructions
+from goose.cli.replay import replay_messages
+from goose.cli.session import Session, load_profile
+from goose.cli.session_notifier import SessionNotifier
 from goose.toolkit.utils import parse_plan, render_template
 from goose.utils import load_plugins
 from goose.utils.autocomplete import SUPPORTED_SHELLS, setup_autocomplete
-from goose.utils.session_file import list_sorted_session_files
+from goose.utils.credentials import credential_stores, save_credential
+from goose.utils.session_file import (
+    list_sorted_session_files,
+    read_audit_records,
+    read_from_file,
+    read_session_tags,
+    remove_session_files,
+    session_audit_path,
+)
+from goose.utils.status import get_status_token_counts
+from goose.utils.theme import DEFAULT_THEME, check_theme
 
 LOG_LEVELS = ["DEBUG", "INFO", "WARNING", "ERROR", "CRITICAL"]
 LOG_CHOICE = click.Choice(LOG_LEVELS)
@@ -48,6 +74,21 @@ def get_version() -> None:
             print(f"  [red]Could not retrieve version for {module}: {e}[/red]")
 
 
+@goose_cli.command(name="doctor")
+@click.option("--profile", help="The profile to check, defaults to the default profile")
+@click.option("--round-trip", is_flag=True, help="Also validate the key and send the provider a minimal request")
+@click.pass_context
+def doctor(ctx: click.Context, profile: Optional[str], round_trip: bool) -> None:
+    """Check a profile and the provider it uses are configured correctly, without starting a session"""
+    checks = run_checks(profile or "default", round_trip=round_trip)
+    for check in checks:
+        mark = "[green]✓[/green]" if check.ok else "[red]✗[/red]"
+        print(f"{mark} [bold]{check.name}[/bold]: {check.detail}")
+
+    if not all(check.ok for check in checks):
+        ctx.exit(1)
+
+
 def get_current_shell() -> str:
     return os.getenv("SHELL", "").split("/")[-1]
 
@@ -142,6 +183,49 @@ def list_providers() -> None:
         print("\n")
 
 
+@providers.command(name="configure")
+@click.argument("name")
+def configure_provider(name: str) -> None:
+    """Store the environment variables a provider needs, in your keychain when one is available"""
+    providers = load_plugins(group="exchange.provider")
+    if name not in providers:
+        raise click.BadParameter(f"Unknown provider {name}, see `goose providers list`", param_hint="NAME")
+
+    stores = credential_stores(CREDENTIALS_PATH)
+    for env_variable in providers[name].REQUIRED_ENV_VARS:
+        value = click.prompt(f"Enter {env_variable} value for {name}", default="", show_default=False, hide_input=True)
+        if not value:
+            print(f"[dim]Skipped {env_variable}")
+            continue
+        store = save_credential(stores, env_variable, value)
+        print(f"Saved {env_variable} to {store.location(env_variable)}")
+
+
+@goose_cli.group()
+def profiles() -> None:
+    """Manage profiles"""
+    pass
+
+
+@profiles.command(name="list")
+@click.option("--json", "as_json", is_flag=True, help="Print the profiles as a JSON object keyed by name")
+def list_profiles(as_json: bool) -> None:
+    """List the profiles with their settings, and the credentials their providers use masked"""
+    if not PROFILES_CONFIG_PATH.exists():
+        raise click.ClickException(f"No configuration at {PROFILES_CONFIG_PATH}, start a session to create one")
+    settings = {name: profile_settings(profile) for name, profile in read_config().items()}
+    if as_json:
+        click.echo(json.dumps(settings, indent=2))
+        return
+
+    for name, profile in settings.items():
+        print(f" - [bold]{name}[/bold]: {profile['provider']}/{profile['processor']}")
+        print(f"        accelerator: {profile['accelerator']}, moderator: {profile['moderator']}")
+        print(f"        toolkits: {', '.join(toolkit['name'] for toolkit in profile['toolkits']) or 'none'}")
+        for env_variable, masked in profile["credentials"].items():
+            print(f"        {env_variable}: {masked or '[yellow]not set[/yellow]'}")
+
+
 def autocomplete_session_files(ctx: click.Context, args: str, incomplete: str) -> None:
     return [
         f"{session_name}"
@@ -154,14 +238,62 @@ def get_session_files() -> dict[str, Path]:
     return list_sorted_session_files(SESSIONS_PATH)
 
 
+def resolve_session(name: Optional[str]) -> Optional[tuple[str, Path]]:
+    """The named session, or the most recent one without a name, printing why when there is none"""
+    session_files = get_session_files()
+    if name is None:
+        if not session_files:
+            print("No sessions found.")
+            return None
+        name = list(session_files.keys())[0]
+    elif name not in session_files:
+        print(f"No session found with the name {name}.")
+        return None
+    return name, session_files[name]
+
+
+def validate_theme(ctx: click.Context, param: click.Parameter, value: Optional[str]) -> Optional[str]:
+    if value is None:
+        return None
+    try:
+        return check_theme(value)
+    except ValueError as e:
+        raise click.BadParameter(str(e))
+
+
 @session.command(name="start")
 @click.argument("name", required=False, shell_complete=autocomplete_session_files)
 @click.option("--profile")
 @click.option("--plan", type=click.Path(exists=True))
 @click.option("--log-level", type=click.Choice(["DEBUG", "INFO", "WARNING", "ERROR", "CRITICAL"]), default="INFO")
 @click.option("--tracing", is_flag=True, required=False)
+@click.option(
+    "--system-prompt-file",
+    type=click.Path(exists=True, dir_okay=False),
+    help="A jinja template to use in place of the default system prompt",
+)
+@click.option(
+    "--append-system",
+    multiple=True,
+    help="An instruction to add to the system prompt, can be repeated",
+)
+@click.option("--theme", callback=validate_theme, help="The pygments style to highlight code with, e.g. monokai")
+@click.option(
+    "--context",
+    multiple=True,
+    type=click.Path(exists=True, dir_okay=False),
+    help="A file to add to the context before the first reply, can be repeated",
+)
 def session_start(
-    name: Optional[str], profile: str, log_level: str, plan: Optional[str] = None, tracing: bool = False
+    name: Optional[str],
+    profile: str,
+    log_level: str,
+    plan: Optional[str] = None,
+    tracing: bool = False,
+    system_prompt_file: Optional[str] = None,
+    append_system: tuple[str, ...] = (),
+    theme: Optional[str] = None,
+    context: tuple[str, ...] = (),
 ) -> None:
     """Start a new goose session"""
     if plan:
@@ -172,7 +304,17 @@ def session_start(
         _plan = None
 
     try:
-        session = Session(name=name, profile=profile, plan=_plan, log_level=log_level, tracing=tracing)
+        session = Session(
+            name=name,
+            profile=profile,
+            plan=_plan,
+            log_level=log_level,
+            tracing=tracing,
+            system_prompt_file=system_prompt_file,
+            append_system=append_system,
+            theme=theme,
+        )
+        session.load_context_files(context)
         session.run()
     except RuntimeError as e:
         print(f"[red]Error: {e}")
@@ -204,7 +346,25 @@ def session_planned(plan: str, log_level: str, args: Optional[dict[str, str]]) -
 @click.argument("name", required=False, shell_complete=autocomplete_session_files)
 @click.option("--profile")
 @click.option("--log-level", type=LOG_CHOICE, default="INFO")
-def session_resume(name: Optional[str], profile: str, log_level: str) -> None:
+@click.option(
+    "--system-prompt-file",
+    type=click.Path(exists=True, dir_okay=False),
+    help="A jinja template to use in place of the default system prompt",
+)
+@click.option(
+    "--append-system",
+    multiple=True,
+    help="An instruction to add to the system prompt, can be repeated",
+)
+@click.option("--theme", callback=validate_theme, help="The pygments style to highlight code with, e.g. monokai")
+def session_resume(
+    name: Optional[str],
+    profile: str,
+    log_level: str,
+    system_prompt_file: Optional[str] = None,
+    append_system: tuple[str, ...] = (),
+    theme: Optional[str] = None,
+) -> None:
     """Resume an existing goose session"""
     session_files = get_session_files()
     if name is None:
@@ -219,7 +379,14 @@ def session_resume(name: Optional[str], profile: str, log_level: str) -> None:
             print(f"Resuming session: {name}")
         else:
             print(f"Creating new session: {name}")
-    session = Session(name=name, profile=profile, log_level=log_level)
+    session = Session(
+        name=name,
+        profile=profile,
+        log_level=log_level,
+        system_prompt_file=system_prompt_file,
+        append_system=append_system,
+        theme=theme,
+    )
     session.run(new_session=False)
 
 
@@ -229,36 +396,133 @@ def session_resume(name: Optional[str], profile: str, log_level: str) -> None:
 @click.option("--log-level", type=LOG_CHOICE, default="INFO")
 @click.option("--resume-session", is_flag=True, help="Resume the last session if available")
 @click.option("--tracing", is_flag=True, required=False)
+@click.option(
+    "--system-prompt-file",
+    type=click.Path(exists=True, dir_okay=False),
+    help="A jinja template to use in place of the default system prompt",
+)
+@click.option(
+    "--append-system",
+    multiple=True,
+    help="An instruction to add to the system prompt, can be repeated",
+)
+@click.option("--theme", callback=validate_theme, help="The pygments style to highlight code with, e.g. monokai")
+@click.option(
+    "--output-file",
+    "--output",
+    type=click.File("w"),
+    help="Write the final answer to this file, or to stdout with -",
+)
+@click.option(
+    "--timeout",
+    type=click.FloatRange(min=0, min_open=True),
+    help="Stop the run after this many seconds, saving its progress and exiting with an error",
+)
+@click.option(
+    "--continue-on-error",
+    is_flag=True,
+    help="Exit with 0 even if the run fails or ends without a final answer, e.g. to carry on with a batch",
+)
+@click.option(
+    "--context",
+    multiple=True,
+    type=click.Path(exists=True, dir_okay=False),
+    help="A file to add to the context before the first reply, can be repeated",
+)
 def run(
     message_file: Optional[str],
     profile: str,
     log_level: str,
     resume_session: bool = False,
     tracing: bool = False,
+    system_prompt_file: Optional[str] = None,
+    append_system: tuple[str, ...] = (),
+    theme: Optional[str] = None,
+    output_file: Optional[TextIO] = None,
+    timeout: Optional[float] = None,
+    continue_on_error: bool = False,
+    context: tuple[str, ...] = (),
 ) -> None:
-    """Run a single-pass session with a message from a markdown input file"""
+    """Run a single-pass session with a message from a markdown input file
+
+    The file can start with YAML front-matter between --- lines, setting the model, provider,
+    systems (toolkits) and append_system instructions to use for this run.
+
+    The run succeeds when it ends with a reply from the assistant that doesn't call a tool, and
+    exits with an error otherwise, unless --continue-on-error is set.
+    """
     if message_file:
         with open(message_file, "r") as f:
             initial_message = f.read()
     else:
         initial_message = click.get_text_stream("stdin").read()
+    try:
+        instructions = parse_instructions(initial_message)
+    except ValueError as e:
+        raise click.UsageError(str(e))
 
+    name = None
     if resume_session:
         session_files = get_session_files()
         if session_files:
             name = list(session_files.keys())[0]
-            session = Session(name=name, profile=profile, log_level=log_level, tracing=tracing)
-    else:
-        session = Session(profile=profile, log_level=log_level, tracing=tracing)
-    session.single_pass(initial_message=initial_message)
+        else:
+            print("No session found to resume, starting a new one.")
+    session = Session(
+        name=name,
+        profile=profile,
+        log_level=log_level,
+        tracing=tracing,
+        system_prompt_file=system_prompt_file,
+        append_system=instructions.append_system + append_system,
+        theme=theme,
+        profile_overrides=instructions.profile_overrides(),
+    )
+    session.load_context_files(context)
+    try:
+        answer = session.single_pass(initial_message=instructions.text, timeout=timeout)
+    except Exception as e:
+        if not continue_on_error:
+            raise
+        print(f"[yellow]The run failed, continuing because of --continue-on-error: {e}[/]")
+        return
+
+    if answer is None:
+        if not continue_on_error:
+            raise click.ClickException("The run ended without a final answer.")
+        print("[yellow]The run ended without a final answer, continuing because of --continue-on-error.[/]")
+        return
+    if output_file is not None:
+        output_file.write(answer)
 
 
 @session.command(name="list")
-def session_list() -> None:
+@click.option("--tag", "tags", multiple=True, help="Only list sessions with this tag, repeat it to require several")
+def session_list(tags: tuple[str, ...]) -> None:
     """List goose sessions"""
     session_files = get_session_files().items()
     for session_name, session_file in session_files:
-        print(f"{datetime.fromtimestamp(session_file.stat().st_mtime).strftime('%Y-%m-%d %H:%M:%S')}    {session_name}")
+        session_tags = read_session_tags(session_file)
+        if not set(tag.lstrip("#") for tag in tags) <= set(session_tags):
+            continue
+        line = f"{datetime.fromtimestamp(session_file.stat().st_mtime).strftime('%Y-%m-%d %H:%M:%S')}    {session_name}"
+        if session_tags:
+            line += "    " + " ".join(f"#{tag}" for tag in session_tags)
+        print(line)
+
+
+@session.command(name="status")
+@click.option("--profile")
+def session_status(profile: Optional[str]) -> None:
+    """Show the resources each toolkit contributes to the context, with their token counts"""
+    _profile = load_profile(profile)
+    notifier = SessionNotifier(Status(""))
+    toolkits = build_toolkits(_profile, notifier=notifier)
+    for spec, toolkit in zip(_profile.toolkits, toolkits):
+        counts = get_status_token_counts(toolkit)
+        print(f"[green]{spec.name}[/green]: [bold][cyan]{sum(counts.values())}[/cyan][/bold] tokens")
+        for name, count in counts.items():
+            print(f"    {name}: {count} tokens")
 
 
 @session.command(name="clear")
@@ -267,7 +531,100 @@ def session_clear(keep: int) -> None:
     """Delete old goose sessions, keeping the most recent sessions up to the specified number"""
     for i, (_, session_file) in enumerate(get_session_files().items()):
         if i >= keep:
-            session_file.unlink()
+            remove_session_files(session_file)
+
+
+def parse_age(ctx: click.Context, param: click.Parameter, value: Optional[str]) -> Optional[timedelta]:
+    if value is None:
+        return None
+    match = re.fullmatch(r"(\d+)([dhm])", value.strip())
+    if match is None:
+        raise click.BadParameter("Use a number followed by d, h or m, e.g. 30d")
+    amount, unit = int(match.group(1)), match.group(2)
+    return {"d": timedelta(days=amount), "h": timedelta(hours=amount), "m": timedelta(minutes=amount)}[unit]
+
+
+@session.command(name="delete")
+@click.argument("name", required=False, shell_complete=autocomplete_session_files)
+@click.option("--all", "delete_all", is_flag=True, help="Delete every session, after a confirmation")
+@click.option("--older-than", callback=parse_age, help="Delete sessions last used longer ago than this, e.g. 30d")
+@click.option("--list", "list_only", is_flag=True, help="List the sessions that would be deleted without deleting")
+def session_delete(name: Optional[str], delete_all: bool, older_than: Optional[timedelta], list_only: bool) -> None:
+    """Delete a goose session by name, or every session matching --all or --older-than"""
+    if sum([name is not None, delete_all, older_than is not None]) != 1:
+        raise click.UsageError("Specify exactly one of a session name, --all or --older-than")
+
+    session_files = get_session_files()
+    if name is not None:
+        if any(sep in name for sep in (os.sep, os.altsep, "/") if sep) or name in (".", ".."):
+            raise click.BadParameter(f"'{name}' is not a session name", param_hint="NAME")
+        to_delete = [session_files[name]] if name in session_files else []
+    elif delete_all:
+        to_delete = list(session_files.values())
+    else:
+        cutoff = datetime.now() - older_than
+        to_delete = [f for f in session_files.values() if datetime.fromtimestamp(f.stat().st_mtime) < cutoff]
+
+    if list_only:
+        for session_file in to_delete:
+            modified = datetime.fromtimestamp(session_file.stat().st_mtime).strftime("%Y-%m-%d %H:%M:%S")
+            print(f"{modified}    {session_file.stem}")
+        return
+
+    if delete_all and to_delete and not click.confirm(f"Delete all {len(to_delete)} sessions?"):
+        return
+
+    for session_file in to_delete:
+        remove_session_files(session_file)
+    print(f"Deleted {len(to_delete)} session(s)")
+
+
+@session.command(name="audit")
+@click.argument("name", required=False, shell_complete=autocomplete_session_files)
+def session_audit(name: Optional[str]) -> None:
+    """Summarize the tool calls made in a goose session, the most recent one by default"""
+    resolved = resolve_session(name)
+    if resolved is None:
+        return
+    name, session_file = resolved
+
+    records = read_audit_records(session_audit_path(session_file))
+    if not records:
+        print(f"No tool calls recorded for {name}.")
+        return
+
+    # calls, errors and total duration of each tool
+    summary = {}
+    for record in records:
+        calls, errors, duration = summary.get(record["tool"], (0, 0, 0.0))
+        summary[record["tool"]] = (calls + 1, errors + (not record["success"]), duration + record["duration"])
+
+    print(f"[green]{name}[/green]: [bold][cyan]{len(records)}[/cyan][/bold] tool calls")
+    for tool, (calls, errors, duration) in summary.items():
+        print(f"    {tool}: {calls} calls, {errors} failed, {duration:.2f}s")
+
+
+@session.command(name="replay")
+@click.argument("name", required=False, shell_complete=autocomplete_session_files)
+@click.option("--delay", type=click.FloatRange(min=0), default=0.0, help="Seconds to wait between messages")
+@click.option("--step", is_flag=True, help="Wait for a keypress before each message, instead of a delay")
+@click.option("--theme", callback=validate_theme, help="The pygments style to highlight code with, e.g. monokai")
+def session_replay(name: Optional[str], delay: float, step: bool, theme: Optional[str]) -> None:
+    """Show the messages of a goose session again, the most recent one by default, without contacting a provider"""
+    if step and delay:
+        raise click.UsageError("--step waits for a keypress between messages, so it can't be used with --delay.")
+    session = resolve_session(name)
+    if session is None:
+        return
+    _, session_file = session
+
+    def pause() -> None:
+        if step:
+            click.pause("[press any key for the next message]")
+        else:
+            time.sleep(delay)
+
+    replay_messages(read_from_file(session_file), render=print, pause=pause, theme=theme or DEFAULT_THEME)
 
 
 @click.group(
@@ -276,8 +633,11 @@ def session_clear(keep: int) -> None:
     help="AI-powered tool to assist in solving programming and operational tasks",
 )
 @click.option("-V", "--version", is_flag=True, help="List the version of goose and any plugins")
+@click.option("--no-dotenv", is_flag=True, help="Don't load environment variables from the project's .env file")
 @click.pass_context
-def cli(ctx: click.Context, version: bool, **kwargs: dict) -> None:
+def cli(ctx: click.Context, version: bool, no_dotenv: bool = False, **kwargs: dict) -> None:
+    if not no_dotenv:
+        load_dotenv_file(Path.cwd())
     if version:
         ctx.invoke(get_version)
         ctx.exit()
@@ -13,7 +13,10 @@ from goose.command import get_commands
 
 
 class GoosePromptSession:
-    def __init__(self) -> None:
+    def __init__(self, prompt: str = "G❯ ") -> None:
+        # the text shown in front of the user's input
+        self.prompt = prompt
+
         # instantiate the commands available in the prompt
         self.commands = dict()
         command_plugins = get_commands()
@@ -71,7 +74,7 @@ class GoosePromptSession:
 
     def get_user_input(self) -> "UserInput":
         try:
-            text = FormattedText([("#00AEAE", "G❯ ")])  # Define the prompt style and text.
+            text = FormattedText([("#00AEAE", self.prompt)])  # Define the prompt style and text.
             message = self.main_prompt_session.prompt(text, validator=PromptValidator(), validate_while_typing=False)
             if message.strip() in ("exit", ":q"):
                 return UserInput(PromptAction.EXIT)
@@ -0,0 +1,44 @@
+import json
+from typing import Callable
+
+from exchange import Message, Text, ToolResult, ToolUse
+from rich.console import RenderableType
+from rich.markdown import Markdown
+from rich.markup import escape
+
+from goose.toolkit.utils import preview, tool_header
+from goose.utils.theme import DEFAULT_THEME
+
+
+def render_message(message: Message, theme: str = DEFAULT_THEME) -> list[RenderableType]:
+    """What a session showed for message, with tool calls as their headers and results previewed"""
+    renderables = []
+    for content in message.content:
+        if isinstance(content, Text) and content.text.strip():
+            if message.role == "user":
+                renderables.append(f"[bold cyan]user[/bold cyan]: {escape(content.text)}")
+            else:
+                renderables.append(Markdown(content.text, code_theme=theme))
+        elif isinstance(content, ToolUse):
+            renderables.append(tool_header(content.name, json.dumps(content.parameters)))
+        elif isinstance(content, ToolResult):
+            style = "red" if content.is_error else "dim"
+            renderables.append(f"[{style}]{escape(preview(content.output))}[/{style}]")
+    return renderables
+
+
+def replay_messages(
+    messages: list[Message],
+    render: Callable[[RenderableType], None],
+    pause: Callable[[], None],
+    theme: str = DEFAULT_THEME,
+) -> None:
+    """Render the messages of a saved session in order, calling pause between them
+
+    Nothing is sent to a provider, the messages are only shown again.
+    """
+    for index, message in enumerate(messages):
+        if index:
+            pause()
+        for renderable in render_message(message, theme):
+            render(renderable)
@@ -1,28 +1,63 @@
+import _thread
+import hashlib
+import json
+import signal
+import time
 import traceback
+from contextlib import contextmanager
 from datetime import datetime
 from pathlib import Path
-from typing import Optional
+from threading import Event, Timer
+from types import FrameType
+from typing import Iterator, Mapping, Optional, Sequence
 
-from exchange import Message, Text, ToolResult, ToolUse
+import click
+from attrs import evolve
+from exchange import CompletionTimeoutError, Message, Text, ToolErrorCategory, ToolResult, ToolUse
 from exchange.observers import ObserverManager, observe_wrapper
 from rich import print
 from rich.markdown import Markdown
 from rich.panel import Panel
-from rich.prompt import Prompt
+from rich.prompt import Confirm, Prompt
 from rich.status import Status
 
 from goose._logger import get_logger, setup_logging
-from goose.cli.config import LOG_PATH, ensure_config, session_path
+from goose.build import switch_provider
+from goose.cli.config import (
+    LOG_PATH,
+    ensure_config,
+    read_config,
+    session_lock_timeout,
+    session_path,
+    use_user_prompts,
+)
 from goose.cli.prompt.goose_prompt_session import GoosePromptSession
 from goose.cli.prompt.overwrite_session_prompt import OverwriteSessionPrompt
 from goose.cli.session_notifier import SessionNotifier
 from goose.profile import Profile
+from goose.synopsis.system import system
 from goose.utils import droid, load_plugins
 from goose.utils._cost_calculator import get_total_cost_message
-from goose.utils._create_exchange import create_exchange
-from goose.utils.session_file import is_empty_session, is_existing_session, log_messages, read_or_create_file
+from goose.utils._create_exchange import create_exchange, create_provider
+from goose.utils.session_file import (
+    SessionInUseError,
+    SessionLock,
+    add_session_tags,
+    ensure_writable,
+    is_empty_session,
+    is_existing_session,
+    log_audit_record,
+    log_messages,
+    read_from_file,
+    read_or_create_file,
+    read_session_tags,
+    session_audit_path,
+    session_lock_path,
+    session_running_path,
+)
 
 RESUME_MESSAGE = "I see we were interrupted. How can I help you?"
+CONTINUE_MESSAGE = "Continue with my last instruction from where you stopped."
 
 
 def load_provider() -> str:
@@ -65,6 +100,10 @@ class Session:
         plan: Optional[dict] = None,
         log_level: Optional[str] = "INFO",
         tracing: bool = False,
+        system_prompt_file: Optional[str] = None,
+        append_system: Sequence[str] = (),
+        theme: Optional[str] = None,
+        profile_overrides: Optional[Mapping[str, any]] = None,
         **kwargs: dict[str, any],
     ) -> None:
         if name is None:
@@ -72,28 +111,52 @@ class Session:
         else:
             self.name = name
         self.profile_name = profile
-        self.prompt_session = GoosePromptSession()
         self.status_indicator = Status("", spinner="dots")
-        self.notifier = SessionNotifier(self.status_indicator)
         self.has_plan = plan is not None
         self.tracing = tracing
-
-        self.exchange = create_exchange(profile=load_profile(profile), notifier=self.notifier)
+        # set by SIGTERM, so that an interrupt it raises during a tool call stops the whole reply
+        self.terminating = Event()
+        # set when a run is stopped by its timeout
+        self.timed_out = False
+        # when the timeout of a run is reached, so that a completion that hangs is given up on then
+        self.deadline: Optional[float] = None
+
+        use_user_prompts()
+        self.profile = load_profile(profile)
+        if profile_overrides:
+            self.profile = evolve(self.profile, **profile_overrides)
+        self.theme = theme or self.profile.theme
+        self.notifier = SessionNotifier(self.status_indicator, theme=self.theme)
+        self.exchange = create_exchange(
+            profile=self.profile,
+            notifier=self.notifier,
+            system_prompt_file=Path(system_prompt_file) if system_prompt_file else None,
+            append_system=append_system,
+        )
+        self.exchange.tool_call_listeners.append(self._audit_tool_call)
+        self.exchange.tool_call_listeners.append(self._report_cancelled_tool_call)
+        self.exchange.context_trim_listeners.append(self._report_context_trimmed)
         setup_logging(log_file_directory=LOG_PATH, log_level=log_level)
 
         all_observers = load_plugins(group="exchange.observer")
-        profile_observer_names = load_profile(profile).observers
+        profile_observer_names = self.profile.observers
         observers_to_init = [all_observers[o.name]() for o in profile_observer_names if o.name in all_observers]
 
         self.observer_manager = ObserverManager.get_instance()
         self.observer_manager.initialize(tracing=tracing, observers=observers_to_init)
 
+        # in locked down environments the sessions directory can be read only, then we keep the
+        # conversation in memory rather than failing to save it after every reply
+        self.persist = ensure_writable(self.session_file_path)
+        if not self.persist:
+            print(f"[yellow]Can't write to {self.session_file_path}, this session won't be saved.[/]")
+
         self.exchange.messages.extend(self._get_initial_messages())
 
         if len(self.exchange.messages) == 0 and plan:
             self.setup_plan(plan=plan)
 
-        self.prompt_session = GoosePromptSession()
+        self.prompt_session = GoosePromptSession(prompt=self.profile.prompt)
 
     def __del__(self) -> None:
         if hasattr(self, "observer_manager"):
@@ -133,39 +196,181 @@ class Session:
     def process_first_message(self) -> Optional[Message]:
         # Get a first input unless it has been specified, such as by a plan
         if len(self.exchange.messages) == 0 or self.exchange.messages[-1].role == "assistant":
-            user_input = self.prompt_session.get_user_input()
-            if user_input.to_exit():
-                return None
-            return Message.user(text=user_input.text)
+            return self.prompt_user_message()
         return self.exchange.messages.pop()
 
-    def single_pass(self, initial_message: Optional[str]) -> None:
+    def prompt_user_message(self) -> Optional[Message]:
+        """Prompt until the user sends a message, running session commands such as /profile along the way"""
+        while True:
+            user_input = self.prompt_session.get_user_input()
+            if not user_input.to_continue():
+                return None
+            if not self.run_session_command(user_input.text):
+                return Message.user(text=user_input.text)
+
+    def run_session_command(self, text: str) -> bool:
+        """Run the input as a session command if it is one, returning whether it was"""
+        command, _, argument = text.strip().partition(" ")
+        if command == "/tag":
+            self.tag_session(argument.split())
+            return True
+        if command != "/profile":
+            return False
+
+        name = argument.strip()
+        if not name:
+            self.list_profiles()
+            return True
+        try:
+            self.switch_profile(name)
+        except Exception as e:
+            print(f"[red]Could not switch to the profile {name}: {e}[/]")
+        return True
+
+    def tag_session(self, tags: list[str]) -> None:
+        """Add tags to the session, to find it by with `goose session list --tag`, or show its tags without any"""
+        tags = [tag.lstrip("#") for tag in tags if tag.lstrip("#")]
+        if not tags:
+            current = read_session_tags(self.session_file_path)
+            print(f"[dim]tags: {' '.join('#' + tag for tag in current) or 'none, use /tag NAME... to add some'}[/]")
+            return
+        if not self.persist:
+            print("[yellow]This session isn't being saved, so its tags can't be either.[/]")
+            return
+        updated = add_session_tags(self.session_file_path, tags)
+        print(f"[dim]tags: {' '.join('#' + tag for tag in updated)}[/]")
+
+    def list_profiles(self) -> None:
+        current = self.profile_name or "default"
+        for name, profile in read_config().items():
+            marker = "[green]*[/green]" if name == current else " "
+            print(f"{marker} [bold]{name}[/bold]: {profile.provider}/{profile.processor}")
+        print("[dim]Use /profile NAME to switch, the conversation so far is kept.[/]")
+
+    def switch_profile(self, name: str) -> None:
+        """Continue the session with the provider and models of another profile"""
+        profiles = read_config()
+        if name not in profiles:
+            raise ValueError(f"there is no profile named {name}, the profiles are {', '.join(profiles)}")
+
+        profile = profiles[name]
+        self.exchange = switch_provider(self.exchange, profile, create_provider(profile))
+        self.profile = profile
+        self.profile_name = name
+        print(f"[dim]switched to profile [cyan]{name}[/cyan] | {profile.provider}/{profile.processor}[/]")
+
+    def single_pass(self, initial_message: Optional[str], timeout: Optional[float] = None) -> Optional[str]:
         """
         Handles a single input message and processes a reply
         without entering a loop for additional inputs.
 
         Args:
             initial_message (Optional[str]): The initial user message to process.
+            timeout (Optional[float]): Stop the reply after this many seconds, keeping what it did so far,
+                and raise a ClickException so the run exits with an error.
+
+        Returns:
+            Optional[str]: The final answer, the text of the last reply in this run that didn't use a tool
         """
+        first_new_message = len(self.exchange.messages)
         profile = self.profile_name or "default"
         print(f"[dim]starting session | name: [cyan]{self.name}[/]  profile: [cyan]{profile}[/]")
-        print(f"[dim]saving to {self.session_file_path}")
+        if self.persist:
+            print(f"[dim]saving to {self.session_file_path}")
+
+        timer = Timer(timeout, self._handle_timeout) if timeout is not None else None
+        with self._session_lock(interactive=False), self._sigterm_as_interrupt():
+            if timer is not None:
+                # the timer only interrupts between python calls, a completion blocked on the network
+                # is given up on at the deadline instead
+                self.deadline = time.monotonic() + timeout
+                timer.start()
+            try:
+                self._single_pass_replies(initial_message)
+            except KeyboardInterrupt:
+                # the timer or a SIGTERM can fire just as the reply finishes, once nothing is listening for it
+                if not (self.timed_out or self.terminating.is_set()):
+                    raise
+            finally:
+                self.deadline = None
+                if timer is not None:
+                    self._stop_timer(timer)
 
+        print(f"[dim]ended run | name: [cyan]{self.name}[/]  profile: [cyan]{profile}[/]")
+        print(f"[dim]to resume: [magenta]goose session resume {self.name} --profile {profile}[/][/]")
+        if self.timed_out:
+            raise click.ClickException(f"The run timed out after {timeout:g} seconds.")
+        return self._final_answer(self.exchange.messages[first_new_message:])
+
+    def _single_pass_replies(self, initial_message: Optional[str]) -> None:
         # Check to see if there is a planned operation to perform prior to the bespoke prompt
         if self.has_plan and len(self.exchange.messages) > 0:
             # Process the plan prompt
             self.exchange.add(self.exchange.messages.pop())
             self.reply()
 
-        if initial_message:
-            # Process initial message
-            message = Message.user(initial_message)
+        if initial_message and not self.terminating.is_set():
+            # Process initial message, unless we are resuming a run of the same instruction. The
+            # interrupted reply was already recovered when the session loaded, so we ask to continue it
+            if self._last_user_text() == initial_message.strip():
+                message = Message.user(CONTINUE_MESSAGE)
+            else:
+                message = Message.user(initial_message)
 
             self.exchange.add(message)
             self.reply()  # Process the user message
 
-        print(f"[dim]ended run | name: [cyan]{self.name}[/]  profile: [cyan]{profile}[/]")
-        print(f"[dim]to resume: [magenta]goose session resume {self.name} --profile {profile}[/][/]")
+    def _stop_timer(self, timer: Timer) -> None:
+        """Cancel the timeout, waiting for a timer that already fired so that its interrupt can't escape later"""
+        try:
+            timer.cancel()
+            timer.join()
+        except KeyboardInterrupt:
+            if not self.timed_out:
+                raise
+
+    def _generate(self) -> Message:
+        """Generate the next message, stopping the reply like the timer would once the run's deadline passes"""
+        if self.deadline is None:
+            return self.exchange.generate()
+        remaining = max(self.deadline - time.monotonic(), 0.0)
+        if self.exchange.completion_timeout is not None and self.exchange.completion_timeout < remaining:
+            return self.exchange.generate()
+        try:
+            return self.exchange.generate(completion_timeout=remaining)
+        except CompletionTimeoutError:
+            self.timed_out = True
+            self.terminating.set()
+            raise KeyboardInterrupt
+
+    def _handle_timeout(self) -> None:
+        """Stop the reply in progress like a SIGTERM would, so that it is saved before the run exits"""
+        self.timed_out = True
+        self.terminating.set()
+        _thread.interrupt_main()
+
+    @staticmethod
+    def _final_answer(messages: list[Message]) -> Optional[str]:
+        for message in reversed(messages):
+            if message.role == "assistant" and not message.tool_use:
+                return message.text
+        return None
+
+    def load_context_files(self, paths: Sequence[str]) -> None:
+        """Make the files active before the first reply, so their content is in the context from the start"""
+        for path in paths:
+            try:
+                system.remember_file(path)
+            except ValueError as e:
+                raise click.ClickException(str(e))
+            print(f"[dim]added {path} to the context")
+
+    def _last_user_text(self) -> Optional[str]:
+        """The text of the most recent message the user typed, skipping tool results"""
+        for message in reversed(self.exchange.messages):
+            if message.role == "user" and message.text:
+                return message.text.strip()
+        return None
 
     def run(self, new_session: bool = True) -> None:
         """
@@ -176,18 +381,86 @@ class Session:
             new_session (bool): True when starting a new session, False when resuming.
         """
         time_start = datetime.now()
-        if is_existing_session(self.session_file_path) and new_session:
+        if self.persist and is_existing_session(self.session_file_path) and new_session:
             self._prompt_overwrite_session()
 
         profile_name = self.profile_name or "default"
-        print(f"[dim]starting session | name: [cyan]{self.name}[/cyan]  profile: [cyan]{profile_name}[/cyan][/dim]")
+        model = f"{self.profile.provider}/{self.profile.processor}"
+        print(
+            f"[dim]starting session | name: [cyan]{self.name}[/cyan]  profile: [cyan]{profile_name}[/cyan]"
+            + f"  model: [cyan]{model}[/cyan][/dim]"
+        )
         print()
+
+        with self._session_lock():
+            self._run_loop()
+
+        self._remove_empty_session()
+        time_end = datetime.now()
+        self._log_cost(start_time=time_start, end_time=time_end)
+
+    @contextmanager
+    def _session_lock(self, interactive: bool = True) -> Iterator[None]:
+        """Hold the session's lock while replying, so another process can't write to the session at the same time
+
+        Only an interactive session asks whether to continue after an unclean exit, others keep the history.
+        """
+        if not self.persist:
+            yield
+            return
+        lock = SessionLock(session_lock_path(self.session_file_path))
+        try:
+            lock.acquire(timeout=session_lock_timeout())
+        except (SessionInUseError, ValueError) as e:
+            raise click.ClickException(str(e))
+
+        try:
+            # the marker is only removed when we exit cleanly, and checked once we hold the lock,
+            # so finding it means the last run was killed rather than that another one is running
+            running_path = session_running_path(self.session_file_path)
+            if running_path.exists():
+                print("[yellow]The last run of this session did not exit cleanly, its final reply may not be saved.[/]")
+                if self.exchange.messages and interactive:
+                    self._offer_to_continue()
+                elif self.exchange.messages:
+                    print("[dim]continuing from where it stopped[/]")
+            running_path.touch()
+            yield
+            running_path.unlink(missing_ok=True)
+        finally:
+            lock.release()
+
+    def _offer_to_continue(self) -> None:
+        """Continue the conversation an unclean exit left behind, or start the session over"""
+        if Confirm.ask("Continue from where it stopped? Otherwise the session starts over", default=True):
+            return
+        self.exchange.messages.clear()
+        with open(self.session_file_path, "w") as f:
+            f.write("")
+
+    def _run_loop(self) -> None:
+        with self._sigterm_as_interrupt():
+            self._prompt_and_reply()
+
+    @contextmanager
+    def _sigterm_as_interrupt(self) -> Iterator[None]:
+        """Handle SIGTERM like ctrl-c while replying, restoring the previous handler afterwards"""
+        previous_sigterm_handler = signal.signal(signal.SIGTERM, self._handle_sigterm)
+        try:
+            yield
+        finally:
+            signal.signal(signal.SIGTERM, previous_sigterm_handler)
+
+    def _prompt_and_reply(self) -> None:
         message = self.process_first_message()
         while message:  # Loop until no input (empty string).
             self.notifier.start()
             try:
                 self.exchange.add(message)
                 self.reply()  # Process the user message.
+            except CompletionTimeoutError as e:
+                self.exchange.rewind()
+                print(f"[yellow]{e}. We've removed your last message, send it again to retry.[/]")
             except Exception:
                 # rewind to right before the last user message
                 self.exchange.rewind()
@@ -199,13 +472,15 @@ class Session:
                     + " - [yellow]depending on the error you may be able to continue[/]"
                 )
             self.notifier.stop()
+            if self.terminating.is_set():
+                break
             print()  # Print a newline for separation.
-            user_input = self.prompt_session.get_user_input()
-            message = Message.user(text=user_input.text) if user_input.to_continue() else None
+            message = self.prompt_user_message()
 
-        self._remove_empty_session()
-        time_end = datetime.now()
-        self._log_cost(start_time=time_start, end_time=time_end)
+    def _handle_sigterm(self, signum: int, frame: Optional[FrameType]) -> None:
+        """Handle a termination request like ctrl-c, so the reply in progress is saved before we exit"""
+        self.terminating.set()
+        raise KeyboardInterrupt
 
     @observe_wrapper(session_id=lambda instance: instance.name)
     def reply(self) -> None:
@@ -215,27 +490,22 @@ class Session:
 
         try:
             self.status_indicator.update("processing request")
-            response = self.exchange.generate()
+            response = self._generate()
             self.status_indicator.update("got response, processing")
             committed.append(response)
-
-            if response.text:
-                print(Markdown(response.text))
+            self._print_response(response)
 
             while response.tool_use:
-                content = []
-                for tool_use in response.tool_use:
-                    tool_result = self.exchange.call_function(tool_use)
-                    content.append(tool_result)
+                content = self.exchange.call_functions(
+                    response.tool_use, cancel=self.terminating, cancel_tool_on_interrupt=True
+                )
                 message = Message(role="user", content=content)
                 committed.append(message)
                 self.exchange.add(message)
                 self.status_indicator.update("processing tool results")
-                response = self.exchange.generate()
+                response = self._generate()
                 committed.append(response)
-
-                if response.text:
-                    print(Markdown(response.text))
+                self._print_response(response)
         except KeyboardInterrupt:
             # The interrupt reply modifies the message history,
             # and we sync those changes to committed
@@ -243,7 +513,13 @@ class Session:
 
         # we log the committed messages only once the reply completes
         # this prevents messages related to uncaught errors from being recorded
-        log_messages(self.session_file_path, committed)
+        if self.persist:
+            log_messages(self.session_file_path, committed)
+
+    def _print_response(self, response: Message) -> None:
+        # skip whitespace-only text, which would otherwise print as an empty block before a tool call
+        if response.text.strip():
+            print(Markdown(response.text, code_theme=self.theme))
 
     def interrupt_reply(self, committed: list[Message]) -> None:
         """Recover from an interruption at an arbitrary state"""
@@ -280,11 +556,37 @@ class Session:
         # Print the recovery message with markup for visibility.
         print(f"[yellow]{recovery}[/]")
 
+    def _audit_tool_call(self, tool_use: ToolUse, result: ToolResult, duration: float) -> None:
+        """Record a tool call in the audit file kept alongside the session"""
+        if not self.persist:
+            return
+        parameters = json.dumps(tool_use.parameters, sort_keys=True, default=str)
+        record = {
+            "timestamp": datetime.now().isoformat(),
+            "tool": tool_use.name,
+            "args_hash": hashlib.sha256(parameters.encode()).hexdigest()[:16],
+            "duration": duration,
+            "success": not result.is_error,
+            "error_category": result.error_category,
+        }
+        log_audit_record(session_audit_path(self.session_file_path), record)
+
+    def _report_cancelled_tool_call(self, tool_use: ToolUse, result: ToolResult, duration: float) -> None:
+        """Let the user know a ctrl-c skipped only the running tool, and how to stop the whole reply"""
+        if result.error_category == ToolErrorCategory.CANCELLED:
+            print(f"[yellow]Cancelled the call to {tool_use.name}, press ctrl-c again to stop the whole reply.[/]")
+
+    def _report_context_trimmed(self, dropped: int) -> None:
+        """Let the user know older messages were dropped, so it's clear why they may be forgotten"""
+        print(f"[dim yellow]⚠ context trimmed, dropped {dropped} older messages[/]")
+
     @property
     def session_file_path(self) -> Path:
         return session_path(self.name)
 
     def load_session(self) -> list[Message]:
+        if not self.persist:
+            return read_from_file(self.session_file_path) if is_existing_session(self.session_file_path) else []
         return read_or_create_file(self.session_file_path)
 
     def _log_cost(self, start_time: datetime, end_time: datetime) -> None:
@@ -1,20 +1,26 @@
 from rich.status import Status
 from rich.live import Live
 from rich.console import RenderableType
+from rich.markdown import Markdown
 from rich import print
 
 from goose.notifier import Notifier
+from goose.utils.theme import DEFAULT_THEME
 
 
 class SessionNotifier(Notifier):
-    def __init__(self, status_indicator: Status) -> None:
+    def __init__(self, status_indicator: Status, theme: str = DEFAULT_THEME) -> None:
         self.status_indicator = status_indicator
         self.live = Live(self.status_indicator, refresh_per_second=8, transient=True)
+        self.theme = theme
 
     def notify(self, message: str) -> None:
         print(f"Notification: {message}")
 
     def log(self, content: RenderableType) -> None:
+        if isinstance(content, Markdown):
+            # toolkits build their markdown without knowing the theme, so apply it to code blocks here
+            content.code_theme = self.theme
         print(content)
 
     def status(self, status: str) -> None:
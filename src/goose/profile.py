@@ -3,6 +3,7 @@ from typing import Mapping
 from attrs import asdict, define, field
 
 from goose.utils import ensure_list
+from goose.utils.theme import DEFAULT_THEME, check_theme
 
 
 @define
@@ -30,6 +31,10 @@ class Profile:
     moderator: str
     toolkits: list[ToolkitSpec] = field(factory=list, converter=ensure_list(ToolkitSpec))
     observers: list[ObserverSpec] = field(factory=list, converter=ensure_list(ObserverSpec))
+    generation_args: dict[str, any] = field(factory=dict)
+    name: str = "Goose"
+    prompt: str = "G❯ "
+    theme: str = field(default=DEFAULT_THEME)
 
     @toolkits.validator
     def check_toolkit_requirements(self, _: type["ToolkitSpec"], toolkits: list[ToolkitSpec]) -> None:
@@ -44,6 +49,10 @@ class Profile:
                     msg = f"Toolkit {toolkit_name} requires {req} but it is not present"
                     raise ValueError(msg)
 
+    @theme.validator
+    def check_theme_installed(self, _: type["str"], theme: str) -> None:
+        check_theme(theme)
+
     def to_dict(self) -> dict[str, any]:
         return asdict(self)
 
@@ -1,10 +1,11 @@
 import os
 from pathlib import Path
 
+from exchange import ToolError
 from goose.notifier import Notifier
 from goose.view import ExchangeView
 from goose.synopsis.system import system
-from goose.utils.shell import shell
+from goose.utils.shell import bash_not_found_error, find_bash, shell
 from goose.synopsis.util import log_command
 
 
@@ -12,15 +13,34 @@ class Bash:
     def __init__(self, notifier: Notifier, exchange_view: ExchangeView) -> None:
         self.notifier = notifier
         self.exchange_view = exchange_view
+        # commands are written for bash, so run them with it rather than the platform's default shell
+        self.executable = find_bash()
+
+    def _run(self, command: str) -> str:
+        return system.redact(self._execute(command))
+
+    def _execute(self, command: str) -> str:
+        """Run the command and return its output as is, only an error is redacted as it goes to the model"""
+        if self.executable is None:
+            raise bash_not_found_error()
+        try:
+            return shell(
+                command, self.notifier, self.exchange_view, cwd=system.cwd, env=system.env, executable=self.executable
+            )
+        except ToolError as e:
+            raise ToolError(system.redact(e.message), e.category) from e
 
     def _logshell(self, command: str, title: str = "shell") -> None:
         log_command(self.notifier, command, path=os.path.abspath(system.cwd), title=title)
 
     def _source(self, path: str) -> str:
         """Source the file at path."""
+        # resolving the path refuses a file outside of the sandbox or one that isn't permitted
+        system.to_file_patho(path)
         source_command = f"source {path} && env"
         self._logshell(f"source {path}")
-        result = shell(source_command, self.notifier, self.exchange_view, cwd=system.cwd, env=system.env)
+        # the env dump is never shown to the model, and redacting it would put the placeholders into the env
+        result = self._execute(source_command)
         env_vars = dict(line.split("=", 1) for line in result.splitlines() if "=" in line)
         system.env.update(env_vars)
         return f"Sourced {path}"
@@ -35,7 +55,7 @@ class Bash:
             raise ValueError("You must source files through the bash tool with 'source' command.")
 
         self._logshell(command)
-        return shell(command, self.notifier, self.exchange_view, cwd=system.cwd, env=system.env)
+        return self._run(command)
 
     def _change_dir(self, path: str) -> str:
         """Change the directory to the specified path."""
@@ -0,0 +1,139 @@
+import json
+from io import StringIO
+from typing import Literal, Optional
+
+import tomlkit
+from exchange import ToolError, ToolErrorCategory
+from ruamel.yaml import YAML
+
+from goose.notifier import Notifier
+from goose.synopsis.file_history import FileHistory
+from goose.synopsis.system import system
+from goose.toolkit.utils import tool_header
+
+ConfigEditCommand = Literal["set", "delete"]
+ConfigFormat = Literal["json", "yaml", "toml"]
+
+FORMATS: dict[str, ConfigFormat] = {".json": "json", ".yaml": "yaml", ".yml": "yaml", ".toml": "toml"}
+MISSING = object()
+
+
+def _display(value: object) -> str:
+    if value is MISSING:
+        return "(missing)"
+    # tomlkit wraps values to keep their formatting, unwrap them to show the plain value
+    if hasattr(value, "unwrap"):
+        value = value.unwrap()
+    return json.dumps(value, default=str)
+
+
+def _parse_value(value: Optional[str]) -> object:
+    """Read value as JSON, so numbers, booleans, lists and objects keep their type, or else as a string"""
+    if value is None:
+        raise ValueError("The value is required to set a key.")
+    try:
+        return json.loads(value)
+    except json.JSONDecodeError:
+        return value
+
+
+def _json_indent(content: str) -> int:
+    for line in content.splitlines()[1:]:
+        stripped = line.lstrip(" ")
+        if stripped and len(stripped) < len(line):
+            return len(line) - len(stripped)
+    return 2
+
+
+class ConfigEditor:
+    def __init__(self, notifier: Notifier, file_history: FileHistory) -> None:
+        self.notifier = notifier
+        # shared with the text editor, so that undo_edit can restore an edited file
+        self._file_history = file_history
+
+    def _load(self, content: str, config_format: ConfigFormat) -> object:
+        if config_format == "json":
+            return json.loads(content)
+        if config_format == "yaml":
+            return YAML().load(content)
+        return tomlkit.parse(content)
+
+    def _dump(self, data: object, config_format: ConfigFormat, original: str) -> str:
+        if config_format == "json":
+            dumped = json.dumps(data, indent=_json_indent(original), ensure_ascii=False)
+            return dumped + "\n" if original.endswith("\n") else dumped
+        if config_format == "yaml":
+            stream = StringIO()
+            YAML().dump(data, stream)
+            return stream.getvalue()
+        return tomlkit.dumps(data)
+
+    def _resolve_parent(self, data: object, parts: list[str], create: bool) -> object:
+        """The container holding the last part of the key, creating missing tables when setting"""
+        current = data
+        for part in parts[:-1]:
+            if isinstance(current, list):
+                if not part.isdigit() or int(part) >= len(current):
+                    raise ToolError(f"There is no index {part} in the list at this key.", ToolErrorCategory.NOT_FOUND)
+                current = current[int(part)]
+            elif isinstance(current, dict):
+                if part not in current:
+                    if not create:
+                        raise ToolError(f"The key {part} does not exist.", ToolErrorCategory.NOT_FOUND)
+                    current[part] = {}
+                current = current[part]
+            else:
+                raise ValueError(f"The key {part} is inside a value that is not a table or list.")
+        return current
+
+    def edit(self, path: str, command: ConfigEditCommand, key: str, value: Optional[str] = None) -> str:
+        """Set or delete the value at a dotted key in a JSON, YAML or TOML file, returning the change."""
+        patho = system.to_file_patho(path)
+        config_format = FORMATS.get(patho.suffix.lower())
+        if config_format is None:
+            supported = ", ".join(FORMATS)
+            raise ToolError(
+                f"Can't edit {path} as a config file, the supported extensions are {supported}.",
+                ToolErrorCategory.INVALID_ARGS,
+            )
+        if not patho.is_file():
+            raise ToolError(f"The file {path} does not exist.", ToolErrorCategory.NOT_FOUND)
+        if command not in ("set", "delete"):
+            raise ValueError(f"Unknown command '{command}'.")
+
+        content = patho.read_text()
+        try:
+            data = self._load(content, config_format)
+        except Exception as e:
+            raise ValueError(f"Could not parse {path} as {config_format}: {e}")
+
+        parts = key.split(".")
+        parent = self._resolve_parent(data, parts, create=command == "set")
+        last = parts[-1]
+        if isinstance(parent, list):
+            if not last.isdigit() or int(last) >= len(parent):
+                raise ToolError(f"There is no index {last} in the list at {key}.", ToolErrorCategory.NOT_FOUND)
+            last = int(last)
+            before = parent[last]
+        elif isinstance(parent, dict):
+            before = parent.get(last, MISSING)
+        else:
+            raise ValueError(f"The key {key} is inside a value that is not a table or list.")
+
+        if command == "set":
+            parent[last] = _parse_value(value)
+            after = parent[last]
+        else:
+            if before is MISSING:
+                raise ToolError(f"The key {key} does not exist in {path}.", ToolErrorCategory.NOT_FOUND)
+            del parent[last]
+            after = MISSING
+
+        self._file_history.save(str(patho), content)
+        patho.write_text(self._dump(data, config_format, content))
+
+        change = f"{key}: {_display(before)} -> {_display(after)}"
+        self.notifier.log("")
+        self.notifier.log(tool_header("config_edit", f"{path} | {change}"))
+        self.notifier.log("")
+        return f"Successfully updated {path}, {change}"
@@ -0,0 +1,36 @@
+from difflib import unified_diff
+
+from attrs import define
+from exchange import ToolError, ToolErrorCategory
+from goose.synopsis.system import system
+
+
+@define
+class FileDiff:
+    diff: str
+    added: int
+    removed: int
+
+    @property
+    def summary(self) -> str:
+        return f"{self.added} lines added, {self.removed} lines removed"
+
+
+def _read_file(path: str) -> list[str]:
+    patho = system.to_file_patho(path)
+    if not patho.exists():
+        raise ToolError(f"The file {path} does not exist, so there is nothing to compare.", ToolErrorCategory.NOT_FOUND)
+    if not patho.is_file():
+        raise ValueError(f"{path} is a directory, only files can be compared.")
+    return patho.read_text().splitlines(keepends=True)
+
+
+def compare_files(path_a: str, path_b: str) -> FileDiff:
+    """The unified diff that turns the file at path_a into the one at path_b"""
+    lines = list(unified_diff(_read_file(path_a), _read_file(path_b), fromfile=path_a, tofile=path_b))
+    # the first two lines are the ---/+++ file headers
+    changes = lines[2:]
+    added = sum(1 for line in changes if line.startswith("+"))
+    removed = sum(1 for line in changes if line.startswith("-"))
+    diff = "".join(line if line.endswith("\n") else line + "\n\\ No newline at end of file\n" for line in lines)
+    return FileDiff(diff, added, removed)
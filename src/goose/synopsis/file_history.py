@@ -0,0 +1,62 @@
+from itertools import count
+
+# enough to step back through a run of edits, without keeping every version of a file edited all session
+DEFAULT_MAX_VERSIONS = 10
+DEFAULT_MAX_BYTES = 20 * 1024 * 1024
+
+
+class FileHistory:
+    """The earlier versions of the files changed in a session, so that each change can be undone
+
+    At most max_versions are kept for each file and max_bytes of content across all files. When
+    either is exceeded the oldest versions are dropped, so undo only reaches back that far.
+    """
+
+    def __init__(self, max_versions: int = DEFAULT_MAX_VERSIONS, max_bytes: int = DEFAULT_MAX_BYTES) -> None:
+        self.max_versions = max_versions
+        self.max_bytes = max_bytes
+        self.size = 0
+        # each version is kept with when it was saved, to find the oldest one across files
+        self._versions: dict[str, list[tuple[int, str]]] = {}
+        self._order = count()
+        self._dropped: set[str] = set()
+
+    def __contains__(self, path: str) -> bool:
+        return bool(self._versions.get(path))
+
+    def versions(self, path: str) -> list[str]:
+        """The versions kept for path, oldest first"""
+        return [content for _, content in self._versions.get(path, [])]
+
+    def save(self, path: str, content: str) -> None:
+        """Keep content as the version of path to restore on the next undo"""
+        self._versions.setdefault(path, []).append((next(self._order), content))
+        self.size += len(content.encode())
+
+        if len(self._versions[path]) > self.max_versions:
+            self._drop_oldest(path)
+        while self.size > self.max_bytes and self._versions:
+            self._drop_oldest(min(self._versions, key=lambda kept: self._versions[kept][0][0]))
+
+    def pop(self, path: str) -> str:
+        """Remove and return the latest version of path, raising ValueError if none is left"""
+        if path not in self:
+            if path in self._dropped:
+                raise ValueError(
+                    f"No edit history left to undo changes on {path}, "
+                    "its older versions were dropped to stay within the history limits."
+                )
+            raise ValueError(f"No edit history available to undo changes on {path}.")
+
+        _, content = self._versions[path].pop()
+        self.size -= len(content.encode())
+        if not self._versions[path]:
+            del self._versions[path]
+        return content
+
+    def _drop_oldest(self, path: str) -> None:
+        _, content = self._versions[path].pop(0)
+        self.size -= len(content.encode())
+        self._dropped.add(path)
+        if not self._versions[path]:
+            del self._versions[path]
@@ -0,0 +1,117 @@
+import shutil
+from pathlib import Path
+from typing import Literal, Optional
+
+from exchange import ToolError, ToolErrorCategory
+from goose.notifier import Notifier
+from goose.synopsis.file_history import FileHistory
+from goose.synopsis.system import system
+from goose.toolkit.utils import tool_header
+
+FileOperationsCommand = Literal["move", "copy", "delete"]
+
+
+class FileOperations:
+    def __init__(self, notifier: Notifier, file_history: FileHistory) -> None:
+        self.notifier = notifier
+        # shared with the text editor, so that undo_edit can restore a deleted file
+        self._file_history = file_history
+
+        # Command dispatch dictionary
+        self.command_dispatch = {
+            "move": self._move,
+            "copy": self._copy,
+            "delete": self._delete,
+        }
+
+    def _resolve(self, path: str, allow_outside: bool) -> Path:
+        """Resolve path against the cwd, refusing paths outside of it unless allowed."""
+        patho = system.to_file_patho(path)
+        if not allow_outside and not patho.resolve().is_relative_to(Path(system.cwd).resolve()):
+            raise ToolError(
+                f"{path} is outside of the working directory {system.cwd}, set allow_outside to operate on it.",
+                ToolErrorCategory.PERMISSION_DENIED,
+            )
+        return patho
+
+    def _resolve_source(self, path: str, allow_outside: bool) -> Path:
+        patho = self._resolve(path, allow_outside)
+        if not patho.exists():
+            raise ToolError(f"The path {path} does not exist.", ToolErrorCategory.NOT_FOUND)
+        return patho
+
+    def _resolve_destination(self, destination: Optional[str], allow_outside: bool) -> Path:
+        if not destination:
+            raise ValueError("The destination is required to move or copy a file.")
+        patho = self._resolve(destination, allow_outside)
+        if patho.exists():
+            raise ValueError(f"The destination {destination} already exists, delete it first to replace it.")
+        return patho
+
+    def _move(self, path: str, destination: Optional[str], allow_outside: bool) -> str:
+        """Move or rename a file or directory, keeping moved files active."""
+        source = self._resolve_source(path, allow_outside)
+        target = self._resolve_destination(destination, allow_outside)
+
+        moved = [source] if source.is_file() else [p for p in source.rglob("*") if p.is_file()]
+        active = [p for p in moved if system.is_active(str(p))]
+
+        target.parent.mkdir(parents=True, exist_ok=True)
+        shutil.move(source, target)
+        for patho in active:
+            system.forget_file(str(patho))
+            system.remember_file(str(target / patho.relative_to(source)))
+
+        self._log_file_operation("move", f"{path} -> {destination}")
+        return f"Successfully moved {path} to {destination}"
+
+    def _copy(self, path: str, destination: Optional[str], allow_outside: bool) -> str:
+        """Copy a file or directory."""
+        source = self._resolve_source(path, allow_outside)
+        target = self._resolve_destination(destination, allow_outside)
+
+        target.parent.mkdir(parents=True, exist_ok=True)
+        if source.is_dir():
+            shutil.copytree(source, target)
+        else:
+            shutil.copy2(source, target)
+
+        self._log_file_operation("copy", f"{path} -> {destination}")
+        return f"Successfully copied {path} to {destination}"
+
+    def _delete(self, path: str, destination: Optional[str], allow_outside: bool) -> str:
+        """Delete a file, keeping its content so the delete can be undone."""
+        if destination:
+            raise ValueError("delete does not take a destination, only the path to delete.")
+        patho = self._resolve_source(path, allow_outside)
+        if not patho.is_file():
+            raise ValueError(f"{path} is a directory, only files can be deleted.")
+
+        try:
+            content = patho.read_text()
+        except UnicodeDecodeError:
+            # the history only keeps text, so a binary file can't be restored
+            content = None
+        if content is not None:
+            self._file_history.save(str(patho), content)
+        patho.unlink()
+        system.forget_file(str(patho))
+
+        self._log_file_operation("delete", path)
+        if content is None:
+            return f"Successfully deleted {path}, it is a binary file so the delete can't be undone"
+        return f"Successfully deleted {path}, use undo_edit on it to restore it"
+
+    def _log_file_operation(self, command: str, description: str) -> None:
+        self.notifier.log("")
+        self.notifier.log(tool_header(command, description))
+        self.notifier.log("")
+
+    def run_command(
+        self, command: FileOperationsCommand, path: str, destination: Optional[str] = None, allow_outside: bool = False
+    ) -> str:
+        """Dispatch file operations to the appropriate handler."""
+        if command not in self.command_dispatch:
+            raise ValueError(f"Unknown command '{command}'.")
+
+        return self.command_dispatch[command](path, destination=destination, allow_outside=allow_outside)
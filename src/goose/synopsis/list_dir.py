@@ -0,0 +1,131 @@
+from datetime import datetime
+from fnmatch import fnmatch
+from pathlib import Path
+from typing import Iterator, Literal, Optional
+
+from attrs import define
+from exchange import ToolError, ToolErrorCategory
+from goose.synopsis.system import system
+
+# caps on what a single listing returns, so that a large tree can't flood the context
+MAX_ENTRIES = 200
+MAX_DEPTH = 5
+IGNORE_FILES = (".gitignore", ".gooseignore")
+
+EntryType = Literal["file", "dir", "symlink"]
+
+
+@define
+class Entry:
+    path: str
+    type: EntryType
+    size: Optional[int]
+    modified: str
+
+    def __str__(self) -> str:
+        size = "-" if self.size is None else str(self.size)
+        name = f"{self.path}/" if self.type == "dir" else self.path
+        return f"{self.type:<7} {size:>10}  {self.modified}  {name}"
+
+
+@define
+class IgnorePattern:
+    base: Path
+    pattern: str
+    dir_only: bool
+    anchored: bool
+
+    def matches(self, patho: Path, is_dir: bool) -> bool:
+        if self.dir_only and not is_dir:
+            return False
+        try:
+            relative = patho.relative_to(self.base)
+        except ValueError:
+            return False
+        if self.anchored:
+            return fnmatch(relative.as_posix(), self.pattern)
+        return any(fnmatch(part, self.pattern) for part in relative.parts)
+
+
+def load_ignore_patterns(directories: list[Path]) -> list[IgnorePattern]:
+    """Read the .gitignore and .gooseignore files in each directory
+
+    Negated patterns are not supported and are skipped.
+    """
+    patterns = []
+    for directory in directories:
+        for ignore_file in IGNORE_FILES:
+            ignore_path = directory / ignore_file
+            if not ignore_path.is_file():
+                continue
+            for line in ignore_path.read_text().splitlines():
+                line = line.strip()
+                if not line or line.startswith(("#", "!")):
+                    continue
+                dir_only = line.endswith("/")
+                line = line.rstrip("/")
+                anchored = "/" in line
+                patterns.append(IgnorePattern(directory, line.lstrip("/"), dir_only, anchored))
+    return patterns
+
+
+def list_directory(path: str, recursive: bool = False, max_depth: int = MAX_DEPTH) -> tuple[list[Entry], bool]:
+    """List the entries under path, sorted by path and skipping ignored files
+
+    Returns the entries, capped at MAX_ENTRIES, and whether the listing was truncated.
+    """
+    root = system.to_patho(path)
+    if not root.is_dir():
+        raise ToolError(f"The path {path} is not an existing directory.", ToolErrorCategory.NOT_FOUND)
+
+    depth = min(max(max_depth, 1), MAX_DEPTH) if recursive else 1
+    ignore_dirs = list(dict.fromkeys([Path(system.cwd), root]))
+    patterns = load_ignore_patterns(ignore_dirs)
+
+    entries = []
+    truncated = False
+
+    def walk(directory: Path, level: int) -> None:
+        nonlocal truncated
+        for child in sorted(directory.iterdir()):
+            if truncated:
+                return
+            is_dir = child.is_dir() and not child.is_symlink()
+            if child.name == ".git" or any(pattern.matches(child, is_dir) for pattern in patterns):
+                continue
+            if len(entries) == MAX_ENTRIES:
+                truncated = True
+                return
+
+            stat = child.lstat()
+            if child.is_symlink():
+                entry_type, size = "symlink", None
+            elif is_dir:
+                entry_type, size = "dir", None
+            else:
+                entry_type, size = "file", stat.st_size
+            modified = datetime.fromtimestamp(stat.st_mtime).isoformat(sep=" ", timespec="seconds")
+            entries.append(Entry(child.relative_to(root).as_posix(), entry_type, size, modified))
+
+            if is_dir and level < depth:
+                walk(child, level + 1)
+
+    walk(root, 1)
+    return entries, truncated
+
+
+def iter_files(root: Path) -> Iterator[Path]:
+    """Every file under root in sorted order, skipping .git and the files ignored in root"""
+    patterns = load_ignore_patterns([root])
+
+    def walk(directory: Path) -> Iterator[Path]:
+        for child in sorted(directory.iterdir()):
+            is_dir = child.is_dir() and not child.is_symlink()
+            if child.name == ".git" or any(pattern.matches(child, is_dir) for pattern in patterns):
+                continue
+            if is_dir:
+                yield from walk(child)
+            elif child.is_file():
+                yield child
+
+    yield from walk(root)
@@ -85,13 +85,17 @@ class Synopsis(Moderator):
         if len(self.originals) < 5:
             return "\n".join([message.summary for message in self.originals])
         model = os.environ.get("SUMMARIZER", exchange.model)
-        new_exchange = exchange.replace(moderator=ContextTruncate(), tools=(), system="", messages=[], model=model)
+        new_exchange = exchange.replace(
+            moderator=ContextTruncate(), tools=(), system="", messages=[], model=model, samples=1
+        )
         new_exchange.add(message)
         return new_exchange.generate().content[0].text
 
     def plan(self, exchange: Exchange) -> str:
         message = Message.load("plan.md", synopsis=self, messages=self.originals, exchange=exchange, system=system)
         model = os.environ.get("PLANNER", exchange.model)
-        new_exchange = exchange.replace(moderator=PassiveModerator(), tools=(), system="", messages=[], model=model)
+        new_exchange = exchange.replace(
+            moderator=PassiveModerator(), tools=(), system="", messages=[], model=model, samples=1
+        )
         new_exchange.add(message)
         return new_exchange.generate().content[0].text
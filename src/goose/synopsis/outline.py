@@ -0,0 +1,61 @@
+import re
+
+from attrs import define
+from goose.toolkit.utils import get_language
+
+# patterns for the top-level symbols of each language, keyed by the name get_language gives it;
+# they only match unindented lines, so methods and nested definitions are left out
+OUTLINE_PATTERNS = {
+    "python": [re.compile(r"^(?P<kind>class|def|async def)\s+(?P<name>\w+)")],
+    "rust": [
+        re.compile(
+            r"^(?:pub(?:\([^)]*\))?\s+)?(?:(?:async|const|unsafe)\s+)*"
+            r"(?P<kind>fn|struct|enum|trait|mod|type|union|static|const)\s+(?P<name>\w+)"
+        ),
+        re.compile(r"^(?:unsafe\s+)?(?P<kind>impl)\b(?:<.*?>)?\s+(?P<name>[^{]+?)(?:\s+where\b.*|\s*\{.*)?$"),
+        re.compile(r"^(?P<kind>macro_rules!)\s*(?P<name>\w+)"),
+    ],
+    "go": [
+        re.compile(r"^(?P<kind>func)\s+(?:\([^)]*\)\s*)?(?P<name>\w+)"),
+        re.compile(r"^(?P<kind>type)\s+(?P<name>\w+)"),
+    ],
+    "javascript": [
+        re.compile(r"^(?:export\s+(?:default\s+)?)?(?:async\s+)?(?P<kind>function\*?|class)\s+(?P<name>[\w$]+)"),
+    ],
+    "typescript": [
+        re.compile(
+            r"^(?:export\s+(?:default\s+)?)?(?:declare\s+)?(?:abstract\s+)?(?:async\s+)?"
+            r"(?P<kind>function\*?|class|interface|type|enum)\s+(?P<name>[\w$]+)"
+        ),
+    ],
+}
+
+
+@define
+class Symbol:
+    line: int
+    kind: str
+    name: str
+
+    def __str__(self) -> str:
+        return f"{self.line}: {self.kind} {self.name}"
+
+
+def outline(path: str, content: str) -> list[Symbol]:
+    """The top-level symbols in content with their line numbers, counting from 1
+
+    Raises a ValueError if there is no outline for the language of path.
+    """
+    language = get_language(path)
+    if language not in OUTLINE_PATTERNS:
+        supported = ", ".join(OUTLINE_PATTERNS)
+        raise ValueError(f"There is no outline for {language or path}, only for {supported}. View the file instead.")
+
+    symbols = []
+    for number, line in enumerate(content.splitlines(), start=1):
+        for pattern in OUTLINE_PATTERNS[language]:
+            match = pattern.match(line)
+            if match:
+                symbols.append(Symbol(number, match["kind"], match["name"].strip()))
+                break
+    return symbols
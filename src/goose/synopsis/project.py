@@ -0,0 +1,28 @@
+from pathlib import Path
+from typing import Optional
+
+from attrs import define
+
+
+@define
+class ProjectType:
+    language: str
+    marker: str
+    tools: str
+
+
+# checked in order, so a repo with several markers is described by the first one found
+PROJECT_TYPES = (
+    ProjectType("Rust", "Cargo.toml", "`cargo build`, `cargo test` and `cargo clippy`"),
+    ProjectType("Go", "go.mod", "`go build`, `go test` and `go vet`"),
+    ProjectType("Python", "pyproject.toml", "the test runner and tools configured in pyproject.toml"),
+    ProjectType("JavaScript", "package.json", "the scripts defined in package.json, run through npm"),
+)
+
+
+def detect_project(cwd: str) -> Optional[ProjectType]:
+    """The type of project in cwd, recognized by the build file at its root"""
+    for project_type in PROJECT_TYPES:
+        if (Path(cwd) / project_type.marker).is_file():
+            return project_type
+    return None
@@ -0,0 +1,92 @@
+import re
+from fnmatch import fnmatch
+from pathlib import Path
+from typing import Optional
+
+from exchange import ToolError, ToolErrorCategory
+from goose.notifier import Notifier
+from goose.synopsis.file_history import FileHistory
+from goose.synopsis.list_dir import iter_files
+from goose.synopsis.system import system
+from goose.toolkit.utils import tool_header
+
+# refuse replacements that would touch more files than this, so a loose pattern can't rewrite the whole project
+MAX_FILES = 100
+
+
+class ProjectReplace:
+    def __init__(self, notifier: Notifier, file_history: FileHistory) -> None:
+        self.notifier = notifier
+        # shared with the text editor, so that undo_edit can restore each modified file
+        self._file_history = file_history
+
+    def _compile(self, pattern: str, regex: bool) -> re.Pattern:
+        if not regex:
+            return re.compile(re.escape(pattern))
+        try:
+            return re.compile(pattern, re.MULTILINE)
+        except re.error as e:
+            raise ToolError(
+                f"The pattern {pattern} is not a valid regular expression: {e}", ToolErrorCategory.INVALID_ARGS
+            )
+
+    def _find_files(self, glob: Optional[str]) -> list[Path]:
+        root = Path(system.cwd)
+        files = []
+        for patho in iter_files(root):
+            relative = patho.relative_to(root).as_posix()
+            if not system.is_permitted(patho):
+                continue
+            if glob is None or fnmatch(relative, glob) or fnmatch(patho.name, glob):
+                files.append(patho)
+        return files
+
+    def replace(self, pattern: str, replacement: str, glob: Optional[str] = None, regex: bool = False) -> str:
+        """Replace pattern across the files under the cwd, returning a summary of the replacements in each file."""
+        compiled = self._compile(pattern, regex)
+        # a literal replacement is used as is, a regex one can refer to groups such as \1
+        repl = replacement if regex else lambda _: replacement
+
+        changes = {}
+        for patho in self._find_files(glob):
+            try:
+                content = patho.read_text()
+            except (UnicodeDecodeError, OSError):
+                continue
+            updated, count = compiled.subn(repl, content)
+            if count:
+                changes[patho] = (content, updated, count)
+
+        if not changes:
+            return f"No matches for {pattern} in the files under {system.cwd}."
+        if len(changes) > MAX_FILES:
+            raise ValueError(
+                f"The pattern matches in {len(changes)} files, more than the {MAX_FILES} that can be changed at once. "
+                "Narrow it down with glob."
+            )
+
+        newly_active = []
+        for patho, (content, updated, _) in changes.items():
+            self._file_history.save(str(patho), content)
+            patho.write_text(updated)
+            if not system.is_active(str(patho)):
+                newly_active.append(patho)
+                try:
+                    system.remember_file(str(patho))
+                except ValueError:
+                    pass
+
+        root = Path(system.cwd)
+        lines = [f"{patho.relative_to(root).as_posix()}: {count}" for patho, (_, _, count) in changes.items()]
+        total = sum(count for _, _, count in changes.values())
+        self.notifier.log("")
+        self.notifier.log(tool_header("project_replace", f"{pattern} -> {replacement}"))
+        self.notifier.log("\n".join(lines))
+        self.notifier.log("")
+
+        summary = [f"Replaced {total} matches in {len(changes)} files:", *lines]
+        if newly_active:
+            names = ", ".join(patho.relative_to(root).as_posix() for patho in newly_active)
+            summary.append(f"Warning: {names} had not been viewed, they are now active so check the changes to them.")
+        summary.append("Use undo_edit on a file to restore it.")
+        return "\n".join(summary)
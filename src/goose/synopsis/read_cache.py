@@ -0,0 +1,69 @@
+import json
+from pathlib import Path
+from typing import Callable, Optional, TypeVar, Union
+
+Fingerprint = tuple[tuple[str, Optional[int], Optional[int]], ...]
+T = TypeVar("T")
+
+
+def fingerprint(paths: list[Path]) -> Fingerprint:
+    """The modified time and size of each path, which change whenever a file is written"""
+    stamps = []
+    for path in paths:
+        try:
+            stat = path.stat()
+        except FileNotFoundError:
+            stamps.append((str(path), None, None))
+            continue
+        stamps.append((str(path), stat.st_mtime_ns, stat.st_size))
+    return tuple(stamps)
+
+
+class ReadCache:
+    """Results of read-only tool calls, reused while the files they read are unchanged
+
+    Each result is stored under the tool name and its arguments, along with the fingerprint of the
+    paths it depends on. A later call with the same arguments reuses the result only if none of
+    those paths have been written since.
+    """
+
+    def __init__(self) -> None:
+        self._entries: dict[str, tuple[list[Path], Fingerprint, object]] = {}
+
+    @staticmethod
+    def _key(tool: str, args: dict[str, object]) -> str:
+        return json.dumps([tool, args], sort_keys=True, default=str)
+
+    def fetch(
+        self,
+        tool: str,
+        args: dict[str, object],
+        paths: Union[list[Path], Callable[[T], list[Path]]],
+        compute: Callable[[], T],
+    ) -> T:
+        """The cached result of the call, or the result of compute stored for next time
+
+        When the paths a call reads are only known from its result, such as the entries of a directory
+        listing, paths can be a function that takes the result and returns them.
+        """
+        key = self._key(tool, args)
+        entry = self._entries.get(key)
+        if entry is not None:
+            read, stamps, result = entry
+            if fingerprint(read) == stamps:
+                return result
+
+        if callable(paths):
+            result = compute()
+            read = paths(result)
+            current = fingerprint(read)
+        else:
+            read = paths
+            # taken before computing, so that a file written in the meantime makes the next call miss
+            current = fingerprint(read)
+            result = compute()
+        self._entries[key] = (read, current, result)
+        return result
+
+    def invalidate(self, tool: str, args: dict[str, object]) -> None:
+        self._entries.pop(self._key(tool, args), None)
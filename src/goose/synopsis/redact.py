@@ -0,0 +1,28 @@
+import re
+from typing import Iterable
+
+REDACTED = "[REDACTED]"
+
+# common formats of API keys and tokens, matched wherever they appear in the text
+DEFAULT_SECRET_PATTERNS = (
+    r"sk-[A-Za-z0-9_-]{20,}",  # OpenAI and Anthropic API keys
+    r"\b(?:AKIA|ASIA)[0-9A-Z]{16}\b",  # AWS access key ids
+    r"(?i)(?<=aws_secret_access_key=)[A-Za-z0-9/+]{40}",  # AWS secret access keys set in the environment
+    r"\bgh[pousr]_[A-Za-z0-9]{36,}",  # GitHub tokens
+    r"\bxox[abprs]-[A-Za-z0-9-]{10,}",  # Slack tokens
+    r"\beyJ[A-Za-z0-9_-]+\.eyJ[A-Za-z0-9_-]+\.[A-Za-z0-9_-]+",  # JWTs
+)
+
+
+def compile_patterns(patterns: Iterable[str]) -> list[re.Pattern]:
+    try:
+        return [re.compile(pattern) for pattern in patterns]
+    except re.error as e:
+        raise ValueError(f"Invalid secret pattern {e.pattern}: {e}")
+
+
+def redact(text: str, patterns: Iterable[re.Pattern]) -> str:
+    """Replace every match of the patterns in text with [REDACTED]"""
+    for pattern in patterns:
+        text = pattern.sub(REDACTED, text)
+    return text
@@ -1,16 +1,24 @@
 import json
+import re
 from tiktoken import get_encoding
 from exchange import Message
 import subprocess
 import os
+import time
 import atexit
 import platform
 from pathlib import Path
-from typing import Dict, Iterable, List, Set
+from typing import Dict, Iterable, List, Optional, Tuple
 
 from attrs import define, field
 from exchange.content import ToolUse
-from goose.toolkit.utils import get_language
+from goose.synopsis.redact import redact
+from goose.toolkit.utils import confine_to_sandbox, file_not_permitted_error, get_language, is_permitted_file
+
+
+DEFAULT_MAX_CONTEXT_FILES = 10
+DEFAULT_MAX_CONTEXT_BYTES = 200_000
+DEFAULT_MAX_ACTIVE_FILES = 20
 
 
 @define
@@ -37,7 +45,20 @@ class OperatingSystem:
     cwd: str = os.getcwd()
     platform: str = platform.system()
     env: Dict[str, str] = os.environ.copy()
-    _active_files: Set[str] = field(init=False, factory=set)
+    # caps on the active files whose content is sent every turn, the rest are only listed by path
+    max_context_files: int = DEFAULT_MAX_CONTEXT_FILES
+    max_context_bytes: int = DEFAULT_MAX_CONTEXT_BYTES
+    # cap on the active files themselves, the least recently viewed or edited are forgotten past it
+    max_active_files: int = DEFAULT_MAX_ACTIVE_FILES
+    # secrets matching these are redacted from command output and file content, empty unless redaction is enabled
+    secret_patterns: List[re.Pattern] = field(factory=list)
+    # when set, every path the tools use has to resolve to somewhere inside it
+    sandbox: Optional[Path] = None
+    # globs limiting the files the tools may read and write, a denied match always wins
+    allowed_files: List[str] = field(factory=list)
+    denied_files: List[str] = field(factory=list)
+    # each active file with when it was last touched, oldest first
+    _active_files: Dict[str, float] = field(init=False, factory=dict)
     _processes: Dict[int, subprocess.Popen] = field(init=False, factory=dict)
 
     def __attrs_post_init__(self) -> None:
@@ -85,16 +106,26 @@ class OperatingSystem:
     def to_patho(self, path: str) -> Path:
         """convert string to pathlib.Path object
 
-        This attempts to resolve the path against the cwd
+        This attempts to resolve the path against the cwd, and refuses paths that escape the sandbox
         """
         patho = Path(path)
-        if patho.is_absolute():
-            return patho
+        if not patho.is_absolute():
+            patho = (self.cwd / patho).resolve()
+        return confine_to_sandbox(patho, self.sandbox, path)
 
-        return (self.cwd / patho).resolve()
+    def to_file_patho(self, path: str) -> Path:
+        """Resolve path like to_patho, also refusing files the allowed and denied globs don't permit"""
+        patho = self.to_patho(path)
+        if not patho.is_dir() and not self.is_permitted(patho):
+            raise file_not_permitted_error(path)
+        return patho
+
+    def is_permitted(self, patho: Path) -> bool:
+        """Whether the allowed and denied globs let the tools use the file at patho"""
+        return is_permitted_file(patho, self.cwd, self.allowed_files, self.denied_files)
 
     def remember_file(self, path: str) -> None:
-        """Place a file into the active files"""
+        """Place a file into the active files, forgetting the least recently touched ones past max_active_files"""
         path = str(self.to_patho(path))
 
         # Do a size check on the file to ensure we don't overload the LLM context
@@ -108,11 +139,14 @@ class OperatingSystem:
         if len(content) > max_output_chars or len(encoder.encode(content)) > max_output_tokens:
             raise ValueError(f"The file at {path} is too large to read directly!")
 
-        self._active_files.add(path)
+        self._active_files.pop(path, None)
+        self._active_files[path] = time.time()
+        while len(self._active_files) > self.max_active_files:
+            del self._active_files[next(iter(self._active_files))]
 
     def forget_file(self, path: str) -> None:
         """Forget an existing active file"""
-        self._active_files.discard(str(self.to_patho(path)))
+        self._active_files.pop(str(self.to_patho(path)), None)
 
     def info(self) -> str:
         """Summarize the current operating system"""
@@ -132,10 +166,32 @@ class OperatingSystem:
     @property
     def active_files(self) -> Iterable["File"]:
         """Yield a File instance for each path in active files, with paths relative to cwd."""
-        self._active_files = set(f for f in self._active_files if Path(f).exists())
+        self._active_files = {f: touched for f, touched in self._active_files.items() if Path(f).exists()}
 
         for path in self._active_files:
-            yield File(path=self.to_relative(path), content=Path(path).read_text(), language=get_language(path))
+            content = self.redact(Path(path).read_text())
+            yield File(path=self.to_relative(path), content=content, language=get_language(path))
+
+    def redact(self, text: str) -> str:
+        """Replace the secrets in text that match the secret patterns"""
+        return redact(text, self.secret_patterns)
+
+    def context_files(self) -> Tuple[List["File"], List[str]]:
+        """Split the active files into those to include with their content and those to list by path only
+
+        The most recently modified files are included, up to max_context_files and max_context_bytes.
+        """
+        files = sorted(self.active_files, key=lambda file: self.to_patho(file.path).stat().st_mtime, reverse=True)
+        included, omitted = [], []
+        total_bytes = 0
+        for file in files:
+            size = len(file.content.encode())
+            if len(included) < self.max_context_files and total_bytes + size <= self.max_context_bytes:
+                included.append(file)
+                total_bytes += size
+            else:
+                omitted.append(file.path)
+        return included, omitted
 
     def restore(self, messages: List[Message]) -> None:
         """Restore the file content space from a previous sessions"""
@@ -1,22 +1,58 @@
 from typing import Optional, Literal
 from pathlib import Path
+from exchange import ToolError, ToolErrorCategory
 from rich.markdown import Markdown
-from rich.rule import Rule
 from goose.notifier import Notifier
+from goose.synopsis.file_history import FileHistory
+from goose.synopsis.outline import outline
 from goose.synopsis.system import system
-from goose.toolkit.utils import RULEPREFIX, RULESTYLE, get_language
+from goose.toolkit.utils import get_language, preview, tool_header
 
-TextEditorCommand = Literal["view", "create", "str_replace", "insert", "undo_edit"]
+TextEditorCommand = Literal["view", "view_outline", "create", "str_replace", "insert", "undo_edit"]
+# the commands that only read the file, so they can safely be run again after a timeout
+READ_ONLY_COMMANDS = ("view", "view_outline")
+
+
+def _missing_file_error(path: str, command: str) -> ToolError:
+    return ToolError(
+        f"The file {path} does not exist, so there is nothing to {command} yet. Use the create command to make it.",
+        ToolErrorCategory.NOT_FOUND,
+    )
+
+
+def _unchanged(path: str, reason: str) -> str:
+    return f"{path} was left unchanged, as {reason}. Nothing was written, so there is nothing to undo."
+
+
+def _normalize(lines: list[str]) -> str:
+    return "\n".join(line.strip() for line in lines)
+
+
+def find_fuzzy_matches(content: str, before: str) -> list[tuple[int, int]]:
+    """Find the blocks of lines in content equal to before once whitespace around each line is ignored
+
+    Returns the (start, end) line indices of every matching block. Any other difference, however
+    small, is not a match, so that code is never replaced for a block that only looks like it.
+    """
+    lines = content.splitlines()
+    target = _normalize(before.strip("\n").splitlines())
+    size = len(before.strip("\n").splitlines())
+    return [
+        (start, start + size)
+        for start in range(len(lines) - size + 1)
+        if _normalize(lines[start : start + size]) == target
+    ]
 
 
 class TextEditor:
-    def __init__(self, notifier: Notifier) -> None:
+    def __init__(self, notifier: Notifier, file_history: Optional[FileHistory] = None) -> None:
         self.notifier = notifier
-        self._file_history = {}
+        self._file_history = file_history if file_history is not None else FileHistory()
 
         # Command dispatch dictionary
         self.command_dispatch = {
             "view": self._view_file_or_directory,
+            "view_outline": self._view_outline,
             "create": self._create_file,
             "str_replace": self._replace_string,
             "insert": self._insert_string,
@@ -25,7 +61,7 @@ class TextEditor:
 
     def _write_file(self, path: str, content: str) -> str:
         """Write content to the file at path."""
-        patho = system.to_patho(path)
+        patho = system.to_file_patho(path)
 
         if patho.exists() and not system.is_active(path):
             raise ValueError(f"You must view {path} using read_file before you overwrite it")
@@ -35,65 +71,123 @@ class TextEditor:
         patho.write_text(content)
         system.remember_file(path)
 
-        language = get_language(path)
-        self._log_file_operation(path, content, language)
+        self._log_file_operation("create", path, content)
         return f"Successfully wrote to {path}"
 
-    def _patch_file(self, path: str, before: str, after: str) -> str:
+    def _patch_file(
+        self, path: str, before: str, after: str, fuzzy: bool = False, occurrence: Optional[int] = None
+    ) -> str:
         """Patch the file by replacing 'before' with 'after'."""
-        patho = system.to_patho(path)
+        patho = system.to_file_patho(path)
 
         if not patho.exists():
-            raise ValueError(f"You can't patch {path} - it does not exist yet")
+            raise _missing_file_error(path, "str_replace")
         if not system.is_active(path):
             raise ValueError(f"You must view {path} using read_file before you patch it")
 
         content = patho.read_text()
 
+        if fuzzy and before not in content:
+            return self._fuzzy_patch_file(path, content, before, after)
+        if occurrence is not None:
+            return self._patch_occurrence(path, content, before, after, occurrence)
         if content.count(before) != 1:
             raise ValueError("The 'before' content must appear exactly once in the file.")
+        if before == after:
+            return _unchanged(path, "'before' and 'after' are identical")
 
         self._save_file_history(patho)
         content = content.replace(before, after)
         system.remember_file(path)
         patho.write_text(content)
 
-        self._log_file_operation(path, f"{before} -> {after}", get_language(path))
+        self._log_file_operation("str_replace", path, f"{before} -> {after}")
         return "Successfully replaced before with after."
 
+    def _patch_occurrence(self, path: str, content: str, before: str, after: str, occurrence: int) -> str:
+        """Replace the nth occurrence of 'before', counting from 1 at the top of the file."""
+        count = content.count(before)
+        if occurrence < 1 or occurrence > count:
+            raise ValueError(
+                f"occurrence must be between 1 and {count}, the number of times 'before' appears in the file, "
+                f"got {occurrence}."
+            )
+        if before == after:
+            return _unchanged(path, "'before' and 'after' are identical")
+
+        # matches are found the way count finds them, so they never overlap
+        start = content.index(before)
+        for _ in range(occurrence - 1):
+            start = content.index(before, start + len(before))
+
+        patho = system.to_file_patho(path)
+        self._save_file_history(patho)
+        patho.write_text(content[:start] + after + content[start + len(before) :])
+        system.remember_file(path)
+
+        line = content.count("\n", 0, start) + 1
+        self._log_file_operation(f"str_replace occurrence {occurrence} of {count}", path, f"{before} -> {after}")
+        return f"Successfully replaced occurrence {occurrence} of {count} of before, at line {line}, with after."
+
+    def _fuzzy_patch_file(self, path: str, content: str, before: str, after: str) -> str:
+        """Replace the unique block of lines matching 'before' when whitespace around each line is ignored."""
+        matches = find_fuzzy_matches(content, before)
+        if not matches:
+            raise ValueError("The 'before' content was not found in the file, even ignoring whitespace.")
+        if len(matches) > 1:
+            locations = ", ".join(f"lines {start + 1}-{end}" for start, end in matches)
+            raise ValueError(f"The 'before' content matches several places ignoring whitespace: {locations}.")
+
+        start, end = matches[0]
+        lines = content.splitlines(keepends=True)
+        matched = "".join(lines[start:end])
+        replacement = after if not matched.endswith("\n") or after.endswith("\n") else after + "\n"
+        if replacement == matched:
+            return _unchanged(path, f"'after' is the same as the match at lines {start + 1}-{end}")
+
+        patho = system.to_file_patho(path)
+        self._save_file_history(patho)
+        patho.write_text("".join(lines[:start]) + replacement + "".join(lines[end:]))
+        system.remember_file(path)
+
+        self._log_file_operation("str_replace", path, f"{matched} -> {after}")
+        return (
+            f"No exact match for 'before', so the match ignoring whitespace at lines {start + 1}-{end} "
+            f"was replaced with after. The replaced content was:\n{matched}"
+        )
+
     def _save_file_history(self, patho: Path) -> None:
         """Save the current content of the file to history for undo functionality."""
         content = patho.read_text() if patho.exists() else ""
-        self._file_history[str(patho)] = content
+        self._file_history.save(str(patho), content)
 
     def _undo_edit(self, path: str, **kwargs: dict) -> str:
-        """Undo the last edit made to a file."""
-        patho = system.to_patho(path)
-
-        if not patho.exists() or str(patho) not in self._file_history:
-            raise ValueError(f"No edit history available to undo changes on {path}.")
+        """Undo the last edit made to a file, stepping further back each time it is called."""
+        patho = system.to_file_patho(path)
 
+        # the file may not exist if the last change was deleting it
         previous_content = self._file_history.pop(str(patho))
+        patho.parent.mkdir(parents=True, exist_ok=True)
         patho.write_text(previous_content)
         system.remember_file(path)
 
-        self._log_file_operation(path, "Undo edit", get_language(path))
+        self._log_file_operation("undo_edit", path)
         return f"Successfully undid the last edit on {path}"
 
     def _view_file_or_directory(self, path: str, view_range: Optional[list[int]] = None, **kwargs: dict) -> str:
         """View the content of a file or directory."""
-        patho = system.to_patho(path)
+        patho = system.to_file_patho(path)
 
         if patho.is_file():
             return self._view_file(patho, view_range)
         elif patho.is_dir():
             return self._view_directory(patho)
         else:
-            raise ValueError(f"The path {path} does not exist.")
+            raise ToolError(f"The path {path} does not exist.", ToolErrorCategory.NOT_FOUND)
 
     def _view_file(self, patho: Path, view_range: Optional[list[int]]) -> str:
         if not patho.exists():
-            raise ValueError(f"The file {patho} does not exist.")
+            raise ToolError(f"The file {patho} does not exist.", ToolErrorCategory.NOT_FOUND)
 
         with open(patho, "r") as f:
             content = f.readlines()
@@ -105,48 +199,98 @@ class TextEditor:
             content = content[start_line - 1 : (end_line if end_line != -1 else len(content))]
 
         system.remember_file(str(patho))
+        self._log_file_operation("view", str(patho))
         return f"Displayed content of {str(patho)}"
 
+    def _view_outline(self, path: str, **kwargs: dict) -> str:
+        """List the top-level symbols of a source file, without making it active."""
+        patho = system.to_file_patho(path)
+        if not patho.is_file():
+            raise ToolError(f"The file {path} does not exist.", ToolErrorCategory.NOT_FOUND)
+
+        try:
+            symbols = outline(path, patho.read_text())
+        except UnicodeDecodeError as e:
+            raise ToolError(f"{path} is a binary file, so it has no outline.", ToolErrorCategory.INVALID_ARGS) from e
+        except ValueError as e:
+            # the language has no outline
+            raise ToolError(str(e), ToolErrorCategory.INVALID_ARGS) from e
+        self._log_file_operation("view_outline", path)
+        if not symbols:
+            return f"No top-level symbols found in {path}"
+        listing = "\n".join(str(symbol) for symbol in symbols)
+        return f"Outline of {path}, use view with a view_range to read the symbols you need:\n{listing}"
+
     def _view_directory(self, patho: Path) -> str:
         files = [str(p) for p in patho.iterdir()]
         dir_content = "\n".join(files)
         return f"The contents of directory {str(patho)}:\n{dir_content}"
 
-    def _insert_string(self, path: str, insert_line: int, new_str: str, **kwargs: dict) -> str:
-        """Insert a string into the file after a specific line number."""
-        patho = system.to_patho(path)
-        if not patho.exists() or not system.is_active(path):
-            raise ValueError(f"You must view {path} before editing.")
-
-        self._save_file_history(patho)
-        with open(patho, "r") as f:
-            lines = f.readlines()
+    def _insert_string(
+        self, path: str, insert_line: int, new_str: str, create_if_missing: bool = False, **kwargs: dict
+    ) -> str:
+        """Insert a string into the file after a specific line number.
+
+        An insert_line of 0 inserts at the top of the file and one equal to the number of lines
+        appends at the end. Whether the file ends with a newline is kept as it was. With
+        create_if_missing, a file that doesn't exist yet is created as if it were empty.
+        Inserting an empty new_str into an existing file leaves it unchanged.
+        """
+        patho = system.to_file_patho(path)
+        if patho.exists():
+            if not system.is_active(path):
+                raise ValueError(f"You must view {path} before editing.")
+            if not new_str:
+                return _unchanged(path, "new_str is empty")
+            content = patho.read_text()
+        elif create_if_missing:
+            # a new file has nothing in it to view first
+            content = ""
+        else:
+            raise _missing_file_error(path, "insert into")
 
+        lines = content.splitlines()
         if insert_line < 0 or insert_line > len(lines):
-            raise ValueError("Insert line is out of range.")
+            raise ValueError(
+                f"insert_line must be between 0 (the top of the file) and {len(lines)} (the end of the file), "
+                f"got {insert_line}."
+            )
 
-        lines.insert(insert_line, new_str + "\n")
-        with open(patho, "w") as f:
-            f.writelines(lines)
+        self._save_file_history(patho)
+        lines[insert_line:insert_line] = new_str.splitlines() or [""]
+        updated = "\n".join(lines)
+        if not content or content.endswith("\n"):
+            updated += "\n"
+        patho.parent.mkdir(parents=True, exist_ok=True)
+        patho.write_text(updated)
 
         system.remember_file(path)
-        self._log_file_operation(path, new_str, get_language(path))
+        self._log_file_operation("insert", path, new_str)
         return f"Successfully inserted new_str into {path} after line {insert_line}"
 
     def _create_file(self, path: str, file_text: str, **kwargs: dict) -> str:
         """Create a new file with the given content."""
         return self._write_file(path, file_text)
 
-    def _replace_string(self, path: str, old_str: str, new_str: str, **kwargs: dict) -> str:
+    def _replace_string(
+        self,
+        path: str,
+        old_str: str,
+        new_str: str,
+        fuzzy: bool = False,
+        occurrence: Optional[int] = None,
+        **kwargs: dict,
+    ) -> str:
         """Replace a string in a file."""
-        return self._patch_file(path, old_str, new_str)
+        return self._patch_file(path, old_str, new_str, fuzzy=fuzzy, occurrence=occurrence)
 
-    def _log_file_operation(self, path: str, content: str, language: Optional[str]) -> None:
-        """Log the file operation in markdown format."""
-        md_content = f"```{language}\n{content}\n```" if language else f"```\n{content}\n```"
+    def _log_file_operation(self, command: str, path: str, content: Optional[str] = None) -> None:
+        """Log a header with the command and path, followed by a preview of the content in markdown format."""
         self.notifier.log("")
-        self.notifier.log(Rule(RULEPREFIX + path, style=RULESTYLE, align="left"))
-        self.notifier.log(Markdown(md_content))
+        self.notifier.log(tool_header(command, path))
+        if content is not None:
+            language = get_language(path) or ""
+            self.notifier.log(Markdown(f"```{language}\n{preview(content)}\n```"))
         self.notifier.log("")
 
     def run_command(self, command: TextEditorCommand, path: str, **kwargs: dict) -> str:
@@ -1,29 +1,119 @@
 # janky global state for now, think about it
-from collections import defaultdict
+import os
 import re
 import tempfile
-from typing import Dict, Optional
+from pathlib import Path
+from typing import Dict, Optional, Sequence
 
 from exchange import Message
 import httpx
+from rich.markdown import Markdown
 from goose.synopsis.bash import Bash
-from goose.synopsis.text_editor import TextEditor, TextEditorCommand
+from goose.synopsis.config_edit import ConfigEditCommand, ConfigEditor
+from goose.synopsis.diff import compare_files
+from goose.synopsis.file_history import DEFAULT_MAX_BYTES, DEFAULT_MAX_VERSIONS, FileHistory
+from goose.synopsis.file_ops import FileOperations, FileOperationsCommand
+from goose.synopsis.list_dir import MAX_DEPTH, MAX_ENTRIES, list_directory
+from goose.synopsis.text_editor import READ_ONLY_COMMANDS, TextEditor, TextEditorCommand
+from goose.synopsis.project import detect_project
+from goose.synopsis.project_replace import ProjectReplace
+from goose.synopsis.read_cache import ReadCache
+from goose.synopsis.redact import DEFAULT_SECRET_PATTERNS, compile_patterns
 from goose.synopsis.process_manager import ProcessManager, ProcessManagerCommand
-from goose.toolkit.base import Toolkit, tool
+from goose.synopsis.system import (
+    DEFAULT_MAX_ACTIVE_FILES,
+    DEFAULT_MAX_CONTEXT_BYTES,
+    DEFAULT_MAX_CONTEXT_FILES,
+    system,
+)
+from goose.toolkit.base import Toolkit, read_only, read_only_when, tool
+from goose.toolkit.utils import split_globs, tool_header
+from goose.utils.goosehints import fetch_goosehints
+
+REDACT_SECRETS_ENV = "GOOSE_REDACT_SECRETS"
+CACHE_READS_ENV = "GOOSE_CACHE_READS"
+SANDBOX_ENV = "GOOSE_SANDBOX"
+ALLOWED_FILES_ENV = "GOOSE_ALLOWED_FILES"
+DENIED_FILES_ENV = "GOOSE_DENIED_FILES"
 
 
 class SynopsisDeveloper(Toolkit):
-    """Provides shell and file operation tools using OperatingSystem."""
+    """Provides shell and file operation tools using OperatingSystem.
+
+    Secrets such as API keys can be redacted from command output and file content before they reach
+    the model, with the redact_secrets argument or the GOOSE_REDACT_SECRETS environment variable.
+    The secret_patterns argument replaces the regular expressions that are redacted.
+
+    Viewing, outlining, listing and comparing the same files again can reuse the earlier result while
+    the files are unchanged, with the cache_reads argument or the GOOSE_CACHE_READS environment variable.
+
+    Earlier versions of changed files are kept so that undo_edit can step back through them, up to
+    max_file_history versions per file and max_file_history_bytes across all files.
 
-    def __init__(self, *args: object, **kwargs: Dict[str, object]) -> None:
+    At most max_active_files files are active at once, past that the least recently viewed or edited
+    are forgotten and have to be viewed again. Of those, the content of the max_context_files most
+    recently modified is sent every turn, up to max_context_bytes in total, and the rest are only
+    listed by path.
+
+    A sandbox directory can be set with the sandbox argument or the GOOSE_SANDBOX environment
+    variable. Commands then start in it and any path that escapes it, through `..` or a symlink,
+    is refused by every tool.
+
+    The files the tools may read and write can also be limited by glob, such as `*.py` or `src/*`,
+    with the allowed_files and denied_files arguments or the comma separated GOOSE_ALLOWED_FILES
+    and GOOSE_DENIED_FILES environment variables. A denied match always wins, and when allowed
+    globs are set a file has to match one of them.
+    """
+
+    def __init__(
+        self,
+        *args: object,
+        redact_secrets: bool = False,
+        secret_patterns: Optional[Sequence[str]] = None,
+        cache_reads: bool = False,
+        max_file_history: int = DEFAULT_MAX_VERSIONS,
+        max_file_history_bytes: int = DEFAULT_MAX_BYTES,
+        max_active_files: int = DEFAULT_MAX_ACTIVE_FILES,
+        max_context_files: int = DEFAULT_MAX_CONTEXT_FILES,
+        max_context_bytes: int = DEFAULT_MAX_CONTEXT_BYTES,
+        sandbox: Optional[str] = None,
+        allowed_files: Optional[Sequence[str]] = None,
+        denied_files: Optional[Sequence[str]] = None,
+        **kwargs: Dict[str, object],
+    ) -> None:
         super().__init__(*args, **kwargs)
-        self._file_history = defaultdict(list)
+        system.max_active_files = max_active_files
+        system.max_context_files = max_context_files
+        system.max_context_bytes = max_context_bytes
+        sandbox = sandbox or os.environ.get(SANDBOX_ENV)
+        system.sandbox = Path(sandbox).expanduser().resolve() if sandbox else None
+        if system.sandbox is not None:
+            system.cwd = str(system.sandbox)
+        system.allowed_files = list(allowed_files or split_globs(os.environ.get(ALLOWED_FILES_ENV)))
+        system.denied_files = list(denied_files or split_globs(os.environ.get(DENIED_FILES_ENV)))
+        # kept across calls, so that edits and deletes can be undone later in the session
+        self._file_history = FileHistory(max_file_history, max_file_history_bytes)
+        if redact_secrets or os.environ.get(REDACT_SECRETS_ENV):
+            system.secret_patterns = compile_patterns(secret_patterns or DEFAULT_SECRET_PATTERNS)
+        else:
+            system.secret_patterns = []
+        self._read_cache = ReadCache() if cache_reads or os.environ.get(CACHE_READS_ENV) else None
 
     def system(self) -> str:
-        """Retrieve system configuration details for developer"""
-        system_prompt = Message.load("developer.md").text
+        """Retrieve system configuration details for developer, tailored to the project in the cwd"""
+        system_prompt = Message.load("developer.md", project=detect_project(system.cwd)).text
         return system_prompt
 
+    def status(self) -> dict[str, str]:
+        """Report the system info, hints and active files that synopsis injects into the context"""
+        resources = {"system": self.system(), "info": system.info(), "hints": fetch_goosehints()}
+        included, omitted = system.context_files()
+        for file in included:
+            resources[file.path] = file.context
+        if omitted:
+            resources["omitted files"] = "\n".join(omitted)
+        return resources
+
     @tool
     def bash(
         self,
@@ -68,6 +158,7 @@ class SynopsisDeveloper(Toolkit):
         return "\n".join(outputs)
 
     @tool
+    @read_only_when(lambda parameters: parameters.get("command") in READ_ONLY_COMMANDS)
     def text_editor(
         self,
         command: TextEditorCommand,
@@ -77,47 +168,231 @@ class SynopsisDeveloper(Toolkit):
         new_str: Optional[str] = None,
         old_str: Optional[str] = None,
         view_range: Optional[list[int]] = None,
+        fuzzy: bool = False,
+        occurrence: Optional[int] = None,
+        create_if_missing: bool = False,
     ) -> str:
         """
         Perform text editing operations on files.
 
         The `command` parameter specifies the operation to perform. Allowed options are:
         - `view`: View the content of a file or directory.
+        - `view_outline`: List the top-level symbols of a source file with their line numbers,
+          to `view` only the parts of a large file you need.
         - `create`: Create a new file with the given content.
         - `str_replace`: Replace a string in a file with a new string.
         - `insert`: Insert a string into a file after a specific line number.
-        - `undo_edit`: Undo the last edit made to a file.
+        - `undo_edit`: Undo the last edit made to a file, call it again to undo the edit before that.
 
         Args:
             command (str): The commands to run.
-                Allowed options are: `view`, `create`, `str_replace`, `insert`, `undo_edit`.
+                Allowed options are: `view`, `view_outline`, `create`, `str_replace`, `insert`, `undo_edit`.
             path (str): Absolute path (or relative path against cwd) to file or directory,
                 e.g. `/repo/file.py` or `/repo` or `curr_dir_file.py`.
             file_text (str, optional): Required parameter of `create` command, with the content
                 of the file to be created.
             insert_line (int, optional): Required parameter of `insert` command.
-                The `new_str` will be inserted AFTER the line `insert_line` of `path`.
+                The `new_str` will be inserted AFTER the line `insert_line` of `path`, so `0` inserts at the
+                top of the file and the number of lines in the file appends at the end.
             new_str (str, optional): Optional parameter of `str_replace` command
                 containing the new string (if not given, no string will be added).
                 Required parameter of `insert` command containing the string to insert.
             old_str (str, optional): Required parameter of `str_replace` command containing the
-                string in `path` to replace.
+                string in `path` to replace. An edit that would not change the file is reported and skipped.
             view_range (list, optional): Optional parameter of `view` command when `path` points to a file.
                 If none is given, the full file is shown. If provided, the file will be shown in the indicated line
                 number range, e.g. [11, 12] will show lines 11 and 12. Indexing at 1 to start.
                 Setting `[start_line, -1]` shows all lines from `start_line` to the end of the file.
+            fuzzy (bool, optional): Optional parameter of `str_replace` command. When `old_str` is not found
+                exactly, replace the single block that only differs from it in whitespace around lines.
+                The response reports what was replaced, check it before continuing.
+            occurrence (int, optional): Optional parameter of `str_replace` command. When `old_str` appears
+                several times, replace only this occurrence of it, counting from 1 at the top of the file.
+            create_if_missing (bool, optional): Optional parameter of `insert` command. When `path` does not
+                exist, create it with `new_str` as its content instead of failing.
         """
-        text_editor_instance = TextEditor(notifier=self.notifier)
-        return text_editor_instance.run_command(
+        text_editor_instance = TextEditor(notifier=self.notifier, file_history=self._file_history)
+
+        def run() -> str:
+            return text_editor_instance.run_command(
+                command=command,
+                path=path,
+                file_text=file_text,
+                insert_line=insert_line,
+                new_str=new_str,
+                old_str=old_str,
+                view_range=view_range,
+                fuzzy=fuzzy,
+                occurrence=occurrence,
+                create_if_missing=create_if_missing,
+            )
+
+        if command not in READ_ONLY_COMMANDS or self._read_cache is None:
+            return run()
+
+        patho = system.to_file_patho(path)
+        args = {"command": command, "path": str(patho), "view_range": view_range}
+        # viewing a file makes it active, so a cached view only stands in for it while the file still is
+        if command == "view" and patho.is_file() and not system.is_active(str(patho)):
+            self._read_cache.invalidate("text_editor", args)
+        return self._read_cache.fetch("text_editor", args, [patho], run)
+
+    @tool
+    def file_operations(
+        self,
+        command: FileOperationsCommand,
+        path: str,
+        destination: Optional[str] = None,
+        allow_outside: bool = False,
+    ) -> str:
+        """
+        Move, copy or delete files, instead of using mv, cp or rm in the shell.
+
+        The `command` parameter specifies the operation to perform. Allowed options are:
+        - `move`: Move or rename a file or directory to `destination`.
+        - `copy`: Copy a file or directory to `destination`.
+        - `delete`: Delete a file. Use the `undo_edit` command of the text editor on the same path to restore it,
+          unless it is a binary file.
+
+        Paths outside of the working directory are refused unless `allow_outside` is set, and
+        an existing destination is never overwritten.
+
+        Args:
+            command (str): The command to run.
+                Allowed options are: `move`, `copy`, `delete`.
+            path (str): Absolute path (or relative path against cwd) to the file or directory to operate on.
+            destination (str, optional): Required parameter of `move` and `copy` commands, the path to
+                move or copy to.
+            allow_outside (bool, optional): Allow operating on paths outside of the working directory.
+                Only set this when the user asked for it.
+        """
+        file_operations_instance = FileOperations(notifier=self.notifier, file_history=self._file_history)
+        return file_operations_instance.run_command(
             command=command,
             path=path,
-            file_text=file_text,
-            insert_line=insert_line,
-            new_str=new_str,
-            old_str=old_str,
-            view_range=view_range,
+            destination=destination,
+            allow_outside=allow_outside,
         )
 
+    @tool
+    def config_edit(self, path: str, command: ConfigEditCommand, key: str, value: Optional[str] = None) -> str:
+        """
+        Set or delete a key in a JSON, YAML or TOML file, instead of editing its text with the text editor.
+
+        The file is parsed according to its extension and written back keeping its format where possible,
+        such as the comments in YAML and TOML. Use the `undo_edit` command of the text editor to restore it.
+
+        Args:
+            path (str): Absolute path (or relative path against cwd) to the .json, .yaml, .yml or .toml file.
+            command (str): `set` to set the value of the key, creating missing tables along the way,
+                or `delete` to remove the key.
+            key (str): The dotted path to the key, such as `dependencies.serde` or `scripts.test`.
+                Numbers select an item of a list, such as `members.0`.
+            value (str, optional): Required parameter of `set`, the value as JSON, such as "1.0.2", 3, true
+                or {"version": "1.0"}. Text that isn't valid JSON is set as a string.
+        """
+        config_editor_instance = ConfigEditor(notifier=self.notifier, file_history=self._file_history)
+        return config_editor_instance.edit(path, command, key, value=value)
+
+    @tool
+    def project_replace(self, pattern: str, replacement: str, glob: Optional[str] = None, regex: bool = False) -> str:
+        """
+        Replace a pattern in every file under the working directory, instead of editing each file in turn.
+
+        Files ignored by .gitignore or .gooseignore are skipped. Modified files become active, and each one
+        can be restored with the `undo_edit` command of the text editor.
+
+        Args:
+            pattern (str): The text to replace, or a regular expression when `regex` is set.
+            replacement (str): The text to replace it with. With `regex` it can refer to groups, such as \\1.
+            glob (str, optional): Only replace in files whose relative path or name matches this glob, such as *.py.
+            regex (bool, optional): Treat `pattern` as a regular expression instead of literal text.
+        """
+        project_replace_instance = ProjectReplace(notifier=self.notifier, file_history=self._file_history)
+        return project_replace_instance.replace(pattern, replacement, glob=glob, regex=regex)
+
+    @tool
+    @read_only
+    def list_dir(self, path: str, recursive: bool = False, max_depth: int = MAX_DEPTH) -> str:
+        """
+        List the contents of a directory with the type, size in bytes and modified time of each entry.
+
+        Entries ignored by .gitignore or .gooseignore are skipped, and at most 200 entries are returned.
+        Prefer this over `ls` or `find` in the shell to explore the layout of a project.
+
+        Args:
+            path (str): Absolute path (or relative path against cwd) to the directory to list.
+            recursive (bool, optional): Also list the contents of subdirectories.
+            max_depth (int, optional): How many levels deep to list when recursive, at most 5.
+        """
+        if self._read_cache is None:
+            entries, truncated = list_directory(path, recursive=recursive, max_depth=max_depth)
+        else:
+            root = system.to_patho(path)
+            args = {"path": str(root), "recursive": recursive, "max_depth": max_depth}
+            # the listing shows the size and modified time of each entry, so it depends on all of them
+            entries, truncated = self._read_cache.fetch(
+                "list_dir",
+                args,
+                lambda listing: [root] + [root / entry.path for entry in listing[0]],
+                lambda: list_directory(path, recursive=recursive, max_depth=max_depth),
+            )
+        if not entries:
+            return f"{path} is empty."
+
+        lines = [str(entry) for entry in entries]
+        if truncated:
+            lines.append(f"... listing stopped after {MAX_ENTRIES} entries, list a subdirectory to see more")
+        return "\n".join(lines)
+
+    @tool
+    @read_only
+    def diff_files(self, path_a: str, path_b: str) -> str:
+        """
+        Compare two files, such as a file and its backup, and show the user a unified diff of them.
+
+        You are told how many lines were added and removed, view the files if you need the details.
+        Prefer this over running `diff` in the shell.
+
+        Args:
+            path_a (str): Absolute path (or relative path against cwd) to the original file.
+            path_b (str): Absolute path (or relative path against cwd) to the file to compare it with.
+        """
+        if self._read_cache is None:
+            file_diff = compare_files(path_a, path_b)
+        else:
+            patho_a, patho_b = system.to_file_patho(path_a), system.to_file_patho(path_b)
+            args = {"path_a": str(patho_a), "path_b": str(patho_b)}
+            file_diff = self._read_cache.fetch(
+                "diff_files", args, [patho_a, patho_b], lambda: compare_files(path_a, path_b)
+            )
+        if not file_diff.diff:
+            return f"{path_a} and {path_b} are identical."
+
+        self.notifier.log("")
+        self.notifier.log(tool_header("diff", f"{path_a} -> {path_b}"))
+        self.notifier.log(Markdown(f"```diff\n{file_diff.diff}```"))
+        self.notifier.log("")
+        return f"{path_a} and {path_b} differ, {file_diff.summary}. The full diff was shown to the user."
+
+    @tool
+    def forget_file(self, path: str) -> str:
+        """
+        Stop showing a file among the active files, once you no longer need its content in every turn.
+
+        View the file again to make it active again.
+
+        Args:
+            path (str): Absolute path (or relative path against cwd) to the active file to forget.
+        """
+        if not system.is_active(path):
+            return f"{path} is not an active file."
+        system.forget_file(path)
+        self.notifier.log("")
+        self.notifier.log(tool_header("forget_file", path))
+        self.notifier.log("")
+        return f"{path} is no longer active."
+
     @tool
     def process_manager(
         self,
@@ -1,6 +1,6 @@
 import inspect
 from abc import ABC
-from typing import Mapping, Optional, TypeVar
+from typing import Callable, Mapping, Optional, TypeVar
 
 from attrs import define, field
 from exchange import Tool
@@ -16,6 +16,22 @@ def tool(func: F) -> F:
     return func
 
 
+def read_only(func: F) -> F:
+    """Mark a tool as only reading state, so that it can safely be called again after a timeout"""
+    func._is_read_only = True
+    return func
+
+
+def read_only_when(predicate: Callable[[dict[str, any]], bool]) -> Callable[[F], F]:
+    """Mark a tool as only reading state for the calls whose parameters match predicate, such as a view command"""
+
+    def mark(func: F) -> F:
+        func._is_read_only = predicate
+        return func
+
+    return mark
+
+
 @define
 class Requirements:
     """A collection of requirements for advanced toolkits
@@ -55,6 +71,10 @@ class Toolkit(ABC):
         """Get the addition to the system prompt for this toolkit."""
         return ""
 
+    def status(self) -> dict[str, str]:
+        """Get the resources this toolkit currently contributes to the context, keyed by name."""
+        return {"system": self.system()}
+
     def tools(self) -> tuple[Tool, ...]:
         """Get the tools for this toolkit
 
@@ -62,4 +82,8 @@ class Toolkit(ABC):
         with @tool.
         """
         candidates = inspect.getmembers(self, predicate=inspect.ismethod)
-        return (Tool.from_function(candidate) for _, candidate in candidates if getattr(candidate, "_is_tool", None))
+        return (
+            Tool.from_function(candidate, read_only=getattr(candidate, "_is_read_only", False))
+            for _, candidate in candidates
+            if getattr(candidate, "_is_tool", None)
+        )
@@ -4,10 +4,19 @@ import tempfile
 import httpx
 
 from pathlib import Path
+from typing import Optional, Sequence
 
 from exchange import Message
-from goose.toolkit.base import Toolkit, tool
-from goose.toolkit.utils import get_language, RULEPREFIX, RULESTYLE
+from goose.toolkit.base import Toolkit, read_only, tool
+from goose.toolkit.utils import (
+    RULEPREFIX,
+    RULESTYLE,
+    confine_to_sandbox,
+    file_not_permitted_error,
+    get_language,
+    is_permitted_file,
+    split_globs,
+)
 from goose.utils.goosehints import fetch_goosehints
 from goose.utils.shell import shell
 from rich.markdown import Markdown
@@ -20,12 +29,48 @@ class Developer(Toolkit):
 
     The tools include plan management, a general purpose shell execution tool, and file operations.
     We also include some default shell strategies in the prompt, such as using ripgrep
+
+    A sandbox directory can be set with the sandbox argument or the GOOSE_SANDBOX environment
+    variable. Relative paths are then resolved against it, any path that escapes it (through `..`
+    or a symlink) is refused, and shell commands start in it.
+
+    The files the file tools may read and write can also be limited by glob, such as `*.py` or
+    `src/*`, with the allowed_files and denied_files arguments or the comma separated
+    GOOSE_ALLOWED_FILES and GOOSE_DENIED_FILES environment variables. A denied match always wins,
+    and when allowed globs are set a file has to match one of them.
     """
 
-    def __init__(self, *args: object, **kwargs: dict[str, object]) -> None:
+    def __init__(
+        self,
+        *args: object,
+        sandbox: Optional[str] = None,
+        allowed_files: Optional[Sequence[str]] = None,
+        denied_files: Optional[Sequence[str]] = None,
+        **kwargs: dict[str, object],
+    ) -> None:
         super().__init__(*args, **kwargs)
         self.timestamps: dict[str, float] = {}
-        self.cwd = os.getcwd()
+        sandbox = sandbox or os.environ.get("GOOSE_SANDBOX")
+        self.sandbox = Path(sandbox).expanduser().resolve() if sandbox else None
+        self.cwd = str(self.sandbox) if self.sandbox else os.getcwd()
+        self.allowed_files = list(allowed_files or split_globs(os.environ.get("GOOSE_ALLOWED_FILES")))
+        self.denied_files = list(denied_files or split_globs(os.environ.get("GOOSE_DENIED_FILES")))
+
+    def resolve_path(self, path: str) -> Path:
+        """Resolve path for a file tool, refusing paths that escape the sandbox or aren't permitted"""
+        resolved = self._resolve_in_sandbox(path)
+        if not self.is_permitted(resolved):
+            raise file_not_permitted_error(path)
+        return resolved
+
+    def is_permitted(self, path: Path) -> bool:
+        """Whether the allowed and denied globs let the file tools use path"""
+        return is_permitted_file(path, self.cwd, self.allowed_files, self.denied_files)
+
+    def _resolve_in_sandbox(self, path: str) -> Path:
+        if self.sandbox is None:
+            return Path(path)
+        return confine_to_sandbox(self.sandbox / Path(path).expanduser(), self.sandbox, path)
 
     def system(self) -> str:
         """Retrieve system configuration details for developer"""
@@ -119,7 +164,7 @@ class Developer(Toolkit):
             after (str): The content it will be replaced with
         """
         self.notifier.status(f"editing {path}")
-        _path = Path(path)
+        _path = self.resolve_path(path)
         language = get_language(path)
 
         content = _path.read_text()
@@ -146,6 +191,7 @@ class Developer(Toolkit):
         return "Succesfully replaced before with after."
 
     @tool
+    @read_only
     def read_file(self, path: str) -> str:
         """Read the content of the file at path
 
@@ -153,10 +199,11 @@ class Developer(Toolkit):
             path (str): The destination file path, in the format "path/to/file.txt"
         """
         language = get_language(path)
-        content = Path(path).expanduser().read_text()
+        _path = self.resolve_path(path).expanduser()
+        content = _path.read_text()
         self.notifier.log(Markdown(f"```\ncat {path}\n```"))
         # Record the last read timestamp
-        self.timestamps[path] = os.path.getmtime(path)
+        self.timestamps[path] = os.path.getmtime(_path)
         return f"```{language}\n{content}\n```"
 
     @tool
@@ -175,7 +222,7 @@ class Developer(Toolkit):
         # Log the command being executed in a visually structured format (Markdown).
         self.notifier.log(Rule(RULEPREFIX + "shell", style=RULESTYLE, align="left"))
         self.notifier.log(Markdown(f"```bash\n{command}\n```"))
-        return shell(command, self.notifier, self.exchange_view)
+        return shell(command, self.notifier, self.exchange_view, cwd=self.cwd if self.sandbox else None)
 
     @tool
     def write_file(self, path: str, content: str) -> str:
@@ -188,6 +235,7 @@ class Developer(Toolkit):
             content (str): The raw file content.
         """  # noqa: E501
         self.notifier.status("writing file")
+        _path = self.resolve_path(path)
         # Get the programming language for syntax highlighting in logs
         language = get_language(path)
         md = f"```{language}\n{content}\n```"
@@ -198,10 +246,9 @@ class Developer(Toolkit):
         self.notifier.log(Rule(RULEPREFIX + path, style=RULESTYLE, align="left"))
         self.notifier.log(Markdown(md))
 
-        _path = Path(path)
         if path in self.timestamps:
             last_read_timestamp = self.timestamps.get(path, 0.0)
-            current_timestamp = os.path.getmtime(path)
+            current_timestamp = os.path.getmtime(_path)
             if current_timestamp > last_read_timestamp:
                 raise RuntimeError(
                     f"File '{path}' has been modified since it was last read."
@@ -215,7 +262,7 @@ class Developer(Toolkit):
         _path.write_text(content)
 
         # Update the last read timestamp after writing to the file
-        self.timestamps[path] = os.path.getmtime(path)
+        self.timestamps[path] = os.path.getmtime(_path)
 
         # Return a success message
         return f"Successfully wrote to {path}"
@@ -1,10 +1,48 @@
+from datetime import datetime, timedelta
 from pathlib import Path
 from typing import Optional, List, Dict
 import re
 
+from exchange import ToolError, ToolErrorCategory
 from jinja2 import Environment, FileSystemLoader
 
 from goose.toolkit.base import Toolkit, tool
+from goose.toolkit.utils import tool_header
+
+# memories remembered since this change start with a line such as "@2024-05-01T09:30:00"
+TIMESTAMP_PREFIX = "@"
+RELATIVE_SINCE = re.compile(r"^(\d+)([mhdw])$")
+RELATIVE_UNITS = {"m": "minutes", "h": "hours", "d": "days", "w": "weeks"}
+
+
+def now() -> datetime:
+    return datetime.now().replace(microsecond=0)
+
+
+def parse_since(since: str) -> datetime:
+    """Parse an ISO date or time, or a time relative to now such as 30m, 12h, 7d or 2w"""
+    match = RELATIVE_SINCE.match(since.strip())
+    if match:
+        amount, unit = match.groups()
+        return now() - timedelta(**{RELATIVE_UNITS[unit]: int(amount)})
+    try:
+        parsed = datetime.fromisoformat(since.strip())
+    except ValueError:
+        raise ToolError(
+            f"Could not understand since '{since}', use an ISO date such as 2024-05-01 or a relative time such as 7d",
+            ToolErrorCategory.INVALID_ARGS,
+        )
+    # timestamps are stored in local time
+    return parsed.astimezone().replace(tzinfo=None) if parsed.tzinfo else parsed
+
+
+def _parse_timestamp(line: str) -> Optional[datetime]:
+    if not line.startswith(TIMESTAMP_PREFIX):
+        return None
+    try:
+        return datetime.fromisoformat(line[len(TIMESTAMP_PREFIX) :].strip())
+    except ValueError:
+        return None
 
 
 class Memory(Toolkit):
@@ -59,6 +97,8 @@ class Memory(Toolkit):
 
     def _get_memory_file(self, category: str, scope: str = "global") -> Path:
         """Get the path to a memory category file"""
+        if scope not in ("global", "local"):
+            raise ToolError(f"Unknown scope '{scope}', it must be 'global' or 'local'", ToolErrorCategory.INVALID_ARGS)
         base_dir = self.global_memory_dir if scope == "global" else self.local_memory_dir
         return base_dir / f"{category}.txt"
 
@@ -77,12 +117,16 @@ class Memory(Toolkit):
                 memory_lines = block.strip().split("\n")
                 tags = []
                 text = []
+                # memories remembered before timestamps were recorded have none
+                timestamp = None
                 for line in memory_lines:
                     if line.startswith("#"):
                         tags.extend(tag.strip() for tag in line[1:].split())
+                    elif timestamp is None and not text and (parsed := _parse_timestamp(line)):
+                        timestamp = parsed
                     else:
                         text.append(line)
-                memories.append({"text": "\n".join(text).strip(), "tags": tags})
+                memories.append({"text": "\n".join(text).strip(), "tags": tags, "timestamp": timestamp})
         return memories
 
     def _save_memories(self, memories: List[Dict[str, str]], category: str, scope: str = "global") -> None:
@@ -90,12 +134,20 @@ class Memory(Toolkit):
         memory_file = self._get_memory_file(category, scope)
         content = []
         for memory in memories:
+            if memory.get("timestamp"):
+                content.append(f"{TIMESTAMP_PREFIX}{memory['timestamp'].isoformat()}")
             if memory["tags"]:
                 content.append(f"#{' '.join(memory['tags'])}")
             content.append(memory["text"])
             content.append("")  # Empty line between memories
         memory_file.write_text("\n".join(content))
 
+    def _log_memory_operation(self, name: str, category: Optional[str], scope: Optional[str]) -> None:
+        """Log a header naming the memory tool, the category and the scope it works on"""
+        detail = f"{category or 'all categories'} ({scope or 'all scopes'})"
+        self.notifier.log("")
+        self.notifier.log(tool_header(name, detail))
+
     @tool
     def remember(self, text: str, category: str, tags: Optional[str] = None, scope: str = "global") -> str:
         """Save a memory with optional tags in a specific category
@@ -114,11 +166,13 @@ class Memory(Toolkit):
         if tags:
             tag_list = [tag.strip().lstrip("#") for tag in tags.split() if tag.strip()]
 
+        self._log_memory_operation("remember", category, scope)
+
         # Load existing memories
         memories = self._load_memories(category, scope)
 
         # Add new memory
-        memories.append({"text": text, "tags": tag_list})
+        memories.append({"text": text, "tags": tag_list, "timestamp": now()})
 
         # Save updated memories
         self._save_memories(memories, category, scope)
@@ -127,14 +181,20 @@ class Memory(Toolkit):
         return f"I'll remember that in the {category} category{tag_msg} ({scope} scope)"
 
     @tool
-    def search(self, query: str, category: Optional[str] = None, scope: Optional[str] = None) -> str:
+    def search(
+        self, query: str, category: Optional[str] = None, scope: Optional[str] = None, since: Optional[str] = None
+    ) -> str:
         """Search through memories by text and tags
 
         Args:
             query (str): Text to search for in memories and tags
             category (str, optional): Specific category to search in
             scope (str, optional): Which scope to search - 'global', 'local', or None (both)
+            since (str, optional): Only return memories remembered since then, as an ISO date such as 2024-05-01
+                or a time relative to now such as 30m, 12h, 7d or 2w. Older memories without a time are left out.
         """
+        self._log_memory_operation(f"search '{query}'", category, scope)
+        cutoff = parse_since(since) if since else None
         results = []
         scopes = ["global", "local"] if scope is None else [scope]
 
@@ -153,6 +213,8 @@ class Memory(Toolkit):
             for cat in categories:
                 memories = self._load_memories(cat, current_scope)
                 for memory in memories:
+                    if cutoff and (memory["timestamp"] is None or memory["timestamp"] < cutoff):
+                        continue
                     # Search in text and tags
                     if query.lower() in memory["text"].lower() or any(
                         query.lower() in tag.lower() for tag in memory["tags"]
@@ -172,6 +234,7 @@ class Memory(Toolkit):
         Args:
             scope (str, optional): Which scope to list - 'global', 'local', or None (both)
         """
+        self._log_memory_operation("list_categories", None, scope)
         categories = []
 
         if scope in (None, "local") and self.local_memory_dir.exists():
@@ -199,6 +262,7 @@ class Memory(Toolkit):
             category (str): The category to remove
             scope (str): Which scope to remove from - 'global' or 'local'
         """
+        self._log_memory_operation("forget_category", category, scope)
         memory_file = self._get_memory_file(category, scope)
         if not memory_file.exists():
             return f"No {category} category found in {scope} scope"
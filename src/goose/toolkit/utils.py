@@ -1,14 +1,87 @@
+from fnmatch import fnmatchcase
 from pathlib import Path
-from typing import Optional
+from typing import Optional, Sequence
 
+from exchange import ToolError, ToolErrorCategory
 from pygments.lexers import get_lexer_for_filename
 from pygments.util import ClassNotFound
 
-from jinja2 import Environment, FileSystemLoader
+from jinja2 import Environment, FileSystemLoader, StrictUndefined
+from rich.markup import escape
+from rich.rule import Rule
 
 
 RULESTYLE = "bold"
 RULEPREFIX = f"[{RULESTYLE}]───[/] "
+PREVIEW_LINES = 10
+
+
+def tool_header(title: str, detail: Optional[str] = None) -> Rule:
+    """The rule logged above a tool call, e.g. `str_replace | src/main.py`
+
+    Args:
+        title (str): What the tool is doing, usually its name or command
+        detail (str, optional): What it is doing it to, such as a path, shown dimmed after the title
+    """
+    text = RULEPREFIX + title
+    if detail:
+        text += f" | [dim magenta]{escape(detail)}[/]"
+    return Rule(text, style=RULESTYLE, align="left")
+
+
+def preview(content: str, max_lines: int = PREVIEW_LINES) -> str:
+    """The first lines of content, noting how many more were left out"""
+    lines = content.splitlines()
+    if len(lines) <= max_lines:
+        return content
+    return "\n".join(lines[:max_lines] + [f"... {len(lines) - max_lines} more lines"])
+
+
+def confine_to_sandbox(patho: Path, sandbox: Optional[Path], path: str) -> Path:
+    """Resolve patho, refusing it if it escapes the sandbox through `..` or a symlink
+
+    Without a sandbox patho is returned as it is.
+    """
+    if sandbox is None:
+        return patho
+    # resolving follows symlinks and collapses "..", so what we check is where the path really points
+    resolved = patho.resolve()
+    if not resolved.is_relative_to(sandbox):
+        raise ToolError(f"The path {path} escapes the sandbox {sandbox}.", ToolErrorCategory.INVALID_ARGS)
+    return resolved
+
+
+def split_globs(value: Optional[str]) -> list[str]:
+    """The globs in a comma separated list, such as `*.pem, secrets/*`"""
+    return [glob.strip() for glob in (value or "").split(",") if glob.strip()]
+
+
+def is_permitted_file(patho: Path, cwd: str, allowed_files: Sequence[str], denied_files: Sequence[str]) -> bool:
+    """Whether the allowed and denied globs let the file tools use patho
+
+    A denied match always wins, and when allowed globs are set a file has to match one of them.
+    Matching ignores case, so that `*.pem` also covers `KEY.PEM`.
+    """
+    # globs are matched against the file name and the path relative to the working directory,
+    # so that both `*.pem` and `src/*` work
+    absolute = (Path(cwd) / patho.expanduser()).resolve()
+    candidates = [patho.name, absolute.as_posix()]
+    if absolute.is_relative_to(Path(cwd).resolve()):
+        candidates.append(absolute.relative_to(Path(cwd).resolve()).as_posix())
+
+    def matches(globs: Sequence[str]) -> bool:
+        return any(fnmatchcase(candidate.lower(), glob.lower()) for glob in globs for candidate in candidates)
+
+    if matches(denied_files):
+        return False
+    return not allowed_files or matches(allowed_files)
+
+
+def file_not_permitted_error(path: str) -> ToolError:
+    return ToolError(
+        f"The file type of {path} is not permitted by the allowed and denied files.",
+        ToolErrorCategory.PERMISSION_DENIED,
+    )
 
 
 def get_language(filename: str) -> str:
@@ -48,6 +121,20 @@ def render_template(template_path: Path, context: Optional[dict] = None) -> str:
     return template.render(context or {})
 
 
+def render_template_string(template: str, context: Optional[dict] = None) -> str:
+    """Render an inline Jinja2 template, for guidance composed without a template file
+
+    Unlike render_template, a variable missing from the context raises jinja2.UndefinedError
+    rather than rendering as an empty string.
+
+    Args:
+        template (str): The template source
+        context (dict, optional): The values the template refers to
+    """
+    env = Environment(undefined=StrictUndefined)
+    return env.from_string(template).render(context or {})
+
+
 def find_last_task_group_index(input_str: str) -> int:
     lines = input_str.splitlines()
     last_group_start_index = -1
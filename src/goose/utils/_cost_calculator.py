@@ -43,6 +43,12 @@ def _calculate_cost(model: str, token_usage: Usage) -> Optional[float]:
     return None
 
 
+def _payload_sizes(token_usage: Usage) -> str:
+    if token_usage.request_bytes is None:
+        return ""
+    return f" | sent {token_usage.request_bytes} bytes, received {token_usage.response_bytes or 0} bytes"
+
+
 def get_total_cost_message(
     token_usages: dict[str, Usage], session_name: str, start_time: datetime, end_time: datetime
 ) -> str:
@@ -52,10 +58,11 @@ def get_total_cost_message(
     for model, token_usage in token_usages.items():
         cost = _calculate_cost(model, token_usage)
         if cost is not None:
-            message += f"{session_name_prefix} | Cost for model {model} {str(token_usage)}: ${cost:.2f}\n"
+            message += f"{session_name_prefix} | Cost for model {model} {str(token_usage)}: ${cost:.2f}"
             total_cost += cost
         else:
-            message += f"{session_name_prefix} | Cost for model {model} {str(token_usage)}: Not available\n"
+            message += f"{session_name_prefix} | Cost for model {model} {str(token_usage)}: Not available"
+        message += f"{_payload_sizes(token_usage)}\n"
 
     datetime_range = f"{formatted_time(start_time)} - {formatted_time(end_time)}"
     summary = f"{datetime_range} | {session_name_prefix} | Total cost: ${total_cost:.2f}"
@@ -1,7 +1,7 @@
 import os
 import sys
-from typing import Optional
-import keyring
+from pathlib import Path
+from typing import Optional, Sequence
 
 from prompt_toolkit import prompt
 from prompt_toolkit.shortcuts import confirm
@@ -9,17 +9,26 @@ from rich import print
 from rich.panel import Panel
 
 from goose.build import build_exchange
-from goose.cli.config import PROFILES_CONFIG_PATH
+from goose.cli.config import CREDENTIALS_PATH, PROFILES_CONFIG_PATH
 from goose.cli.session_notifier import SessionNotifier
 from goose.profile import Profile
+from goose.utils.credentials import CredentialStoreUnavailableError, credential_stores, load_credential, save_credential
 from exchange import Exchange
 from exchange.invalid_choice_error import InvalidChoiceError
+from exchange.providers import Provider, get_provider
 from exchange.providers.base import MissingProviderEnvVariableError
 
 
-def create_exchange(profile: Profile, notifier: SessionNotifier) -> Exchange:
+def create_exchange(
+    profile: Profile,
+    notifier: SessionNotifier,
+    system_prompt_file: Optional[Path] = None,
+    append_system: Sequence[str] = (),
+) -> Exchange:
     try:
-        return build_exchange(profile, notifier=notifier)
+        return build_exchange(
+            profile, notifier=notifier, system_prompt_file=system_prompt_file, append_system=append_system
+        )
     except InvalidChoiceError as e:
         error_message = (
             f"[bold red]{e.message}[/bold red].\nPlease check your configuration file at {PROFILES_CONFIG_PATH}.\n"
@@ -28,25 +37,47 @@ def create_exchange(profile: Profile, notifier: SessionNotifier) -> Exchange:
         print(error_message)
         sys.exit(1)
     except MissingProviderEnvVariableError as e:
-        api_key = _get_api_key_from_keychain(e.env_variable, e.provider)
+        api_key = _get_api_key_from_store(e.env_variable, e.provider)
         if api_key is None or api_key == "":
             error_message = f"{e.message}. Please set the required environment variable to continue."
             print(Panel(error_message, style="red"))
             sys.exit(1)
         else:
             os.environ[e.env_variable] = api_key
-            return build_exchange(profile=profile, notifier=notifier)
+            return build_exchange(
+                profile=profile, notifier=notifier, system_prompt_file=system_prompt_file, append_system=append_system
+            )
+
+
+def create_provider(profile: Profile) -> Provider:
+    """Create the profile's provider, looking up or asking for a missing API key like create_exchange
+
+    Raises MissingProviderEnvVariableError if no key is given, rather than exiting.
+    """
+    provider_cls = get_provider(profile.provider)
+    try:
+        return provider_cls.from_env()
+    except MissingProviderEnvVariableError as e:
+        api_key = _get_api_key_from_store(e.env_variable, e.provider)
+        if not api_key:
+            raise
+        os.environ[e.env_variable] = api_key
+        return provider_cls.from_env()
 
 
-def _get_api_key_from_keychain(env_variable: str, provider: str) -> Optional[str]:
-    api_key = keyring.get_password("goose", env_variable)
+def _get_api_key_from_store(env_variable: str, provider: str) -> Optional[str]:
+    stores = credential_stores(CREDENTIALS_PATH)
+    api_key, store = load_credential(stores, env_variable)
     if api_key is not None:
-        print(f"Using {env_variable} value for {provider} from your keychain")
+        print(f"Using {env_variable} value for {provider} from {store.description}")
     else:
         api_key = prompt(f"Enter {env_variable} value for {provider}:".strip())
         if api_key is not None and len(api_key) > 0:
-            save_to_keyring = confirm(f"Would you like to save the {env_variable} value to your keychain?")
-            if save_to_keyring:
-                keyring.set_password("goose", env_variable, api_key)
-                print(f"Saved {env_variable} to your key_chain. service_name: goose, user_name: {env_variable}")
+            save_to_store = confirm(f"Would you like to save the {env_variable} value to {stores[0].description}?")
+            if save_to_store:
+                try:
+                    store = save_credential(stores, env_variable, api_key)
+                    print(f"Saved {env_variable} to {store.location(env_variable)}")
+                except CredentialStoreUnavailableError as e:
+                    print(f"[yellow]{e.message}[/yellow]")
     return api_key
@@ -0,0 +1,126 @@
+import os
+from abc import ABC, abstractmethod
+from pathlib import Path
+from typing import Optional
+
+import keyring
+from keyring.errors import KeyringError
+from ruamel.yaml import YAML
+
+SERVICE_NAME = "goose"
+# how many trailing characters of a secret to show, so it can be told apart from others
+VISIBLE_SECRET_CHARS = 4
+
+
+class CredentialStoreUnavailableError(Exception):
+    def __init__(self, store: "CredentialStore", reason: str) -> None:
+        self.message = f"Could not use {store.description}: {reason}"
+        super().__init__(self.message)
+
+
+class CredentialStore(ABC):
+    """Stores the secrets providers read from their environment variables, such as API keys"""
+
+    description: str
+
+    @abstractmethod
+    def get(self, key: str) -> Optional[str]:
+        """The secret stored under key, or None if it isn't stored or the store is unavailable"""
+        pass
+
+    @abstractmethod
+    def set(self, key: str, value: str) -> None:
+        """Store the secret under key, raising CredentialStoreUnavailableError if the store can't be written"""
+        pass
+
+    @abstractmethod
+    def location(self, key: str) -> str:
+        """Where the secret for key is stored, to tell the user"""
+        pass
+
+
+class KeyringCredentialStore(CredentialStore):
+    """Keeps secrets in the OS keychain"""
+
+    description = "your keychain"
+
+    def get(self, key: str) -> Optional[str]:
+        try:
+            return keyring.get_password(SERVICE_NAME, key)
+        except KeyringError:
+            return None
+
+    def set(self, key: str, value: str) -> None:
+        try:
+            keyring.set_password(SERVICE_NAME, key, value)
+        except KeyringError as e:
+            raise CredentialStoreUnavailableError(self, str(e))
+
+    def location(self, key: str) -> str:
+        return f"your key_chain. service_name: {SERVICE_NAME}, user_name: {key}"
+
+
+class FileCredentialStore(CredentialStore):
+    """Keeps secrets in a yaml file only readable by the current user"""
+
+    def __init__(self, path: Path) -> None:
+        self.path = path
+        self.description = str(path)
+
+    def _read(self) -> dict[str, str]:
+        if not self.path.exists():
+            return {}
+        return dict(YAML().load(self.path) or {})
+
+    def get(self, key: str) -> Optional[str]:
+        return self._read().get(key)
+
+    def set(self, key: str, value: str) -> None:
+        secrets = self._read()
+        secrets[key] = value
+        try:
+            self.path.parent.mkdir(parents=True, exist_ok=True)
+            self.path.touch(mode=0o600, exist_ok=True)
+            os.chmod(self.path, 0o600)
+            YAML().dump(secrets, self.path)
+        except OSError as e:
+            raise CredentialStoreUnavailableError(self, str(e))
+
+    def location(self, key: str) -> str:
+        return str(self.path)
+
+
+def credential_stores(path: Path) -> list[CredentialStore]:
+    """The stores to look secrets up in, in order, falling back to the file at path without a keychain"""
+    return [KeyringCredentialStore(), FileCredentialStore(path)]
+
+
+def save_credential(stores: list[CredentialStore], key: str, value: str) -> CredentialStore:
+    """Save the secret in the first store that is available, returning that store
+
+    Raises the error of the last store if none of them are available.
+    """
+    for store in stores[:-1]:
+        try:
+            store.set(key, value)
+            return store
+        except CredentialStoreUnavailableError:
+            continue
+    stores[-1].set(key, value)
+    return stores[-1]
+
+
+def load_credential(stores: list[CredentialStore], key: str) -> tuple[Optional[str], Optional[CredentialStore]]:
+    """Find the secret in the first store that has it, returning it with that store"""
+    for store in stores:
+        value = store.get(key)
+        if value is not None:
+            return value, store
+    return None, None
+
+
+def mask_secret(value: str) -> str:
+    """The secret with all but its last few characters hidden, or hidden entirely if it is short"""
+    if len(value) <= 2 * VISIBLE_SECRET_CHARS:
+        return "****"
+    return "****" + value[-VISIBLE_SECRET_CHARS:]
@@ -1,21 +1,29 @@
 from pathlib import Path
+from typing import Optional
 
 from goose.toolkit.utils import render_template
 
+HINTS_FILE_NAME = ".goosehints"
 
-def fetch_goosehints() -> str:
-    hints = []
-    dirs = [Path.cwd()] + list(Path.cwd().parents)
+
+def goosehints_paths(cwd: Optional[Path] = None) -> list[Path]:
+    """The hints files that apply in cwd, ordered from the least to the most specific
+
+    The global hints come first, then the project hints from the repository root (the closest
+    directory with a .git) down to cwd. Later hints are read last, so they take precedence.
+    """
+    cwd = cwd or Path.cwd()
+    dirs = []
+    for dir in [cwd, *cwd.parents]:
+        dirs.append(dir)
+        if (dir / ".git").exists():
+            break
     # reverse to go from parent to child
     dirs.reverse()
 
-    for dir in dirs:
-        hints_path = dir / ".goosehints"
-        if hints_path.is_file():
-            hints.append(render_template(hints_path))
+    paths = [Path.home() / ".config/goose" / HINTS_FILE_NAME] + [dir / HINTS_FILE_NAME for dir in dirs]
+    return [path for path in paths if path.is_file()]
 
-    home_hints_path = Path.home() / ".config/goose/.goosehints"
-    if home_hints_path.is_file():
-        hints.append(render_template(home_hints_path))
 
-    return "\n\n".join(hints)
+def fetch_goosehints() -> str:
+    return "\n\n".join(f"Hints from {path}:\n{render_template(path)}" for path in goosehints_paths())
@@ -1,20 +1,143 @@
 import json
+import sys
+import time
 from pathlib import Path
-from typing import Iterator
+from typing import IO, Iterator, Optional
 
 from exchange import Message
 
 from goose.cli.config import SESSION_FILE_SUFFIX
 
+AUDIT_FILE_SUFFIX = ".audit.jsonl"
+TAGS_FILE_SUFFIX = ".tags.json"
+RUNNING_FILE_SUFFIX = ".running"
+LOCK_POLL_INTERVAL = 0.1
+
+if sys.platform == "win32":
+    import msvcrt
+
+    def _try_lock(file: IO) -> bool:
+        try:
+            file.seek(0)
+            msvcrt.locking(file.fileno(), msvcrt.LK_NBLCK, 1)
+            return True
+        except OSError:
+            return False
+
+else:
+    import fcntl
+
+    def _try_lock(file: IO) -> bool:
+        try:
+            fcntl.flock(file.fileno(), fcntl.LOCK_EX | fcntl.LOCK_NB)
+            return True
+        except OSError:
+            return False
+
+
+class SessionInUseError(Exception):
+    def __init__(self, path: Path) -> None:
+        self.message = f"The session {path.stem} is in use by another goose process, close it or try again later."
+        super().__init__(self.message)
+
+
+class SessionLock:
+    """An advisory lock on a session's lock file, held while a process reads and writes the session
+
+    The lock is released when the process exits, however it exits, so unlike the lock file
+    existing it always means another process is using the session.
+    """
+
+    def __init__(self, path: Path) -> None:
+        self.path = path
+        self._file: Optional[IO] = None
+
+    def acquire(self, timeout: float = 0) -> None:
+        """Take the lock, waiting up to timeout seconds for another process to release it
+
+        Raises SessionInUseError if it is still held after that.
+        """
+        self._file = open(self.path, "a")
+        deadline = time.monotonic() + timeout
+        while not _try_lock(self._file):
+            if time.monotonic() >= deadline:
+                self.release()
+                raise SessionInUseError(self.path)
+            time.sleep(LOCK_POLL_INTERVAL)
+
+    def release(self) -> None:
+        # closing the file releases the lock
+        if self._file is not None:
+            self._file.close()
+            self._file = None
+
 
 def is_existing_session(path: Path) -> bool:
     return path.is_file() and path.stat().st_size > 0
 
 
+def session_lock_path(path: Path) -> Path:
+    """The file a process holds a SessionLock on while it uses a session
+
+    It is never removed while the session is in use, since a process waiting for the lock would
+    then hold it on a file that no longer exists while another locks a new one.
+    """
+    return path.with_suffix(".lock")
+
+
+def session_running_path(path: Path) -> Path:
+    """The marker file that exists while a session is running, and is left behind if it exits uncleanly"""
+    return path.with_suffix(RUNNING_FILE_SUFFIX)
+
+
+def session_audit_path(path: Path) -> Path:
+    """The file alongside a session that records every tool call made in it"""
+    return path.with_suffix(AUDIT_FILE_SUFFIX)
+
+
+def session_tags_path(path: Path) -> Path:
+    """The file alongside a session that keeps the tags it was given"""
+    return path.with_suffix(TAGS_FILE_SUFFIX)
+
+
+def remove_session_files(path: Path) -> None:
+    """Delete the session at path along with every file kept alongside it"""
+    path.unlink()
+    for sidecar in (session_lock_path, session_running_path, session_audit_path, session_tags_path):
+        sidecar(path).unlink(missing_ok=True)
+
+
+def read_session_tags(path: Path) -> list[str]:
+    """The tags of the session at path, in the order they were added"""
+    tags_path = session_tags_path(path)
+    if not tags_path.exists():
+        return []
+    return json.loads(tags_path.read_text())
+
+
+def add_session_tags(path: Path, tags: list[str]) -> list[str]:
+    """Add tags to the session at path, skipping ones it already has, and return all of its tags"""
+    existing = read_session_tags(path)
+    updated = existing + [tag for tag in dict.fromkeys(tags) if tag not in existing]
+    session_tags_path(path).write_text(json.dumps(updated))
+    return updated
+
+
 def is_empty_session(path: Path) -> bool:
     return path.is_file() and path.stat().st_size == 0
 
 
+def ensure_writable(path: Path) -> bool:
+    """Whether the session file can be written, creating it empty if it doesn't exist yet"""
+    try:
+        path.parent.mkdir(parents=True, exist_ok=True)
+        with open(path, "a"):
+            pass
+        return True
+    except OSError:
+        return False
+
+
 def read_or_create_file(file_path: Path) -> list[Message]:
     if file_path.exists():
         return read_from_file(file_path)
@@ -39,7 +162,8 @@ def list_sorted_session_files(session_files_directory: Path) -> dict[str, Path]:
 
 
 def list_session_files(session_files_directory: Path) -> Iterator[Path]:
-    return session_files_directory.glob(f"*{SESSION_FILE_SUFFIX}")
+    files = session_files_directory.glob(f"*{SESSION_FILE_SUFFIX}")
+    return (path for path in files if not path.name.endswith(AUDIT_FILE_SUFFIX))
 
 
 def session_file_exists(session_files_directory: Path) -> bool:
@@ -53,3 +177,16 @@ def log_messages(file_path: Path, messages: list[Message]) -> None:
         for message in messages:
             json.dump(message.to_dict(), f)
             f.write("\n")
+
+
+def log_audit_record(file_path: Path, record: dict[str, any]) -> None:
+    with open(file_path, "a") as f:
+        json.dump(record, f)
+        f.write("\n")
+
+
+def read_audit_records(file_path: Path) -> list[dict[str, any]]:
+    if not file_path.exists():
+        return []
+    with open(file_path, "r") as f:
+        return [json.loads(line) for line in f if line.strip()]
@@ -1,14 +1,21 @@
 import os
 import re
+import shutil
+import signal
 import subprocess
 import time
 from typing import Mapping, Optional
 
+from exchange import ToolError, ToolErrorCategory
 from goose.notifier import Notifier
 from goose.utils.ask import ask_an_ai
 from goose.view import ExchangeView
+from rich.markup import escape
 from rich.prompt import Confirm
 
+# how much of the latest line of output to show in the status while a command runs
+PROGRESS_WIDTH = 80
+
 
 def is_dangerous_command(command: str) -> bool:
     """
@@ -46,17 +53,44 @@ def is_dangerous_command(command: str) -> bool:
     return False
 
 
+def find_bash() -> Optional[str]:
+    """The path to bash on the PATH, or None if there isn't one, as on Windows without Git Bash or WSL"""
+    return shutil.which("bash")
+
+
+def bash_not_found_error() -> ToolError:
+    return ToolError(
+        "bash was not found on the PATH, so shell commands can't run."
+        " Ask the user to install a bash, such as Git Bash or WSL on Windows, and add it to their PATH.",
+        ToolErrorCategory.NOT_FOUND,
+    )
+
+
 def keep_unsafe_command_prompt(command: str) -> bool:
     message = f"\nWe flagged the command - [bold red]{command}[/] - as potentially unsafe, do you want to proceed?"
     return Confirm.ask(message, default=True)
 
 
+def kill_process(proc: subprocess.Popen) -> None:
+    """Kill the command and anything it started, then reap it"""
+    try:
+        if os.name == "posix":
+            # the command leads its own process group, see shell
+            os.killpg(proc.pid, signal.SIGKILL)
+        else:
+            proc.kill()
+    except ProcessLookupError:
+        pass
+    proc.wait()
+
+
 def shell(
     command: str,
     notifier: Notifier,
     exchange_view: ExchangeView,
     cwd: Optional[str] = None,
     env: Optional[Mapping[str, str]] = None,
+    executable: Optional[str] = None,
 ) -> str:
     """Execute a command on the shell
 
@@ -93,6 +127,10 @@ def shell(
         text=True,
         cwd=cwd,
         env=env,
+        executable=executable,
+        # a process group of its own, so that killing the command also kills whatever it started, while
+        # staying in our session so that commands reading from the terminal, such as sudo, still can
+        preexec_fn=os.setpgrp if os.name == "posix" else None,
     )
     # this enables us to read lines without blocking
     os.set_blocking(proc.stdout.fileno(), False)
@@ -101,47 +139,62 @@ def shell(
     output_lines = []
     last_output_time = time.time()
     cutoff = 10
-    while proc.poll() is None:
-        notifier.status("running shell command")
-        line = proc.stdout.readline()
-        if line:
-            output_lines.append(line)
-            last_output_time = time.time()
-
-        # If we see a clear pattern match, we plan to abort
-        exit_criteria = any(pattern.search(line) for pattern in compiled_patterns)
-
-        # and if we haven't seen a new line in 10+s, check with AI to see if it may be stuck
-        if not exit_criteria and time.time() - last_output_time > cutoff:
-            notifier.status("checking on shell status")
-            response = ask_an_ai(
-                input="\n".join([command] + output_lines),
-                prompt=(
-                    "You will evaluate the output of shell commands to see if they may be stuck."
-                    " Look for commands that appear to be awaiting user input, or otherwise running indefinitely (such as a web service)."  # noqa
-                    " A command that will take a while, such as downloading resources is okay."  # noqa
-                    " return [Yes] if stuck, [No] otherwise."
-                ),
-                exchange=exchange_view.processor,
-                with_tools=False,
-            )
-            exit_criteria = "[yes]" in response.content[0].text.lower()
-            # We add exponential backoff for how often we check for the command being stuck
-            cutoff *= 10
-
-        if exit_criteria:
-            proc.terminate()
-            raise ValueError(
-                f"The command `{command}` looks like it will run indefinitely or is otherwise stuck."
-                f"You may be able to specify inputs if it applies to this command."
-                f"Otherwise to enable continued iteration, you'll need to ask the user to run this command in another terminal."  # noqa
-            )
+    notifier.status("running shell command")
+    try:
+        while proc.poll() is None:
+            line = proc.stdout.readline()
+            if line:
+                output_lines.append(line)
+                last_output_time = time.time()
+                # show each line as it arrives, so long running commands such as builds show their progress
+                notifier.status(f"running shell command | {escape(line.strip()[:PROGRESS_WIDTH])}")
+
+            # If we see a clear pattern match, we plan to abort
+            exit_criteria = any(pattern.search(line) for pattern in compiled_patterns)
+
+            # and if we haven't seen a new line in 10+s, check with AI to see if it may be stuck
+            if not exit_criteria and time.time() - last_output_time > cutoff:
+                notifier.status("checking on shell status")
+                response = ask_an_ai(
+                    input="\n".join([command] + output_lines),
+                    prompt=(
+                        "You will evaluate the output of shell commands to see if they may be stuck."
+                        " Look for commands that appear to be awaiting user input, or otherwise running indefinitely (such as a web service)."  # noqa
+                        " A command that will take a while, such as downloading resources is okay."  # noqa
+                        " return [Yes] if stuck, [No] otherwise."
+                    ),
+                    exchange=exchange_view.processor,
+                    with_tools=False,
+                )
+                exit_criteria = "[yes]" in response.content[0].text.lower()
+                # We add exponential backoff for how often we check for the command being stuck
+                cutoff *= 10
+                notifier.status("running shell command")
+
+            if exit_criteria:
+                proc.terminate()
+                raise ValueError(
+                    f"The command `{command}` looks like it will run indefinitely or is otherwise stuck."
+                    f"You may be able to specify inputs if it applies to this command."
+                    f"Otherwise to enable continued iteration, you'll need to ask the user to run this command in another terminal."  # noqa
+                )
+    except BaseException:
+        # an interrupt or timeout cancelling the reply must not leave the command running
+        kill_process(proc)
+        raise
 
     # read any remaining lines
     while line := proc.stdout.readline():
         output_lines.append(line)
     output = "".join(output_lines)
 
+    # The shell reports commands it could not find or execute through reserved return codes,
+    # raise those so the model is told what kind of failure it was
+    if proc.returncode == 127:
+        raise ToolError(f"Command not found\n{output}", ToolErrorCategory.NOT_FOUND)
+    if proc.returncode == 126:
+        raise ToolError(f"Command could not be executed\n{output}", ToolErrorCategory.PERMISSION_DENIED)
+
     # Determine the result based on the return code
     if proc.returncode == 0:
         result = "Command succeeded"
@@ -0,0 +1,13 @@
+from tiktoken import get_encoding
+
+from goose.toolkit.base import Toolkit
+
+
+def get_status_token_counts(toolkit: Toolkit) -> dict[str, int]:
+    """Count the tokens of each resource a toolkit currently contributes to the context
+
+    Args:
+        toolkit (Toolkit): The toolkit to report on
+    """
+    encoder = get_encoding("cl100k_base")
+    return {name: len(encoder.encode(content)) for name, content in toolkit.status().items() if content}
@@ -0,0 +1,17 @@
+from pygments.styles import get_all_styles
+
+# the pygments style rich uses for code blocks unless told otherwise
+DEFAULT_THEME = "monokai"
+
+
+def available_themes() -> list[str]:
+    """The names of the installed pygments styles, which code blocks can be highlighted with"""
+    return sorted(get_all_styles())
+
+
+def check_theme(name: str) -> str:
+    """Return the theme name if it is installed, otherwise raise a ValueError listing the ones that are"""
+    themes = available_themes()
+    if name not in themes:
+        raise ValueError(f"Unknown theme '{name}'. Available themes: {', '.join(themes)}")
+    return name
@@ -1,6 +1,7 @@
 from unittest.mock import patch
 
 from prompt_toolkit import PromptSession
+from prompt_toolkit.formatted_text import FormattedText
 import pytest
 from goose.cli.prompt.goose_prompt_session import GoosePromptSession
 from goose.cli.prompt.user_input import PromptAction, UserInput
@@ -35,6 +36,15 @@ def test_get_user_input_to_continue():
         assert user_input == UserInput(PromptAction.CONTINUE, "input_value")
 
 
+def test_get_user_input_uses_configured_prompt():
+    with patch.object(PromptSession, "prompt", return_value="input_value") as mock_prompt:
+        goose_prompt_session = GoosePromptSession(prompt="Nova> ")
+
+        goose_prompt_session.get_user_input()
+
+        assert mock_prompt.call_args.args[0] == FormattedText([("#00AEAE", "Nova> ")])
+
+
 @pytest.mark.parametrize("exit_input", ["exit", ":q"])
 def test_get_user_input_to_exit(exit_input, mock_prompt_session):
     with patch.object(PromptSession, "prompt", return_value=exit_input):
@@ -1,8 +1,18 @@
 from unittest.mock import patch
 
 import pytest
-from goose.cli.config import ensure_config, read_config, session_path, write_config
+import exchange.message as message_module
+from goose.cli.config import (
+    ensure_config,
+    find_dotenv,
+    read_config,
+    session_path,
+    use_user_prompts,
+    write_config,
+)
 from goose.profile import default_profile
+from goose.synopsis.moderator import Synopsis
+from goose.synopsis.system import system
 
 
 @pytest.fixture
@@ -92,3 +102,25 @@ def test_ensure_config_get_existing_profile_not_exist(
 
 def test_session_path(mock_sessions_path):
     assert session_path("session1") == mock_sessions_path / "session1.jsonl"
+
+
+def test_user_prompts_override_bundled_templates(tmp_path, monkeypatch, exchange_factory):
+    monkeypatch.setattr(message_module, "TEMPLATE_SEARCH_PATHS", [])
+    (tmp_path / "synopsis.md").write_text("custom synopsis on {{ system.platform }}")
+
+    with patch("goose.cli.config.PROMPTS_PATH", tmp_path):
+        use_user_prompts()
+
+    assert Synopsis().get_synopsis(exchange_factory()).text == f"custom synopsis on {system.platform}"
+
+
+def test_find_dotenv_stops_at_repository_root(tmp_path):
+    (tmp_path / ".env").write_text("OUTSIDE=1\n")
+    repo = tmp_path / "repo"
+    (repo / ".git").mkdir(parents=True)
+    (repo / "src").mkdir()
+
+    assert find_dotenv(repo / "src") is None
+
+    (repo / ".env").write_text("INSIDE=1\n")
+    assert find_dotenv(repo / "src") == repo / ".env"
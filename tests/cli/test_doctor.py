@@ -0,0 +1,103 @@
+from unittest.mock import patch
+
+import pytest
+from click.testing import CliRunner
+from exchange import Message
+from exchange.providers import Usage
+from exchange.providers.base import AccountInfo
+from goose.cli.config import write_config
+from goose.cli.doctor import run_checks
+from goose.cli.main import goose_cli
+
+
+@pytest.fixture
+def configured(tmp_path, monkeypatch, profile_factory):
+    """Write a profile for openai, with no stored credentials to fall back on"""
+    monkeypatch.delenv("OPENAI_API_KEY", raising=False)
+    profiles_path = tmp_path / "profiles.yaml"
+    with (
+        patch("goose.cli.config.PROFILES_CONFIG_PATH", profiles_path),
+        patch("goose.cli.doctor.PROFILES_CONFIG_PATH", profiles_path),
+        patch("goose.cli.doctor.CREDENTIALS_PATH", tmp_path / "credentials.yaml"),
+        patch("keyring.get_password", return_value=None),
+    ):
+        profile = profile_factory(
+            {"provider": "openai", "moderator": "passive", "toolkits": [{"name": "developer", "requires": {}}]}
+        )
+        write_config({"default": profile})
+        yield profile
+
+
+def test_run_checks_valid_config(configured, monkeypatch):
+    monkeypatch.setenv("OPENAI_API_KEY", "test_api_key")
+
+    checks = run_checks("default")
+
+    assert [check.name for check in checks] == ["profile", "provider", "credentials", "moderator", "toolkit"]
+    assert all(check.ok for check in checks)
+    assert checks[2].detail == "OPENAI_API_KEY from the environment"
+
+
+def test_run_checks_missing_api_key(configured):
+    checks = run_checks("default")
+
+    failed = [check for check in checks if not check.ok]
+    assert [check.name for check in failed] == ["credentials"]
+    assert failed[0].detail.startswith("Missing environment variables: OPENAI_API_KEY for provider openai")
+
+
+def test_run_checks_unknown_profile_and_provider(configured, profile_factory):
+    write_config({"default": profile_factory({"provider": "nope", "moderator": "passive"})})
+
+    assert not run_checks("missing")[0].ok
+    checks = run_checks("default")
+    assert [(check.name, check.ok) for check in checks] == [("profile", True), ("provider", False), ("moderator", True)]
+    assert checks[1].detail.startswith("Unknown provider: nope.")
+
+
+def test_run_checks_round_trip(configured, monkeypatch):
+    monkeypatch.setenv("OPENAI_API_KEY", "test_api_key")
+
+    with (
+        patch("exchange.providers.openai.OpenAiProvider.complete") as mock_complete,
+        patch("exchange.providers.openai.OpenAiProvider.account_info") as mock_account_info,
+    ):
+        mock_complete.return_value = (Message.assistant("OK"), Usage(5, 1, 6))
+        mock_account_info.return_value = AccountInfo(key_valid=True, organization="org-goose")
+        checks = run_checks("default", round_trip=True)
+
+    assert checks[-2].name == "account"
+    assert checks[-2].detail == "openai: organization org-goose"
+    assert checks[-1].name == "round trip"
+    assert checks[-1].ok
+    assert checks[-1].detail == "openai/mock_processor used 6 tokens"
+
+
+def test_doctor_command_exit_code(configured, monkeypatch):
+    runner = CliRunner()
+
+    with patch("goose.cli.main.print") as mock_print:
+        result = runner.invoke(goose_cli, ["doctor"])
+    assert result.exit_code == 1
+    printed = [str(call.args[0]) for call in mock_print.call_args_list]
+    assert any("credentials" in line and "OPENAI_API_KEY" in line for line in printed)
+
+    monkeypatch.setenv("OPENAI_API_KEY", "test_api_key")
+    with patch("goose.cli.main.print"):
+        assert runner.invoke(goose_cli, ["doctor"]).exit_code == 0
+
+
+def test_run_checks_round_trip_reports_rejected_key(configured, monkeypatch):
+    monkeypatch.setenv("OPENAI_API_KEY", "test_api_key")
+
+    with (
+        patch("exchange.providers.openai.OpenAiProvider.complete") as mock_complete,
+        patch("exchange.providers.openai.OpenAiProvider.account_info") as mock_account_info,
+    ):
+        mock_complete.return_value = (Message.assistant("OK"), Usage(5, 1, 6))
+        mock_account_info.return_value = AccountInfo(key_valid=False)
+        checks = run_checks("default", round_trip=True)
+
+    account = next(check for check in checks if check.name == "account")
+    assert not account.ok
+    assert account.detail == "openai rejected the key"
@@ -0,0 +1,42 @@
+import pytest
+from goose.cli.instructions import Instructions, parse_instructions
+
+
+def test_parse_instructions_without_front_matter():
+    content = "Fix the failing test\n---\nand nothing else\n"
+    assert parse_instructions(content) == Instructions(content)
+
+
+def test_parse_instructions_with_front_matter():
+    instructions = parse_instructions(
+        "---\n"
+        "model: claude-3-5-sonnet-latest\n"
+        "provider: anthropic\n"
+        "systems:\n"
+        "  - developer\n"
+        "append_system:\n"
+        "  - Keep commits small\n"
+        "  - Use 2-space indent\n"
+        "---\n"
+        "\n"
+        "Fix the failing test\n"
+    )
+
+    assert instructions.text == "Fix the failing test\n"
+    assert instructions.systems == ("developer",)
+    assert instructions.append_system == ("Keep commits small", "Use 2-space indent")
+    assert instructions.profile_overrides() == {
+        "provider": "anthropic",
+        "processor": "claude-3-5-sonnet-latest",
+        "toolkits": ("developer",),
+    }
+
+
+def test_parse_instructions_without_closing_delimiter():
+    content = "---\nFix the failing test\n"
+    assert parse_instructions(content).text == content
+
+
+def test_parse_instructions_front_matter_must_be_a_mapping():
+    with pytest.raises(ValueError, match="must be a mapping"):
+        parse_instructions("---\n- developer\n---\nFix the failing test\n")
@@ -1,5 +1,7 @@
 from datetime import datetime
 import importlib
+import json
+import os
 from time import time
 from unittest.mock import MagicMock, patch
 
@@ -7,7 +9,12 @@ import click
 import pytest
 from click.testing import CliRunner
 from exchange import Message
+from goose.cli.config import write_config
 from goose.cli.main import cli, goose_cli
+from goose.utils.credentials import FileCredentialStore
+from goose.utils.session_file import add_session_tags
+from keyring.errors import NoKeyringError
+from tiktoken import get_encoding
 
 
 @pytest.fixture
@@ -35,16 +42,74 @@ def test_session_start_command_with_session_name(mock_session):
     runner = CliRunner()
     runner.invoke(goose_cli, ["session", "start", "session1", "--profile", "default"])
     mock_session_class.assert_called_once_with(
-        name="session1", profile="default", plan=None, log_level="INFO", tracing=False
+        name="session1",
+        profile="default",
+        plan=None,
+        log_level="INFO",
+        tracing=False,
+        system_prompt_file=None,
+        append_system=(),
+        theme=None,
     )
     mock_session_instance.run.assert_called_once()
 
 
+def test_session_start_command_with_system_prompt_file(mock_session, tmp_path):
+    mock_session_class, mock_session_instance = mock_session
+    system_prompt_file = tmp_path / "system.jinja"
+    system_prompt_file.write_text("You are a custom assistant.")
+    runner = CliRunner()
+    runner.invoke(goose_cli, ["session", "start", "session1", "--system-prompt-file", str(system_prompt_file)])
+    mock_session_class.assert_called_once_with(
+        name="session1",
+        profile=None,
+        plan=None,
+        log_level="INFO",
+        tracing=False,
+        system_prompt_file=str(system_prompt_file),
+        append_system=(),
+        theme=None,
+    )
+    mock_session_instance.run.assert_called_once()
+
+
+def test_session_start_command_with_append_system(mock_session):
+    mock_session_class, _ = mock_session
+    runner = CliRunner()
+    runner.invoke(
+        goose_cli,
+        ["session", "start", "session1", "--append-system", "use 2-space indent", "--append-system", "no emoji"],
+    )
+    assert mock_session_class.call_args.kwargs["append_system"] == ("use 2-space indent", "no emoji")
+
+
+def test_session_start_command_with_theme(mock_session):
+    mock_session_class, _ = mock_session
+    runner = CliRunner()
+
+    runner.invoke(goose_cli, ["session", "start", "--theme", "zenburn"])
+    assert mock_session_class.call_args.kwargs["theme"] == "zenburn"
+
+    result = runner.invoke(goose_cli, ["session", "start", "--theme", "nope"])
+    assert result.exit_code == 2
+    assert "Unknown theme 'nope'" in result.output
+
+
+def test_session_start_command_with_missing_system_prompt_file(mock_session, tmp_path):
+    mock_session_class, _ = mock_session
+    runner = CliRunner()
+    result = runner.invoke(goose_cli, ["session", "start", "--system-prompt-file", str(tmp_path / "missing.jinja")])
+    assert result.exit_code != 0
+    mock_session_class.assert_not_called()
+
+
 def test_session_resume_command_with_session_name(mock_session):
     mock_session_class, mock_session_instance = mock_session
     runner = CliRunner()
     runner.invoke(goose_cli, ["session", "resume", "session1", "--profile", "default"])
-    mock_session_class.assert_called_once_with(name="session1", profile="default", log_level="INFO")
+    mock_session_class.assert_called_once_with(
+        name="session1", profile="default", log_level="INFO", system_prompt_file=None, append_system=(), theme=None
+    )
     mock_session_instance.run.assert_called_once()
 
 
@@ -69,10 +134,226 @@ def test_session_resume_command_without_session_name_use_latest_session(
 
     second_file_path = mock_session_files_path / "second.jsonl"
     mock_print.assert_called_once_with(f"Resuming most recent session: second from {second_file_path}")
-    mock_session_class.assert_called_once_with(name="second", profile="default", log_level="INFO")
+    mock_session_class.assert_called_once_with(
+        name="second", profile="default", log_level="INFO", system_prompt_file=None, append_system=(), theme=None
+    )
     mock_session_instance.run.assert_called_once()
 
 
+def test_run_resume_session_uses_latest_session(mock_session_files_path, mock_session, create_session_file):
+    mock_session_class, mock_session_instance = mock_session
+    for index, session_name in enumerate(["first", "second"]):
+        create_session_file([Message.user("Hello1")], mock_session_files_path / f"{session_name}.jsonl", time() + index)
+    runner = CliRunner()
+    runner.invoke(goose_cli, ["run", "--resume-session"], input="Next step")
+
+    mock_session_class.assert_called_once_with(
+        name="second",
+        profile=None,
+        log_level="INFO",
+        tracing=False,
+        system_prompt_file=None,
+        append_system=(),
+        theme=None,
+        profile_overrides={},
+    )
+    mock_session_instance.single_pass.assert_called_once_with(initial_message="Next step", timeout=None)
+
+
+def test_run_resume_session_without_session_files_starts_new_session(
+    mock_print, mock_session_files_path, mock_session
+):
+    mock_session_class, mock_session_instance = mock_session
+    runner = CliRunner()
+    result = runner.invoke(goose_cli, ["run", "--resume-session"], input="First step")
+
+    assert result.exit_code == 0
+    mock_print.assert_called_once_with("No session found to resume, starting a new one.")
+    mock_session_class.assert_called_once_with(
+        name=None,
+        profile=None,
+        log_level="INFO",
+        tracing=False,
+        system_prompt_file=None,
+        append_system=(),
+        theme=None,
+        profile_overrides={},
+    )
+    mock_session_instance.single_pass.assert_called_once_with(initial_message="First step", timeout=None)
+
+
+def test_run_applies_instruction_front_matter(mock_session, tmp_path):
+    mock_session_class, mock_session_instance = mock_session
+    instructions = tmp_path / "task.md"
+    instructions.write_text(
+        "---\nmodel: gpt-4o-mini\nsystems: [developer, github]\nappend_system: Keep commits small\n---\n"
+        "Fix the failing test\n"
+    )
+
+    result = CliRunner().invoke(goose_cli, ["run", str(instructions), "--append-system", "Use 2-space indent"])
+
+    assert result.exit_code == 0
+    kwargs = mock_session_class.call_args.kwargs
+    assert kwargs["profile_overrides"] == {"processor": "gpt-4o-mini", "toolkits": ("developer", "github")}
+    assert kwargs["append_system"] == ("Keep commits small", "Use 2-space indent")
+    mock_session_instance.single_pass.assert_called_once_with(initial_message="Fix the failing test\n", timeout=None)
+
+
+def test_run_rejects_unknown_front_matter(mock_session, tmp_path):
+    instructions = tmp_path / "task.md"
+    instructions.write_text("---\ntemperature: 0\n---\nFix the failing test\n")
+
+    result = CliRunner().invoke(goose_cli, ["run", str(instructions)])
+
+    assert result.exit_code == 2
+    assert "Unknown front-matter keys: temperature" in result.output
+
+
+def test_run_writes_final_answer_to_output_file(mock_session, tmp_path):
+    _, mock_session_instance = mock_session
+    mock_session_instance.single_pass.return_value = "The answer is 42."
+    output_path = tmp_path / "result.txt"
+
+    result = CliRunner().invoke(goose_cli, ["run", "--output-file", str(output_path)], input="What is the answer?")
+
+    assert result.exit_code == 0
+    assert output_path.read_text() == "The answer is 42."
+
+
+def test_run_writes_final_answer_to_stdout(mock_session):
+    _, mock_session_instance = mock_session
+    mock_session_instance.single_pass.return_value = "The answer is 42."
+
+    result = CliRunner().invoke(goose_cli, ["run", "--output", "-"], input="What is the answer?")
+
+    assert result.output == "The answer is 42."
+
+
+def test_run_passes_timeout(mock_session):
+    _, mock_session_instance = mock_session
+
+    result = CliRunner().invoke(goose_cli, ["run", "--timeout", "30"], input="Run the tests")
+
+    assert result.exit_code == 0
+    mock_session_instance.single_pass.assert_called_once_with(initial_message="Run the tests", timeout=30.0)
+
+
+def test_run_loads_context_files(mock_session, tmp_path):
+    _, mock_session_instance = mock_session
+    (tmp_path / "a.py").write_text("")
+    (tmp_path / "b.md").write_text("")
+    args = ["run", "--context", str(tmp_path / "a.py"), "--context", str(tmp_path / "b.md")]
+
+    assert CliRunner().invoke(goose_cli, args, input="Fix it").exit_code == 0
+    mock_session_instance.load_context_files.assert_called_once_with((str(tmp_path / "a.py"), str(tmp_path / "b.md")))
+
+    result = CliRunner().invoke(goose_cli, ["run", "--context", str(tmp_path / "missing.py")], input="Fix it")
+    assert result.exit_code == 2
+    assert mock_session_instance.load_context_files.call_count == 1
+
+def test_run_exit_code_follows_final_answer(mock_session):
+    _, mock_session_instance = mock_session
+
+    mock_session_instance.single_pass.return_value = "All tests pass."
+    assert CliRunner().invoke(goose_cli, ["run"], input="Fix the tests").exit_code == 0
+
+    mock_session_instance.single_pass.return_value = None
+    result = CliRunner().invoke(goose_cli, ["run"], input="Fix the tests")
+    assert result.exit_code == 1
+    assert "The run ended without a final answer." in result.output
+
+
+def test_run_continue_on_error_exits_with_zero(mock_session):
+    _, mock_session_instance = mock_session
+
+    mock_session_instance.single_pass.return_value = None
+    assert CliRunner().invoke(goose_cli, ["run", "--continue-on-error"], input="Fix the tests").exit_code == 0
+
+    mock_session_instance.single_pass.side_effect = RuntimeError("provider unavailable")
+    assert CliRunner().invoke(goose_cli, ["run"], input="Fix the tests").exit_code == 1
+    assert CliRunner().invoke(goose_cli, ["run", "--continue-on-error"], input="Fix the tests").exit_code == 0
+
+
+def test_session_audit_command(mock_print, mock_session_files_path, create_session_file):
+    create_session_file([Message.user("Hello")], mock_session_files_path / "session1.jsonl")
+    records = [
+        {"tool": "bash", "duration": 1.5, "success": True},
+        {"tool": "bash", "duration": 0.5, "success": False},
+        {"tool": "text_editor", "duration": 0.25, "success": True},
+    ]
+    (mock_session_files_path / "session1.audit.jsonl").write_text("".join(json.dumps(r) + "\n" for r in records))
+
+    runner = CliRunner()
+    runner.invoke(goose_cli, ["session", "audit", "session1"])
+
+    assert [call.args[0] for call in mock_print.call_args_list] == [
+        "[green]session1[/green]: [bold][cyan]3[/cyan][/bold] tool calls",
+        "    bash: 2 calls, 1 failed, 2.00s",
+        "    text_editor: 1 calls, 0 failed, 0.25s",
+    ]
+
+
+def test_session_audit_command_without_records(mock_print, mock_session_files_path, create_session_file):
+    create_session_file([Message.user("Hello")], mock_session_files_path / "session1.jsonl")
+    runner = CliRunner()
+    runner.invoke(goose_cli, ["session", "audit"])
+    mock_print.assert_called_once_with("No tool calls recorded for session1.")
+
+
+def test_providers_configure_command(mock_print, tmp_path):
+    credentials_path = tmp_path / "credentials.yaml"
+    with (
+        patch("goose.cli.main.CREDENTIALS_PATH", credentials_path),
+        patch("keyring.set_password", side_effect=NoKeyringError("no backend")),
+    ):
+        runner = CliRunner()
+        result = runner.invoke(goose_cli, ["providers", "configure", "openai"], input="sk-secret\n")
+
+    assert result.exit_code == 0
+    assert FileCredentialStore(credentials_path).get("OPENAI_API_KEY") == "sk-secret"
+    mock_print.assert_called_with(f"Saved OPENAI_API_KEY to {credentials_path}")
+
+
+def test_providers_configure_command_unknown_provider():
+    runner = CliRunner()
+    result = runner.invoke(goose_cli, ["providers", "configure", "nope"])
+
+    assert result.exit_code == 2
+    assert "Unknown provider nope" in result.output
+
+
+def test_profiles_list_command_masks_credentials(tmp_path, monkeypatch, profile_factory):
+    monkeypatch.setenv("OPENAI_API_KEY", "sk-proj-secret-1234")
+    monkeypatch.delenv("ANTHROPIC_API_KEY", raising=False)
+    profiles_path = tmp_path / "profiles.yaml"
+    with (
+        patch("goose.cli.config.PROFILES_CONFIG_PATH", profiles_path),
+        patch("goose.cli.main.PROFILES_CONFIG_PATH", profiles_path),
+        patch("goose.cli.config.CREDENTIALS_PATH", tmp_path / "credentials.yaml"),
+        patch("keyring.get_password", return_value=None),
+    ):
+        write_config(
+            {
+                "default": profile_factory({"provider": "openai", "processor": "gpt-4o"}),
+                "claude": profile_factory({"provider": "anthropic", "processor": "claude-3-5-sonnet"}),
+            }
+        )
+        runner = CliRunner()
+        listed = runner.invoke(goose_cli, ["profiles", "list"])
+        as_json = runner.invoke(goose_cli, ["profiles", "list", "--json"])
+
+    assert listed.exit_code == 0
+    assert "default: openai/gpt-4o" in listed.output
+    assert "claude: anthropic/claude-3-5-sonnet" in listed.output
+    assert "OPENAI_API_KEY: ****1234" in listed.output
+    assert "sk-proj-secret" not in listed.output
+
+    settings = json.loads(as_json.output)
+    assert settings["default"]["processor"] == "gpt-4o"
+    assert settings["default"]["credentials"] == {"OPENAI_API_KEY": "****1234"}
+    assert settings["claude"]["credentials"] == {"ANTHROPIC_API_KEY": None}
+
+
 def test_session_list_command(mock_print, mock_session_files_path, create_session_file):
     create_session_file([Message.user("Hello")], mock_session_files_path / "abc.jsonl")
     runner = CliRunner()
@@ -81,6 +362,22 @@ def test_session_list_command(mock_print, mock_session_files_path, create_sessio
     mock_print.assert_called_with(f"{file_time}    abc")
 
 
+def test_session_list_command_filters_by_tag(mock_print, mock_session_files_path, create_session_file):
+    for name, tags in [("first", ["work", "urgent"]), ("second", ["work"]), ("third", [])]:
+        create_session_file([Message.user("Hello")], mock_session_files_path / f"{name}.jsonl")
+        if tags:
+            add_session_tags(mock_session_files_path / f"{name}.jsonl", tags)
+    runner = CliRunner()
+
+    runner.invoke(goose_cli, ["session", "list", "--tag", "work"])
+    listed = [call.args[0] for call in mock_print.call_args_list]
+    assert sorted(line.split("    ", 1)[1] for line in listed) == ["first    #work #urgent", "second    #work"]
+
+    mock_print.reset_mock()
+    runner.invoke(goose_cli, ["session", "list", "--tag", "work", "--tag", "urgent"])
+    assert [call.args[0].split("    ", 1)[1] for call in mock_print.call_args_list] == ["first    #work #urgent"]
+
+
 def test_session_clear_command(mock_session_files_path, create_session_file):
     for index, session_name in enumerate(["first", "second"]):
         create_session_file([Message.user("Hello1")], mock_session_files_path / f"{session_name}.jsonl", time() + index)
@@ -92,6 +389,78 @@ def test_session_clear_command(mock_session_files_path, create_session_file):
     assert session_files[0].stem == "second"
 
 
+def test_session_delete_command_by_name(mock_print, mock_session_files_path, create_session_file):
+    for session_name in ["first", "second"]:
+        create_session_file([Message.user("Hello")], mock_session_files_path / f"{session_name}.jsonl")
+    runner = CliRunner()
+    result = runner.invoke(goose_cli, ["session", "delete", "first"])
+
+    assert result.exit_code == 0
+    assert [f.stem for f in mock_session_files_path.glob("*.jsonl")] == ["second"]
+    mock_print.assert_called_with("Deleted 1 session(s)")
+
+
+def test_session_delete_command_older_than(mock_print, mock_session_files_path, create_session_file):
+    create_session_file([Message.user("Hello")], mock_session_files_path / "old.jsonl", time() - 31 * 24 * 60 * 60)
+    create_session_file([Message.user("Hello")], mock_session_files_path / "recent.jsonl", time() - 60)
+    runner = CliRunner()
+
+    runner.invoke(goose_cli, ["session", "delete", "--older-than", "30d", "--list"])
+    assert len(list(mock_session_files_path.glob("*.jsonl"))) == 2
+    assert mock_print.call_args.args[0].endswith("    old")
+
+    runner.invoke(goose_cli, ["session", "delete", "--older-than", "30d"])
+    assert [f.stem for f in mock_session_files_path.glob("*.jsonl")] == ["recent"]
+    mock_print.assert_called_with("Deleted 1 session(s)")
+
+
+def test_session_delete_command_all_requires_confirmation(mock_session_files_path, create_session_file):
+    for session_name in ["first", "second"]:
+        create_session_file([Message.user("Hello")], mock_session_files_path / f"{session_name}.jsonl")
+    runner = CliRunner()
+
+    runner.invoke(goose_cli, ["session", "delete", "--all"], input="n\n")
+    assert len(list(mock_session_files_path.glob("*.jsonl"))) == 2
+
+    runner.invoke(goose_cli, ["session", "delete", "--all"], input="y\n")
+    assert len(list(mock_session_files_path.glob("*.jsonl"))) == 0
+
+
+def test_session_delete_command_refuses_path_traversal(mock_session_files_path, create_session_file):
+    outside_file = mock_session_files_path.parent / "outside.jsonl"
+    create_session_file([Message.user("Hello")], outside_file)
+    runner = CliRunner()
+    result = runner.invoke(goose_cli, ["session", "delete", "../outside"])
+
+    assert result.exit_code != 0
+    assert outside_file.exists()
+    outside_file.unlink()
+
+
+def test_session_status_command(mock_print, profile_factory):
+    toolkit = MagicMock()
+    toolkit.status.return_value = {"info": "cwd is /tmp/project", "hints": "", "main.py": "print('hello world')"}
+    profile = profile_factory({"toolkits": [{"name": "developer", "requires": {}}]})
+    with (
+        patch("goose.cli.main.load_profile", return_value=profile),
+        patch("goose.cli.main.build_toolkits", return_value=[toolkit]) as mock_build_toolkits,
+    ):
+        runner = CliRunner()
+        result = runner.invoke(goose_cli, ["session", "status", "--profile", "default"])
+
+    assert result.exit_code == 0
+    assert mock_build_toolkits.call_args.args == (profile,)
+    encoder = get_encoding("cl100k_base")
+    info_tokens = len(encoder.encode("cwd is /tmp/project"))
+    file_tokens = len(encoder.encode("print('hello world')"))
+    printed = [call.args[0] for call in mock_print.call_args_list]
+    assert printed == [
+        f"[green]developer[/green]: [bold][cyan]{info_tokens + file_tokens}[/cyan][/bold] tokens",
+        f"    info: {info_tokens} tokens",
+        f"    main.py: {file_tokens} tokens",
+    ]
+
+
 def test_combined_group_option():
     with patch("goose.utils.load_plugins") as mock_load_plugin:
         group_option_name = "--describe-commands"
@@ -131,7 +500,9 @@ def test_combined_group_commands(mock_session):
     mock_session_class, mock_session_instance = mock_session
     runner = CliRunner()
     runner.invoke(cli, ["session", "resume", "session1", "--profile", "default"])
-    mock_session_class.assert_called_once_with(name="session1", profile="default", log_level="INFO")
+    mock_session_class.assert_called_once_with(
+        name="session1", profile="default", log_level="INFO", system_prompt_file=None, append_system=(), theme=None
+    )
     mock_session_instance.run.assert_called_once()
 
 
@@ -142,6 +513,24 @@ def test_version_long_option():
     assert "version" in result.output.lower()
 
 
+def test_cli_loads_dotenv_without_overriding_environment(tmp_path, monkeypatch):
+    (tmp_path / ".git").mkdir()
+    (tmp_path / ".env").write_text("GOOSE_TEST_DOTENV_KEY=from-dotenv\nGOOSE_TEST_DOTENV_SET=from-dotenv\n")
+    (tmp_path / "src").mkdir()
+    monkeypatch.chdir(tmp_path / "src")
+    monkeypatch.setenv("GOOSE_TEST_DOTENV_SET", "from-environment")
+
+    CliRunner().invoke(cli, ["--no-dotenv"])
+    assert "GOOSE_TEST_DOTENV_KEY" not in os.environ
+
+    try:
+        CliRunner().invoke(cli, [])
+        assert os.environ["GOOSE_TEST_DOTENV_KEY"] == "from-dotenv"
+        assert os.environ["GOOSE_TEST_DOTENV_SET"] == "from-environment"
+    finally:
+        os.environ.pop("GOOSE_TEST_DOTENV_KEY", None)
+
+
 def test_version_short_option():
     runner = CliRunner()
     result = runner.invoke(cli, ["-V"])
@@ -173,3 +562,40 @@ def test_moderators_list_command():
     assert "passive" in result.output
     assert "summarize" in result.output
     assert "truncate" in result.output
+
+
+def test_session_replay_command(mock_print, mock_session_files_path, create_session_file):
+    create_session_file([Message.user("Hello"), Message.user("[again]")], mock_session_files_path / "session1.jsonl")
+    runner = CliRunner()
+    with patch("goose.cli.main.time.sleep") as mock_sleep:
+        result = runner.invoke(goose_cli, ["session", "replay", "session1", "--delay", "0.5"])
+
+    assert result.exit_code == 0
+    assert [call.args[0] for call in mock_print.call_args_list] == [
+        "[bold cyan]user[/bold cyan]: Hello",
+        "[bold cyan]user[/bold cyan]: \\[again]",
+    ]
+    mock_sleep.assert_called_once_with(0.5)
+
+
+@pytest.mark.parametrize(
+    "args, message",
+    [
+        (["--delay", "-1"], "Invalid value for '--delay'"),
+        (["--delay", "0.5", "--step"], "can't be used with --delay"),
+    ],
+)
+def test_session_replay_command_rejects_bad_pacing(mock_session_files_path, create_session_file, args, message):
+    create_session_file([Message.user("Hello")], mock_session_files_path / "session1.jsonl")
+    runner = CliRunner()
+
+    result = runner.invoke(goose_cli, ["session", "replay", "session1"] + args)
+
+    assert result.exit_code == 2
+    assert message in result.output
+
+
+def test_session_replay_command_unknown_session(mock_print, mock_session_files_path):
+    runner = CliRunner()
+    runner.invoke(goose_cli, ["session", "replay", "missing"])
+    mock_print.assert_called_once_with("No session found with the name missing.")
@@ -0,0 +1,30 @@
+from exchange import Message, Text, ToolResult, ToolUse
+from goose.cli.replay import render_message, replay_messages
+from rich.markdown import Markdown
+from rich.rule import Rule
+
+
+def test_replay_messages_renders_every_message_in_order():
+    messages = [
+        Message.user("List the files"),
+        Message(role="assistant", content=[Text("Sure."), ToolUse(id="1", name="bash", parameters={"cmd": "ls"})]),
+        Message(role="user", content=[ToolResult(tool_use_id="1", output="a.txt\nb.txt")]),
+        Message.assistant("There are two files."),
+    ]
+    events = []
+
+    replay_messages(messages, render=lambda renderable: events.append(renderable), pause=lambda: events.append("pause"))
+
+    assert events[0] == "[bold cyan]user[/bold cyan]: List the files"
+    assert events[1] == "pause"
+    assert isinstance(events[2], Markdown) and events[2].markup == "Sure."
+    assert isinstance(events[3], Rule) and "bash" in str(events[3].title)
+    assert events[4:7] == ["pause", "[dim]a.txt\nb.txt[/dim]", "pause"]
+    assert len(events) == 8
+    assert isinstance(events[7], Markdown) and events[7].markup == "There are two files."
+
+
+def test_render_message_marks_failed_tool_results_and_skips_blank_text():
+    message = Message(role="user", content=[Text("  "), ToolResult(tool_use_id="1", output="boom", is_error=True)])
+
+    assert render_message(message) == ["[red]boom[/red]"]
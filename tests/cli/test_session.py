@@ -1,16 +1,34 @@
 import os
+import signal
+import time
+import threading
 from datetime import datetime
 from typing import Union
 from unittest.mock import MagicMock, mock_open, patch
 
+import click
 import pytest
-from exchange import Message, ToolResult, ToolUse
+from exchange import Message, Text, Tool, ToolResult, ToolUse
 from exchange.observers import ObserverManager
+from exchange.providers import Usage
 from goose.cli.prompt.goose_prompt_session import GoosePromptSession
 from goose.cli.prompt.overwrite_session_prompt import OverwriteSessionPrompt
 from goose.cli.prompt.user_input import PromptAction, UserInput
-from goose.cli.session import Session
+from goose.cli.session import CONTINUE_MESSAGE, RESUME_MESSAGE, Session
+from goose.synopsis.system import system
+from goose.utils.session_file import (
+    SessionLock,
+    is_existing_session,
+    log_messages,
+    read_audit_records,
+    read_from_file,
+    read_session_tags,
+    session_audit_path,
+    session_lock_path,
+    session_running_path,
+)
 from prompt_toolkit import PromptSession
+from rich.markdown import Markdown
 
 SPECIFIED_SESSION_NAME = "mySession"
 SESSION_NAME = "test"
@@ -218,6 +236,532 @@ def test_existing_session_prompt(
     check_prompt_behavior(is_existing=False, new_session=False, should_prompt=False)
 
 
+def test_run_prints_provider_and_model(create_session_with_mock_configs):
+    session = create_session_with_mock_configs({"name": SESSION_NAME})
+
+    with (
+        patch("goose.cli.session.print") as mock_print,
+        patch("goose.cli.session.is_existing_session", return_value=False),
+        patch.object(session, "process_first_message", return_value=None),
+    ):
+        session.run()
+
+    model = f"{session.profile.provider}/{session.profile.processor}"
+    printed = [str(call.args[0]) for call in mock_print.call_args_list if call.args]
+    assert any(f"model: [cyan]{model}[/cyan]" in line for line in printed)
+
+
+@pytest.fixture
+def terminating_session(mock_sessions_path, exchange_factory, profile_factory):
+    """A session whose first reply calls a tool that sends SIGTERM to the process"""
+
+    def terminate() -> str:
+        """Ask the process to terminate"""
+        os.kill(os.getpid(), signal.SIGTERM)
+        return "unreachable"
+
+    provider = MagicMock()
+    provider.complete.return_value = (
+        Message(role="assistant", content=[ToolUse(id="1", name="terminate", parameters={})]),
+        Usage(input_tokens=10, output_tokens=5, total_tokens=15),
+    )
+    exchange = exchange_factory({"provider": provider, "tools": [Tool.from_function(terminate)]})
+
+    with (
+        patch("goose.cli.session.create_exchange", return_value=exchange),
+        patch("goose.cli.session.load_profile", return_value=profile_factory()),
+        patch("goose.cli.session.SessionNotifier"),
+    ):
+        session = Session(name=SESSION_NAME)
+
+    with (
+        patch("goose.cli.session.print") as mock_print,
+        patch.object(session, "process_first_message", return_value=Message.user("Hello")),
+    ):
+        yield session, mock_print
+
+
+def test_sigterm_during_reply_saves_session(terminating_session):
+    session, _ = terminating_session
+
+    session.run()
+
+    saved = read_from_file(session.session_file_path)
+    assert [message.role for message in saved] == ["user", "assistant", "user", "assistant"]
+    assert saved[1].tool_use[0].name == "terminate"
+    assert saved[2].content[0].is_error
+    assert not session_running_path(session.session_file_path).exists()
+    assert signal.getsignal(signal.SIGTERM) is signal.SIG_DFL
+
+
+def test_run_refuses_session_in_use(terminating_session):
+    session, _ = terminating_session
+    lock = SessionLock(session_lock_path(session.session_file_path))
+    lock.acquire()
+
+    with pytest.raises(click.ClickException) as error:
+        session.run()
+
+    assert "in use by another goose process" in error.value.message
+    assert not session.exchange.messages
+    lock.release()
+
+
+def test_run_warns_when_last_run_did_not_exit_cleanly(terminating_session):
+    session, mock_print = terminating_session
+    session_running_path(session.session_file_path).touch()
+
+    session.run()
+
+    printed = [str(call.args[0]) for call in mock_print.call_args_list if call.args]
+    assert any("did not exit cleanly" in line for line in printed)
+
+
+def test_run_does_not_warn_about_a_lock_file_left_by_a_clean_exit(terminating_session):
+    session, mock_print = terminating_session
+    session_lock_path(session.session_file_path).touch()
+
+    session.run()
+
+    printed = [str(call.args[0]) for call in mock_print.call_args_list if call.args]
+    assert not any("did not exit cleanly" in line for line in printed)
+    # the lock file is kept, so a process waiting on it never locks a file another has replaced
+    assert session_lock_path(session.session_file_path).exists()
+
+
+def test_run_rejects_an_invalid_lock_timeout(terminating_session, monkeypatch):
+    session, _ = terminating_session
+    monkeypatch.setenv("GOOSE_SESSION_LOCK_TIMEOUT", "soon")
+
+    with pytest.raises(click.ClickException) as error:
+        session.run()
+
+    assert "GOOSE_SESSION_LOCK_TIMEOUT must be a number of seconds" in error.value.message
+
+
+@pytest.mark.parametrize("keep_going", [True, False])
+def test_run_offers_to_continue_after_an_unclean_exit(terminating_session, keep_going):
+    session, _ = terminating_session
+    saved = [Message.user("Hello"), Message.assistant("Hi")]
+    log_messages(session.session_file_path, saved)
+    session.exchange.messages.extend(saved)
+    session_running_path(session.session_file_path).touch()
+
+    with (
+        patch("goose.cli.session.Confirm.ask", return_value=keep_going) as mock_ask,
+        patch.object(session, "process_first_message", return_value=None),
+    ):
+        session.run(new_session=False)
+
+    mock_ask.assert_called_once()
+    assert len(session.exchange.messages) == (2 if keep_going else 0)
+    assert is_existing_session(session.session_file_path) == keep_going
+
+
+def test_single_pass_keeps_the_history_after_an_unclean_exit(terminating_session):
+    session, mock_print = terminating_session
+    saved = [Message.user("Hello"), Message.assistant("Hi")]
+    log_messages(session.session_file_path, saved)
+    session.exchange.messages.extend(saved)
+    session_running_path(session.session_file_path).touch()
+
+    with (
+        patch("goose.cli.session.Confirm.ask") as mock_ask,
+        patch.object(session, "_single_pass_replies"),
+    ):
+        session.single_pass(initial_message="Hello")
+
+    mock_ask.assert_not_called()
+    assert len(session.exchange.messages) == 2
+    printed = [str(call.args[0]) for call in mock_print.call_args_list if call.args]
+    assert any("did not exit cleanly" in line for line in printed)
+
+
+def test_sigterm_during_single_pass_saves_session(terminating_session):
+    session, _ = terminating_session
+
+    session.single_pass(initial_message="Hello")
+
+    saved = read_from_file(session.session_file_path)
+    assert [message.role for message in saved] == ["user", "assistant", "user", "assistant"]
+    assert saved[2].content[0].is_error
+    assert not session_running_path(session.session_file_path).exists()
+    assert signal.getsignal(signal.SIGTERM) is signal.SIG_DFL
+
+
+def test_run_restores_the_sigterm_handler_when_the_loop_fails(terminating_session):
+    session, _ = terminating_session
+
+    with patch.object(session, "process_first_message", side_effect=RuntimeError("boom")):
+        with pytest.raises(RuntimeError):
+            session.run()
+
+    assert signal.getsignal(signal.SIGTERM) is signal.SIG_DFL
+
+
+def test_single_pass_stops_at_timeout(mock_sessions_path, exchange_factory, profile_factory):
+    def hang() -> str:
+        """Never finish"""
+        while True:
+            time.sleep(0.01)
+
+    provider = MagicMock()
+    provider.complete.return_value = (
+        Message(role="assistant", content=[ToolUse(id="1", name="hang", parameters={})]),
+        Usage(input_tokens=10, output_tokens=5, total_tokens=15),
+    )
+    exchange = exchange_factory({"provider": provider, "tools": [Tool.from_function(hang)]})
+    with (
+        patch("goose.cli.session.create_exchange", return_value=exchange),
+        patch("goose.cli.session.load_profile", return_value=profile_factory()),
+        patch("goose.cli.session.SessionNotifier"),
+    ):
+        session = Session(name=SESSION_NAME)
+
+    start = time.monotonic()
+    with patch("goose.cli.session.print"), pytest.raises(click.ClickException) as error:
+        session.single_pass(initial_message="Hello", timeout=0.2)
+
+    assert time.monotonic() - start < 5
+    assert error.value.message == "The run timed out after 0.2 seconds."
+    saved = read_from_file(session.session_file_path)
+    assert [message.role for message in saved] == ["user", "assistant", "user", "assistant"]
+    assert saved[2].content[0].is_error
+
+
+def test_single_pass_stops_at_timeout_while_the_provider_blocks(
+    mock_sessions_path, exchange_factory, profile_factory
+):
+    released = threading.Event()
+
+    def block(*args, **kwargs):
+        # waiting on a lock, like a socket read, is not interrupted by the timer
+        released.wait(30)
+        return Message.assistant("too late"), Usage(input_tokens=10, output_tokens=5, total_tokens=15)
+
+    provider = MagicMock()
+    provider.complete.side_effect = block
+    with (
+        patch("goose.cli.session.create_exchange", return_value=exchange_factory({"provider": provider})),
+        patch("goose.cli.session.load_profile", return_value=profile_factory()),
+        patch("goose.cli.session.SessionNotifier"),
+    ):
+        session = Session(name=SESSION_NAME)
+
+    start = time.monotonic()
+    try:
+        with patch("goose.cli.session.print"), pytest.raises(click.ClickException) as error:
+            session.single_pass(initial_message="Hello", timeout=0.2)
+    finally:
+        released.set()
+
+    assert time.monotonic() - start < 5
+    assert error.value.message == "The run timed out after 0.2 seconds."
+    assert not any(message.text == "too late" for message in session.exchange.messages)
+
+
+def test_single_pass_swallows_a_timeout_that_fires_as_the_reply_finishes(
+    mock_sessions_path, exchange_factory, profile_factory
+):
+    with (
+        patch("goose.cli.session.create_exchange", return_value=exchange_factory()),
+        patch("goose.cli.session.load_profile", return_value=profile_factory()),
+        patch("goose.cli.session.SessionNotifier"),
+    ):
+        session = Session(name=SESSION_NAME)
+
+    def finish_as_the_timer_fires(initial_message: str) -> None:
+        session._handle_timeout()
+        time.sleep(0.1)
+
+    with (
+        patch.object(session, "_single_pass_replies", side_effect=finish_as_the_timer_fires),
+        patch("goose.cli.session.print"),
+        pytest.raises(click.ClickException) as error,
+    ):
+        session.single_pass(initial_message="Hello", timeout=60)
+
+    assert error.value.message == "The run timed out after 60 seconds."
+
+
+@pytest.fixture
+def resumed_session(mock_sessions_path, create_session_file, exchange_factory, profile_factory):
+    """Resume a saved session whose provider always answers with "Done" """
+
+    def create_session(messages: list[Message]):
+        create_session_file(messages, mock_sessions_path / f"{SESSION_NAME}.jsonl")
+        provider = MagicMock()
+        provider.complete.return_value = (Message.assistant("Done"), Usage(10, 5, 15))
+        with (
+            patch("goose.cli.session.create_exchange", return_value=exchange_factory({"provider": provider})),
+            patch("goose.cli.session.load_profile", return_value=profile_factory()),
+            patch("goose.cli.session.SessionNotifier"),
+        ):
+            return Session(name=SESSION_NAME)
+
+    with patch("goose.cli.session.print"):
+        yield create_session
+
+
+def test_single_pass_resume_with_new_instruction(resumed_session):
+    session = resumed_session([Message.user("Write a test"), Message.assistant("Written")])
+
+    session.single_pass(initial_message="Now run it")
+
+    assert [message.text for message in session.exchange.messages] == ["Write a test", "Written", "Now run it", "Done"]
+    saved = read_from_file(session.session_file_path)
+    assert [message.text for message in saved] == ["Write a test", "Written", "Now run it", "Done"]
+
+
+def test_single_pass_returns_final_answer(resumed_session):
+    session = resumed_session([Message.user("Write a test"), Message.assistant("Written")])
+
+    assert session.single_pass(initial_message="Now run it") == "Done"
+
+
+def test_profile_overrides_are_applied(mock_sessions_path, exchange_factory, profile_factory):
+    with (
+        patch("goose.cli.session.create_exchange", return_value=exchange_factory()) as mock_create_exchange,
+        patch("goose.cli.session.load_profile", return_value=profile_factory()),
+        patch("goose.cli.session.SessionNotifier"),
+    ):
+        session = Session(name=SESSION_NAME, profile_overrides={"processor": "gpt-4o-mini", "toolkits": ["developer"]})
+
+    assert session.profile.processor == "gpt-4o-mini"
+    assert [toolkit.name for toolkit in session.profile.toolkits] == ["developer"]
+    assert mock_create_exchange.call_args.kwargs["profile"] == session.profile
+
+
+def test_session_runs_without_saving_when_sessions_directory_is_unwritable(
+    tmp_path, exchange_factory, profile_factory
+):
+    # a file where the sessions directory should be makes it impossible to create
+    (tmp_path / "config").write_text("")
+    provider = MagicMock()
+    provider.complete.return_value = (Message.assistant("Done"), Usage(10, 5, 15))
+    with (
+        patch("goose.cli.config.SESSIONS_PATH", tmp_path / "config" / "sessions"),
+        patch("goose.cli.session.create_exchange", return_value=exchange_factory({"provider": provider})),
+        patch("goose.cli.session.load_profile", return_value=profile_factory()),
+        patch("goose.cli.session.SessionNotifier"),
+        patch("goose.cli.session.print") as mock_print,
+    ):
+        session = Session(name=SESSION_NAME)
+        assert session.single_pass(initial_message="Hello") == "Done"
+        assert session.single_pass(initial_message="Hello again") == "Done"
+
+    assert not session.persist
+    assert [message.text for message in session.exchange.messages] == ["Hello", "Done", "Hello again", "Done"]
+    printed = [str(call.args[0]) for call in mock_print.call_args_list if call.args]
+    assert sum("won't be saved" in line for line in printed) == 1
+
+
+def test_single_pass_resume_of_interrupted_run(resumed_session):
+    session = resumed_session(
+        [
+            Message.user("Write a test"),
+            Message(role="assistant", content=[ToolUse(id="1", name="write_file", parameters={})]),
+        ]
+    )
+
+    session.single_pass(initial_message="Write a test\n")
+
+    texts = [message.text for message in session.exchange.messages]
+    assert texts == ["Write a test", RESUME_MESSAGE, CONTINUE_MESSAGE, "Done"]
+
+
+def test_reply_records_tool_calls_in_audit_file(mock_sessions_path, exchange_factory, profile_factory):
+    def slow_tool(path: str) -> str:
+        """Take a moment to look at a path
+
+        Args:
+            path (str): The path to look at
+        """
+        time.sleep(0.01)
+        return "looked"
+
+    tool_use = ToolUse(id="1", name="slow_tool", parameters={"path": "a"})
+    provider = MagicMock()
+    provider.complete.side_effect = [
+        (Message(role="assistant", content=[tool_use]), Usage(1, 1, 2)),
+        (Message.assistant("Done"), Usage(1, 1, 2)),
+    ]
+    exchange = exchange_factory({"provider": provider, "tools": [Tool.from_function(slow_tool)]})
+    with (
+        patch("goose.cli.session.create_exchange", return_value=exchange),
+        patch("goose.cli.session.load_profile", return_value=profile_factory()),
+        patch("goose.cli.session.SessionNotifier"),
+        patch("goose.cli.session.print"),
+    ):
+        session = Session(name=SESSION_NAME)
+        session.exchange.add(Message.user("Look at a"))
+        session.reply()
+
+    records = read_audit_records(session_audit_path(session.session_file_path))
+    assert len(records) == 1
+    assert records[0]["tool"] == "slow_tool"
+    assert records[0]["success"] is True
+    assert records[0]["duration"] >= 0.01
+    assert len(records[0]["args_hash"]) == 16
+
+
+def test_reply_continues_after_cancelling_one_tool(mock_sessions_path, exchange_factory, profile_factory):
+    def stuck_tool() -> str:
+        """A tool the user interrupts with ctrl-c"""
+        raise KeyboardInterrupt
+
+    def quick_tool() -> str:
+        """A tool that finishes"""
+        return "done"
+
+    tool_uses = [ToolUse(id="1", name="stuck_tool", parameters={}), ToolUse(id="2", name="quick_tool", parameters={})]
+    provider = MagicMock()
+    provider.complete.side_effect = [
+        (Message(role="assistant", content=tool_uses), Usage(1, 1, 2)),
+        (Message.assistant("Done"), Usage(1, 1, 2)),
+    ]
+    tools = [Tool.from_function(stuck_tool), Tool.from_function(quick_tool)]
+    exchange = exchange_factory({"provider": provider, "tools": tools})
+    with (
+        patch("goose.cli.session.create_exchange", return_value=exchange),
+        patch("goose.cli.session.load_profile", return_value=profile_factory()),
+        patch("goose.cli.session.SessionNotifier"),
+        patch("goose.cli.session.print") as mock_print,
+    ):
+        session = Session(name=SESSION_NAME)
+        session.exchange.add(Message.user("Run both"))
+        session.reply()
+
+    results = session.exchange.messages[-2].content
+    assert results[0].is_error and "cancelled" in results[0].output
+    assert results[1].output == '"done"' and not results[1].is_error
+    assert session.exchange.messages[-1].text == "Done"
+    printed = [str(call.args[0]) for call in mock_print.call_args_list if call.args]
+    assert any("Cancelled the call to stuck_tool" in line for line in printed)
+
+
+def test_context_trimming_is_reported(mock_sessions_path, exchange_factory, profile_factory):
+    with (
+        patch("goose.cli.session.create_exchange", return_value=exchange_factory()),
+        patch("goose.cli.session.load_profile", return_value=profile_factory()),
+        patch("goose.cli.session.SessionNotifier"),
+        patch("goose.cli.session.print") as mock_print,
+    ):
+        session = Session(name=SESSION_NAME)
+        session.exchange.report_trimmed(4)
+
+    mock_print.assert_called_once_with("[dim yellow]⚠ context trimmed, dropped 4 older messages[/]")
+
+
+def test_profile_command_switches_the_provider_for_the_next_completion(
+    mock_sessions_path, exchange_factory, profile_factory
+):
+    first_provider, second_provider = MagicMock(), MagicMock()
+    first_provider.complete.return_value = (Message.assistant("From the first"), Usage(1, 1, 2))
+    second_provider.complete.return_value = (Message.assistant("From the second"), Usage(1, 1, 2))
+    profiles = {
+        "default": profile_factory(),
+        "fast": profile_factory({"provider": "other_provider", "processor": "fast_processor"}),
+    }
+    inputs = [
+        UserInput(action=PromptAction.CONTINUE, text="/profile missing"),
+        UserInput(action=PromptAction.CONTINUE, text="/profile fast"),
+        UserInput(action=PromptAction.CONTINUE, text="Hello again"),
+    ]
+    with (
+        patch("goose.cli.session.create_exchange", return_value=exchange_factory({"provider": first_provider})),
+        patch("goose.cli.session.load_profile", return_value=profiles["default"]),
+        patch("goose.cli.session.SessionNotifier"),
+        patch("goose.cli.session.read_config", return_value=profiles),
+        patch("goose.cli.session.create_provider", return_value=second_provider) as mock_create_provider,
+        patch.object(GoosePromptSession, "get_user_input", side_effect=inputs),
+        patch("goose.cli.session.print") as mock_print,
+    ):
+        session = Session(name=SESSION_NAME)
+        session.exchange.add(Message.user("Hello"))
+        session.exchange.generate()
+
+        message = session.prompt_user_message()
+        session.exchange.add(message)
+        session.exchange.generate()
+
+    assert message.text == "Hello again"
+    mock_create_provider.assert_called_once_with(profiles["fast"])
+    assert second_provider.complete.call_args.args[0] == "fast_processor"
+    assert [m.text for m in session.exchange.messages] == ["Hello", "From the first", "Hello again", "From the second"]
+    assert session.profile_name == "fast"
+    printed = [str(call.args[0]) for call in mock_print.call_args_list if call.args]
+    assert any("there is no profile named missing" in line for line in printed)
+
+
+def test_tag_command_saves_tags_that_survive_resume(mock_sessions_path, exchange_factory, profile_factory):
+    with (
+        patch("goose.cli.session.create_exchange", side_effect=lambda **_: exchange_factory()),
+        patch("goose.cli.session.load_profile", return_value=profile_factory()),
+        patch("goose.cli.session.SessionNotifier"),
+        patch("goose.cli.session.print") as mock_print,
+    ):
+        session = Session(name=SESSION_NAME)
+        assert session.run_session_command("/tag work urgent")
+        assert session.run_session_command("/tag #work review")
+
+        resumed = Session(name=SESSION_NAME)
+        resumed.run_session_command("/tag")
+
+    assert read_session_tags(resumed.session_file_path) == ["work", "urgent", "review"]
+    mock_print.assert_called_with("[dim]tags: #work #urgent #review[/]")
+
+
+def test_reply_does_not_print_whitespace_text(mock_sessions_path, exchange_factory, profile_factory):
+    tool_use = ToolUse(id="1", name="noop", parameters={})
+    provider = MagicMock()
+    provider.complete.side_effect = [
+        (Message(role="assistant", content=[Text("  \n"), tool_use]), Usage(1, 1, 2)),
+        (Message.assistant("Done"), Usage(1, 1, 2)),
+    ]
+
+    def noop() -> str:
+        """Do nothing"""
+        return "ok"
+
+    exchange = exchange_factory({"provider": provider, "tools": [Tool.from_function(noop)]})
+    with (
+        patch("goose.cli.session.create_exchange", return_value=exchange),
+        patch("goose.cli.session.load_profile", return_value=profile_factory()),
+        patch("goose.cli.session.SessionNotifier"),
+        patch("goose.cli.session.print") as mock_print,
+    ):
+        session = Session(name=SESSION_NAME)
+        session.exchange.add(Message.user("Go"))
+        session.reply()
+
+    rendered = [call.args[0].markup for call in mock_print.call_args_list if isinstance(call.args[0], Markdown)]
+    assert rendered == ["Done"]
+
+
+def test_load_context_files_makes_them_active(create_session_with_mock_configs, tmp_path):
+    notes = tmp_path / "notes.md"
+    notes.write_text("# Notes")
+    session = create_session_with_mock_configs({"name": SESSION_NAME})
+
+    with patch("goose.cli.session.print"):
+        session.load_context_files([str(notes)])
+
+    assert system.is_active(str(notes))
+    assert "# Notes" in [file.content for file in system.active_files]
+    system.forget_file(str(notes))
+
+
+def test_load_context_files_rejects_large_files(create_session_with_mock_configs, tmp_path):
+    large = tmp_path / "large.txt"
+    large.write_text("x" * (2**20 + 1))
+    session = create_session_with_mock_configs({"name": SESSION_NAME})
+
+    with pytest.raises(click.ClickException) as error:
+        session.load_context_files([str(large)])
+
+    assert "too large" in error.value.message
+
 def test_prompt_overwrite_session(session_factory):
     def check_overwrite_behavior(choice: str, expected_messages: list[Message]) -> None:
         session = session_factory()
@@ -0,0 +1,103 @@
+from unittest.mock import MagicMock, patch
+
+from exchange import Exchange, Message
+from goose.build import build_exchange, build_system_prompt, switch_provider
+from goose.toolkit.base import Toolkit, read_only, tool
+
+
+def test_build_system_prompt_default(profile_factory):
+    profile = profile_factory({"toolkits": [{"name": "developer", "requires": {}}]})
+    assert "You are an AI assistant named Goose" in build_system_prompt(profile)
+
+
+def test_build_system_prompt_uses_profile_name(profile_factory):
+    profile = profile_factory({"name": "Nova"})
+
+    system_prompt = build_system_prompt(profile)
+
+    assert "You are an AI assistant named Nova" in system_prompt
+    assert "named Goose" not in system_prompt
+
+
+def test_build_system_prompt_from_file(profile_factory, tmp_path):
+    system_prompt_file = tmp_path / "system.jinja"
+    system_prompt_file.write_text("You are a custom assistant with {{ toolkits | join(', ') }}.")
+    profile = profile_factory({"toolkits": [{"name": "developer", "requires": {}}, {"name": "memory", "requires": {}}]})
+
+    system_prompt = build_system_prompt(profile, system_prompt_file)
+
+    assert system_prompt == "You are a custom assistant with developer, memory."
+
+
+def test_build_system_prompt_appends_instructions_in_order(profile_factory):
+    profile = profile_factory({"toolkits": [{"name": "developer", "requires": {}}]})
+
+    system_prompt = build_system_prompt(profile, append_system=["use 2-space indent", "never use emoji"])
+
+    expected = "Always follow these instructions:\n- use 2-space indent\n- never use emoji"
+    assert system_prompt.startswith("You are an AI assistant named Goose")
+    assert system_prompt.endswith(expected)
+
+
+def test_build_exchange_uses_profile_generation_args(profile_factory):
+    profile = profile_factory(
+        {"provider": "openai", "moderator": "passive", "generation_args": {"stop": ["END"], "top_p": 0.5}}
+    )
+
+    with patch("goose.build.get_provider") as mock_get_provider:
+        exchange = build_exchange(profile, notifier=MagicMock())
+
+    assert exchange.provider == mock_get_provider.return_value.from_env.return_value
+    assert exchange.generation_args == {"stop": ["END"], "top_p": 0.5}
+
+
+def test_build_exchange_reads_completion_timeout(profile_factory, monkeypatch):
+    profile = profile_factory({"provider": "openai", "moderator": "passive"})
+
+    with patch("goose.build.get_provider"):
+        assert build_exchange(profile, notifier=MagicMock()).completion_timeout is None
+        monkeypatch.setenv("GOOSE_COMPLETION_TIMEOUT", "90")
+        assert build_exchange(profile, notifier=MagicMock()).completion_timeout == 90.0
+
+
+class EchoToolkit(Toolkit):
+    @tool
+    def echo(self, text: str) -> str:
+        """Echo the text back
+
+        Args:
+            text (str): The text to echo
+        """
+        return text
+
+
+def test_switch_provider_keeps_messages_and_points_toolkits_at_the_copy(profile_factory):
+    toolkit = EchoToolkit(notifier=MagicMock())
+    exchange = Exchange(provider=MagicMock(), model="processor", system="system", tools=toolkit.tools())
+    exchange.add(Message.user("Hello"))
+    other = profile_factory({"processor": "other_processor", "accelerator": "other_accelerator"})
+
+    switched = switch_provider(exchange, other, provider=MagicMock())
+
+    assert switched.model == "other_processor"
+    assert [message.text for message in switched.messages] == ["Hello"]
+    assert toolkit.exchange_view.accelerator.model == "other_accelerator"
+    assert toolkit.exchange_view.accelerator.provider is switched.provider
+
+
+def test_toolkit_tools_carry_the_read_only_mark():
+    class ReadingToolkit(EchoToolkit):
+        @tool
+        @read_only
+        def peek(self, path: str) -> str:
+            """Look at a file
+
+            Args:
+                path (str): The file to look at
+            """
+            return path
+
+    tools = {tool.name: tool for tool in ReadingToolkit(notifier=MagicMock()).tools()}
+
+    assert tools["peek"].read_only
+    assert not tools["echo"].read_only
@@ -1,6 +1,9 @@
-from unittest.mock import MagicMock
+from datetime import datetime
+from unittest.mock import MagicMock, patch
 import pytest
+from exchange import ToolError, ToolErrorCategory
 from goose.toolkit.memory import Memory
+from rich.rule import Rule
 
 
 @pytest.fixture
@@ -109,6 +112,14 @@ def test_invalid_category_name(memory_toolkit):
     assert "test_category_" in files[0].name
 
 
+def test_invalid_scope(memory_toolkit):
+    """Test that an unknown scope is reported as invalid arguments"""
+    with pytest.raises(ToolError) as error:
+        memory_toolkit.remember("Test memory", "test_category", scope="team")
+
+    assert error.value.category == ToolErrorCategory.INVALID_ARGS
+
+
 def test_system_prompt_includes_memories(memory_toolkit):
     """Test that the system prompt includes existing memories"""
     # Add some test memories
@@ -141,3 +152,47 @@ def test_system_prompt_empty_memories(memory_toolkit):
 
     # Check that empty memory state is handled
     assert "No existing memories found" in system_prompt
+
+
+def test_remember_logs_category_and_scope_header(memory_toolkit):
+    memory_toolkit.remember("Test memory", "test_category", scope="local")
+
+    headers = [call.args[0] for call in memory_toolkit.notifier.log.call_args_list if isinstance(call.args[0], Rule)]
+    assert len(headers) == 1
+    assert "remember" in headers[0].title
+    assert "test_category (local)" in headers[0].title
+
+
+def test_search_since(memory_toolkit):
+    with patch("goose.toolkit.memory.now", return_value=datetime(2024, 1, 10, 9, 0)):
+        memory_toolkit.remember("Old deploy notes", "notes", scope="local")
+    with patch("goose.toolkit.memory.now", return_value=datetime(2024, 3, 1, 9, 0)):
+        memory_toolkit.remember("New deploy notes", "notes", scope="local")
+
+    result = memory_toolkit.search("deploy", scope="local", since="2024-02-01")
+    assert "New deploy notes" in result
+    assert "Old deploy notes" not in result
+
+    with patch("goose.toolkit.memory.now", return_value=datetime(2024, 3, 5, 9, 0)):
+        result = memory_toolkit.search("deploy", scope="local", since="7d")
+    assert "New deploy notes" in result
+    assert "Old deploy notes" not in result
+
+
+def test_search_since_skips_memories_without_timestamps(memory_toolkit):
+    memory_file = memory_toolkit.local_memory_dir / "notes.txt"
+    memory_file.write_text("#legacy\nLegacy deploy notes\n")
+    with patch("goose.toolkit.memory.now", return_value=datetime(2024, 3, 1, 9, 0)):
+        memory_toolkit.remember("New deploy notes", "notes", scope="local")
+
+    assert "Legacy deploy notes" in memory_toolkit.search("deploy", scope="local")
+    result = memory_toolkit.search("deploy", scope="local", since="2024-02-01")
+    assert "New deploy notes" in result
+    assert "Legacy deploy notes" not in result
+    assert "@2024-03-01T09:00:00" in memory_file.read_text()
+
+
+def test_search_since_invalid(memory_toolkit):
+    with pytest.raises(ToolError) as error:
+        memory_toolkit.search("deploy", since="last week")
+    assert error.value.category == ToolErrorCategory.INVALID_ARGS
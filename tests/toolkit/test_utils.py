@@ -1,4 +1,7 @@
-from goose.toolkit.utils import parse_plan
+import pytest
+from jinja2 import UndefinedError
+
+from goose.toolkit.utils import parse_plan, preview, render_template_string
 
 
 def test_parse_plan_simple():
@@ -63,3 +66,20 @@ def test_parse_plan_with_numbers():
         "tasks": ["1 Open a file", "2 Run a test"],
     }
     assert expected_result == parse_plan(plan_str)
+
+
+def test_preview_truncates_long_content():
+    content = "\n".join(f"line {i}" for i in range(25))
+    assert preview(content, max_lines=3) == "line 0\nline 1\nline 2\n... 22 more lines"
+    assert preview("short") == "short"
+
+
+def test_render_template_string():
+    template = "Work in {{ cwd }}{% for tool in tools %}, use {{ tool }}{% endfor %}"
+    context = {"cwd": "/repo", "tools": ["shell", "patch"]}
+    assert render_template_string(template, context) == "Work in /repo, use shell, use patch"
+
+
+def test_render_template_string_missing_variable():
+    with pytest.raises(UndefinedError):
+        render_template_string("Work in {{ cwd }}", {})
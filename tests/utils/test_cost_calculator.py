@@ -74,3 +74,13 @@ def test_get_total_cost_message_with_non_available_pricing(mock_prices, start_ti
         + "2024-10-20T01:02:03+00:00 - 2024-10-21T02:03:04+00:00 | Session name: test_session | Total cost: $2.85"
     )
     assert message == expected_message
+
+
+def test_get_total_cost_message_with_payload_sizes(mock_prices, start_time, end_time):
+    usage = Usage(input_tokens=10000, output_tokens=600, total_tokens=10600, request_bytes=2048, response_bytes=512)
+    message = get_total_cost_message({"gpt-4o": usage}, SESSION_NAME, start_time, end_time)
+
+    assert message.splitlines()[0] == (
+        "Session name: test_session | Cost for model gpt-4o Usage(input_tokens=10000, output_tokens=600,"
+        " total_tokens=10600): $0.06 | sent 2048 bytes, received 512 bytes"
+    )
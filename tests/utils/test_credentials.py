@@ -0,0 +1,47 @@
+import stat
+from unittest.mock import patch
+
+from keyring.errors import NoKeyringError
+from goose.utils.credentials import (
+    FileCredentialStore,
+    KeyringCredentialStore,
+    credential_stores,
+    load_credential,
+    save_credential,
+)
+
+
+def test_file_credential_store_round_trip(tmp_path):
+    store = FileCredentialStore(tmp_path / "credentials.yaml")
+
+    assert store.get("OPENAI_API_KEY") is None
+    store.set("OPENAI_API_KEY", "sk-secret")
+    store.set("GROQ_API_KEY", "gsk-secret")
+
+    assert FileCredentialStore(tmp_path / "credentials.yaml").get("OPENAI_API_KEY") == "sk-secret"
+    assert store.get("GROQ_API_KEY") == "gsk-secret"
+    assert stat.S_IMODE((tmp_path / "credentials.yaml").stat().st_mode) == 0o600
+
+
+def test_keyring_credential_store_unavailable_returns_none():
+    with patch("keyring.get_password", side_effect=NoKeyringError("no backend")):
+        assert KeyringCredentialStore().get("OPENAI_API_KEY") is None
+
+
+def test_save_credential_falls_back_to_file_without_keychain(tmp_path):
+    stores = credential_stores(tmp_path / "credentials.yaml")
+
+    with patch("keyring.set_password", side_effect=NoKeyringError("no backend")):
+        store = save_credential(stores, "OPENAI_API_KEY", "sk-secret")
+
+    assert store is stores[1]
+    with patch("keyring.get_password", side_effect=NoKeyringError("no backend")):
+        assert load_credential(stores, "OPENAI_API_KEY") == ("sk-secret", stores[1])
+
+
+def test_load_credential_prefers_keychain(tmp_path):
+    stores = credential_stores(tmp_path / "credentials.yaml")
+    stores[1].set("OPENAI_API_KEY", "from-file")
+
+    with patch("keyring.get_password", return_value="from-keychain"):
+        assert load_credential(stores, "OPENAI_API_KEY") == ("from-keychain", stores[0])
@@ -0,0 +1,38 @@
+from goose.utils.goosehints import fetch_goosehints, goosehints_paths
+
+
+def test_goosehints_merge_from_global_to_cwd(tmp_path, monkeypatch):
+    home = tmp_path / "home"
+    (home / ".config/goose").mkdir(parents=True)
+    (home / ".config/goose/.goosehints").write_text("global hints")
+    monkeypatch.setenv("HOME", str(home))
+
+    repo = tmp_path / "repo"
+    nested = repo / "packages" / "app"
+    nested.mkdir(parents=True)
+    (repo / ".git").mkdir()
+    (tmp_path / ".goosehints").write_text("outside the repo")
+    (repo / ".goosehints").write_text("repo hints")
+    (nested / ".goosehints").write_text("app hints")
+    monkeypatch.chdir(nested)
+
+    assert goosehints_paths() == [
+        home / ".config/goose/.goosehints",
+        repo / ".goosehints",
+        nested / ".goosehints",
+    ]
+    assert fetch_goosehints() == (
+        f"Hints from {home / '.config/goose/.goosehints'}:\nglobal hints\n\n"
+        f"Hints from {repo / '.goosehints'}:\nrepo hints\n\n"
+        f"Hints from {nested / '.goosehints'}:\napp hints"
+    )
+
+
+def test_goosehints_without_repo_walks_to_root(tmp_path, monkeypatch):
+    monkeypatch.setenv("HOME", str(tmp_path / "home"))
+    nested = tmp_path / "a" / "b"
+    nested.mkdir(parents=True)
+    (tmp_path / ".goosehints").write_text("top")
+    (nested / ".goosehints").write_text("bottom")
+
+    assert goosehints_paths(nested) == [tmp_path / ".goosehints", nested / ".goosehints"]
@@ -1,14 +1,24 @@
 import os
+import time
 from pathlib import Path
 from unittest.mock import patch
 
 import pytest
+from exchange import Message, Text
 from goose.utils.session_file import (
+    SessionInUseError,
+    SessionLock,
     is_empty_session,
     list_sorted_session_files,
+    log_messages,
     read_from_file,
     read_or_create_file,
+    remove_session_files,
+    session_audit_path,
     session_file_exists,
+    session_lock_path,
+    session_running_path,
+    session_tags_path,
 )
 
 
@@ -28,6 +38,19 @@ def test_read_from_file_non_jsonl_file(file_path):
         read_from_file(file_path)
 
 
+def test_read_from_file_keeps_message_timestamps(file_path):
+    messages = [
+        Message(role="user", created=1700000000, content=[Text("hello")]),
+        Message(role="assistant", created=1700000042, content=[Text("hi")]),
+    ]
+    log_messages(file_path, messages)
+
+    loaded = read_from_file(file_path)
+
+    assert [message.created for message in loaded] == [1700000000, 1700000042]
+    assert [message.id for message in loaded] == [message.id for message in messages]
+
+
 def test_read_or_create_file_when_file_not_exist(tmp_path):
     file_path = tmp_path / "no_existing.json"
 
@@ -49,6 +72,14 @@ def test_list_sorted_session_files(tmp_path):
     }
 
 
+def test_list_sorted_session_files_skips_audit_files(tmp_path):
+    session_file = create_session_file(tmp_path, "session")
+    session_audit_path(session_file).write_text("{}\n")
+
+    assert session_audit_path(session_file) == tmp_path / "session.audit.jsonl"
+    assert list_sorted_session_files(tmp_path) == {"session": session_file}
+
+
 def test_list_sorted_session_without_session_files(tmp_path):
     session_files_directory = tmp_path / "session_files_dir"
 
@@ -97,3 +128,49 @@ def test_is_not_empty_session(mock_stat, mock_is_file):
 @patch("pathlib.Path.is_file", return_value=False, name="mock_is_file")
 def test_is_not_empty_session_file_not_found(mock_is_file):
     assert not is_empty_session(Path("file_not_found.json"))
+
+
+def test_remove_session_files_removes_every_sidecar(tmp_path):
+    session = tmp_path / "session.jsonl"
+    other = tmp_path / "other.jsonl"
+    paths = [session, session_lock_path(session), session_running_path(session), session_audit_path(session)]
+    for path in paths + [session_tags_path(session), other]:
+        path.write_text("")
+
+    remove_session_files(session)
+
+    assert list(tmp_path.iterdir()) == [other]
+
+
+def test_remove_session_files_without_sidecars(tmp_path):
+    session = tmp_path / "session.jsonl"
+    session.write_text("")
+
+    remove_session_files(session)
+
+    assert not session.exists()
+
+
+def test_session_lock_refuses_second_holder(tmp_path):
+    lock_path = tmp_path / "session.lock"
+    first, second = SessionLock(lock_path), SessionLock(lock_path)
+    first.acquire()
+
+    with pytest.raises(SessionInUseError):
+        second.acquire()
+
+    first.release()
+    second.acquire()
+    second.release()
+
+
+def test_session_lock_waits_for_timeout(tmp_path):
+    lock_path = tmp_path / "session.lock"
+    first = SessionLock(lock_path)
+    first.acquire()
+
+    start = time.monotonic()
+    with pytest.raises(SessionInUseError):
+        SessionLock(lock_path).acquire(timeout=0.3)
+    assert time.monotonic() - start >= 0.3
+    first.release()
@@ -0,0 +1,37 @@
+from unittest.mock import MagicMock, patch
+
+import pytest
+from goose.cli.session_notifier import SessionNotifier
+from goose.utils.theme import available_themes, check_theme
+from rich.markdown import Markdown
+
+
+def test_check_theme_valid():
+    assert "monokai" in available_themes()
+    assert check_theme("github-dark") == "github-dark"
+
+
+def test_check_theme_unknown():
+    with pytest.raises(ValueError) as error:
+        check_theme("Monokai Extended")
+
+    assert str(error.value).startswith("Unknown theme 'Monokai Extended'. Available themes: ")
+    assert "monokai" in str(error.value)
+
+
+def test_profile_rejects_unknown_theme(profile_factory):
+    assert profile_factory().theme == "monokai"
+    assert profile_factory({"theme": "zenburn"}).theme == "zenburn"
+    with pytest.raises(ValueError):
+        profile_factory({"theme": "nope"})
+
+
+def test_session_notifier_highlights_code_with_theme():
+    notifier = SessionNotifier(MagicMock(), theme="zenburn")
+    markdown = Markdown("```python\nprint('hi')\n```")
+
+    with patch("goose.cli.session_notifier.print") as mock_print:
+        notifier.log(markdown)
+
+    mock_print.assert_called_once_with(markdown)
+    assert markdown.code_theme == "zenburn"